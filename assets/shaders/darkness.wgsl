@@ -0,0 +1,18 @@
+// Darkens everything but a soft circle of light around the snake's head.
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct Darkness {
+    center: vec2<f32>,
+    radius: f32,
+    softness: f32,
+    color: vec4<f32>,
+}
+
+@group(2) @binding(0) var<uniform> darkness: Darkness;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = length(mesh.world_position.xy - darkness.center);
+    let shade = smoothstep(darkness.radius - darkness.softness, darkness.radius, distance);
+    return vec4<f32>(darkness.color.rgb, darkness.color.a * shade);
+}
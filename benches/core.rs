@@ -0,0 +1,70 @@
+use bevy::math::{bounding::Aabb2d, vec2, Vec2};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, SeedableRng};
+use snake_bevy::sim;
+
+const SNAKE_LENGTHS: [usize; 3] = [10, 1_000, 10_000];
+const SEGMENT_HALF_SIZE: Vec2 = vec2(10.0, 10.0);
+const WALL_THICKNESS: f32 = 10.0;
+
+// a straight vertical snake; long ones extend past the arena, which the core doesn't mind
+fn snake_body(length: usize) -> Vec<Vec2> {
+    (0..length)
+        .map(|index| vec2(20.0, 20.0 - 20.0 * index as f32))
+        .collect()
+}
+
+fn movement_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movement_step");
+    for length in SNAKE_LENGTHS {
+        let mut body = snake_body(length);
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            b.iter(|| sim::follow_head(black_box(&mut body), black_box(vec2(20.0, 40.0))));
+        });
+    }
+    group.finish();
+}
+
+fn collision_detection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collision_detection");
+    // the head sits just past the tail, so every collider has to be checked
+    for length in SNAKE_LENGTHS {
+        let colliders: Vec<Aabb2d> = snake_body(length)
+            .into_iter()
+            .map(|position| Aabb2d::new(position, SEGMENT_HALF_SIZE))
+            .collect();
+        let head = Aabb2d::new(vec2(20.0, 60.0), SEGMENT_HALF_SIZE);
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            b.iter(|| sim::first_collision(black_box(head), black_box(&colliders)));
+        });
+    }
+    group.finish();
+}
+
+fn apple_spawn_sampling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apple_spawn_sampling");
+    for length in SNAKE_LENGTHS {
+        let body = snake_body(length);
+        let mut rng = StdRng::seed_from_u64(42);
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            b.iter(|| {
+                sim::free_apple_position(
+                    &mut rng,
+                    black_box(&body),
+                    SEGMENT_HALF_SIZE,
+                    WALL_THICKNESS,
+                    64,
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    movement_step,
+    collision_detection,
+    apple_spawn_sampling
+);
+criterion_main!(benches);
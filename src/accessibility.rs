@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{
+    display_final_score, settings::GameSettings, toast::Toast, GameState, OnGameOverScreen,
+};
+
+const SPEED_STEP: f32 = 0.25;
+const MIN_GAME_SPEED: f32 = 0.5;
+const MAX_GAME_SPEED: f32 = 1.5;
+
+/// F9 steps the global game speed between 0.5x and 1.5x. It scales the snake's
+/// tick on top of whatever else sets it, and runs played at anything but 1x
+/// are flagged on shared leaderboards.
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSpeed>()
+            .add_systems(Update, cycle_game_speed)
+            .add_systems(Update, track_run_speed.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_speed_flag.after(display_final_score),
+            )
+            .add_systems(OnExit(GameState::GameOver), reset_run_speed);
+    }
+}
+
+/// Slowest and fastest game speed used during the current run.
+#[derive(Resource)]
+pub struct RunSpeed {
+    slowest: f32,
+    fastest: f32,
+}
+
+impl Default for RunSpeed {
+    fn default() -> Self {
+        RunSpeed {
+            slowest: 1.0,
+            fastest: 1.0,
+        }
+    }
+}
+
+impl RunSpeed {
+    pub fn adjusted(&self) -> bool {
+        self.slowest != 1.0 || self.fastest != 1.0
+    }
+
+    pub fn range(&self) -> (f32, f32) {
+        (self.slowest, self.fastest)
+    }
+}
+
+fn cycle_game_speed(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let speed = settings.accessibility.game_speed + SPEED_STEP;
+    settings.accessibility.game_speed = if speed > MAX_GAME_SPEED + f32::EPSILON {
+        MIN_GAME_SPEED
+    } else {
+        speed
+    };
+    toasts.send(Toast(format!(
+        "Game speed {:.2}x",
+        settings.accessibility.game_speed
+    )));
+}
+
+fn track_run_speed(settings: Res<GameSettings>, mut speed: ResMut<RunSpeed>) {
+    let game_speed = settings.accessibility.game_speed;
+    if game_speed < speed.slowest {
+        speed.slowest = game_speed;
+    }
+    if game_speed > speed.fastest {
+        speed.fastest = game_speed;
+    }
+}
+
+fn display_speed_flag(mut commands: Commands, speed: Res<RunSpeed>) {
+    if !speed.adjusted() {
+        return;
+    }
+    let (slowest, fastest) = speed.range();
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Played at {slowest:.2}x-{fastest:.2}x game speed"),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.8, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(70.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        OnGameOverScreen,
+    ));
+}
+
+fn reset_run_speed(mut speed: ResMut<RunSpeed>) {
+    *speed = RunSpeed::default();
+}
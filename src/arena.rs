@@ -0,0 +1,425 @@
+use bevy::{
+    math::bounding::Aabb2d,
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+    window::{PrimaryWindow, WindowResized},
+};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collider_half_size,
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    main_menu::Difficulty,
+    scoring::score_update,
+    settings::{GameSettings, HitboxSettings},
+    sim,
+    snake::move_snake,
+    Apple, Collider, Ending, GameAssets, GameRng, GameState, OnGameScreen, RunEnd,
+    SnakeBodySegment, SnakeHead, ARENA_MARGIN, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, SEGMENT_SIZE,
+    TOP_WALL, WALL_COLOR, WALL_THICKNESS,
+};
+
+// walls the snake wraps through are only outlined
+const WRAP_EDGE_ALPHA: f32 = 0.25;
+
+/// The arena itself: the camera fitted around it, the grid the snake moves
+/// on, its walls and obstacles, and the apples placed in it, with a new one
+/// for every apple eaten, always on a free cell. A run that leaves no cell
+/// for the next apple has filled the board, and is won.
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WallThickness>()
+            .init_resource::<ArenaGrid>()
+            .register_type::<GridPosition>()
+            .add_systems(Startup, camera_setup)
+            .add_systems(Update, fit_arena_to_window)
+            .add_systems(
+                Update,
+                (
+                    fit_grid_to_walls.run_if(
+                        resource_changed::<GameSettings>.or_else(resource_changed::<Difficulty>),
+                    ),
+                    sync_grid_transforms
+                        .after(move_snake)
+                        .before(DetectCollisions),
+                ),
+            )
+            .add_systems(
+                Update,
+                spawn_on_apple_collisions
+                    .after(DetectCollisions)
+                    .before(score_update)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// How thick the walls around the arena are: the thickness from the
+/// settings plus what the difficulty adds, kept out of the settings so
+/// picking a difficulty never changes the saved file.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WallThickness(pub f32);
+
+impl FromWorld for WallThickness {
+    fn from_world(world: &mut World) -> Self {
+        let settings = world.resource::<GameSettings>();
+        // the main menu's plugin may not have added it yet
+        let difficulty = world
+            .get_resource::<Difficulty>()
+            .copied()
+            .unwrap_or_default();
+        WallThickness::new(&settings.hitboxes, difficulty)
+    }
+}
+
+impl WallThickness {
+    fn new(hitboxes: &HitboxSettings, difficulty: Difficulty) -> Self {
+        WallThickness(hitboxes.wall_thickness + difficulty.wall_growth())
+    }
+}
+
+/// The cells the snake can move through, inside the walls. Cells count from
+/// the arena center in segment-sized steps.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct ArenaGrid {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl FromWorld for ArenaGrid {
+    fn from_world(world: &mut World) -> Self {
+        let wall_thickness = world.resource::<WallThickness>().0;
+        ArenaGrid::inside_walls(wall_thickness)
+    }
+}
+
+impl ArenaGrid {
+    fn inside_walls(wall_thickness: f32) -> Self {
+        let (min, max) = sim::grid_bounds(wall_thickness);
+        ArenaGrid { min, max }
+    }
+
+    /// Brings a cell that left the grid back in on the opposite side.
+    pub fn wrap(&self, cell: IVec2) -> IVec2 {
+        (cell - self.min).rem_euclid(self.max - self.min + IVec2::ONE) + self.min
+    }
+}
+
+/// What the arena's edges do to the snake, picked on the main menu. Rules
+/// that wrap, like the kids preset, wrap whatever is picked.
+#[derive(Reflect, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum WallBehavior {
+    #[default]
+    Solid,
+    // through one edge and back in at the opposite one
+    Wrap,
+}
+
+impl WallBehavior {
+    pub fn label(self) -> &'static str {
+        match self {
+            WallBehavior::Solid => "Solid",
+            WallBehavior::Wrap => "Wrap",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            WallBehavior::Solid => WallBehavior::Wrap,
+            WallBehavior::Wrap => WallBehavior::Solid,
+        }
+    }
+}
+
+/// The cell an entity sits in. The snake moves cell by cell, and its
+/// `Transform` is placed at the center of whichever cell it's in.
+#[derive(Component, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct GridPosition(pub IVec2);
+
+impl GridPosition {
+    /// The cell `position` falls in.
+    pub fn containing(position: Vec2) -> Self {
+        GridPosition((position / SEGMENT_SIZE).round().as_ivec2())
+    }
+
+    pub fn translation(self) -> Vec2 {
+        self.0.as_vec2() * SEGMENT_SIZE
+    }
+}
+
+#[derive(Bundle)]
+pub struct WallBundle {
+    sprite_bundle: SpriteBundle,
+    collider: Collider,
+    kind: ColliderKind,
+}
+
+pub enum WallLocation {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+impl WallLocation {
+    fn position(&self) -> Vec2 {
+        match self {
+            WallLocation::Left => Vec2::new(LEFT_WALL, 0.0),
+            WallLocation::Right => Vec2::new(RIGHT_WALL, 0.0),
+            WallLocation::Bottom => Vec2::new(0.0, BOTTOM_WALL),
+            WallLocation::Top => Vec2::new(0.0, TOP_WALL),
+        }
+    }
+
+    fn size(&self, thickness: f32) -> Vec2 {
+        let arena_height = TOP_WALL - BOTTOM_WALL;
+        let arena_width = RIGHT_WALL - LEFT_WALL;
+
+        assert!(arena_height > 0.0);
+        assert!(arena_width > 0.0);
+
+        match self {
+            WallLocation::Left | WallLocation::Right => {
+                Vec2::new(thickness, arena_height + thickness)
+            }
+            WallLocation::Bottom | WallLocation::Top => {
+                Vec2::new(arena_width + thickness, thickness)
+            }
+        }
+    }
+}
+
+impl WallBundle {
+    pub fn new(location: WallLocation, thickness: f32) -> WallBundle {
+        WallBundle {
+            sprite_bundle: SpriteBundle {
+                transform: Transform {
+                    translation: location.position().extend(0.0),
+                    scale: location.size(thickness).extend(1.0),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: WALL_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            collider: Collider,
+            kind: ColliderKind::Wall,
+        }
+    }
+}
+
+/// A wall the snake wraps through: the same shape, see-through and without
+/// a collider.
+pub fn wrap_edge_sprite(location: WallLocation, thickness: f32) -> SpriteBundle {
+    SpriteBundle {
+        transform: Transform {
+            translation: location.position().extend(0.0),
+            scale: location.size(thickness).extend(1.0),
+            ..default()
+        },
+        sprite: Sprite {
+            color: WALL_COLOR.with_a(WRAP_EDGE_ALPHA),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn camera_setup(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let mut camera = Camera2dBundle::default();
+    // a browser canvas already has the page's size before the first resize
+    if let Ok(window) = windows.get_single() {
+        let scale = arena_view_scale(window.width(), window.height());
+        camera.projection.scale = scale;
+        ui_scale.0 = hud_scale(scale);
+    }
+    commands.spawn(camera);
+}
+
+fn arena_view_scale(window_width: f32, window_height: f32) -> f32 {
+    let view_width = RIGHT_WALL - LEFT_WALL + WALL_THICKNESS + ARENA_MARGIN;
+    let view_height = TOP_WALL - BOTTOM_WALL + WALL_THICKNESS + ARENA_MARGIN;
+    (view_width / window_width).max(view_height / window_height)
+}
+
+fn fit_arena_to_window(
+    mut resize_events: EventReader<WindowResized>,
+    mut projections: Query<&mut OrthographicProjection, With<Camera2d>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Some(resized) = resize_events.read().last() else {
+        return;
+    };
+    // minimized windows report a zero size
+    if resized.width <= 0.0 || resized.height <= 0.0 {
+        return;
+    }
+
+    let scale = arena_view_scale(resized.width, resized.height);
+    for mut projection in &mut projections {
+        projection.scale = scale;
+    }
+    ui_scale.0 = hud_scale(scale);
+}
+
+// the HUD shrinks and grows with the arena instead of the raw window size
+fn hud_scale(view_scale: f32) -> f32 {
+    (1.0 / view_scale).clamp(0.5, 2.0)
+}
+
+fn fit_grid_to_walls(
+    settings: Res<GameSettings>,
+    difficulty: Res<Difficulty>,
+    mut wall_thickness: ResMut<WallThickness>,
+    mut grid: ResMut<ArenaGrid>,
+) {
+    wall_thickness.set_if_neq(WallThickness::new(&settings.hitboxes, *difficulty));
+    grid.set_if_neq(ArenaGrid::inside_walls(wall_thickness.0));
+}
+
+/// Moves whatever changed cells this frame to its new cell's center.
+fn sync_grid_transforms(mut cells: Query<(&GridPosition, &mut Transform), Changed<GridPosition>>) {
+    for (cell, mut transform) in &mut cells {
+        let translation = cell.translation().extend(transform.translation.z);
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_on_apple_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut rng: ResMut<GameRng>,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+    snake: Query<&GridPosition, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    colliders: Query<(Entity, &Transform, &ColliderKind), With<Collider>>,
+) {
+    let eaten: Vec<Entity> = collision_events
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .map(|event| event.collider)
+        .collect();
+    if eaten.is_empty() {
+        return;
+    }
+
+    // segments grown this tick still sit under the head, so the head covers them
+    let mut occupied = occupied_boxes(
+        &snake,
+        colliders
+            .iter()
+            .filter(|(entity, _, _)| !eaten.contains(entity))
+            .map(|(_, transform, kind)| (transform, kind)),
+        &settings.hitboxes,
+    );
+
+    for apple in eaten {
+        commands.entity(apple).despawn();
+        let spawned = apple_spawn_free(
+            &mut commands,
+            &game_assets,
+            &mut rng,
+            wall_thickness.0,
+            &occupied,
+        );
+        let Some(cell) = spawned else {
+            // filling the board is the one way to win a run outright
+            run_end.end(Ending::Won);
+            next_state.set(GameState::GameOver);
+            return;
+        };
+        // apples eaten together mustn't respawn into the same cell
+        occupied.push(Aabb2d::new(cell, SEGMENT_SIZE / 2.0));
+    }
+}
+
+/// The boxes a new apple has to keep out of: the snake's cells and every
+/// collider, apples by their fruit's hitbox.
+pub fn occupied_boxes<'a>(
+    snake: impl IntoIterator<Item = &'a GridPosition>,
+    colliders: impl IntoIterator<Item = (&'a Transform, &'a ColliderKind)>,
+    hitboxes: &HitboxSettings,
+) -> Vec<Aabb2d> {
+    snake
+        .into_iter()
+        .map(|cell| Aabb2d::new(cell.translation(), SEGMENT_SIZE / 2.0))
+        .chain(colliders.into_iter().map(|(transform, &kind)| {
+            Aabb2d::new(
+                transform.translation.truncate(),
+                collider_half_size(transform, kind == ColliderKind::Apple, hitboxes),
+            )
+        }))
+        .collect()
+}
+
+/// Spawns an apple in a random cell none of the `occupied` boxes overlap and
+/// returns that cell, or `None`, spawning nothing, when every cell is taken.
+pub fn apple_spawn_free(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    rng: &mut GameRng,
+    wall_thickness: f32,
+    occupied: &[Aabb2d],
+) -> Option<Vec2> {
+    let _span = info_span!("apple_spawn").entered();
+    let cells = sim::free_cells(occupied, wall_thickness);
+    let &cell = cells.choose(&mut rng.0)?;
+    apple_spawn_at(commands, game_assets, cell.extend(-2.0));
+    Some(cell)
+}
+
+pub fn apple_spawn_at(commands: &mut Commands, game_assets: &GameAssets, position: Vec3) -> Entity {
+    commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh: game_assets.quad.clone(),
+                material: game_assets.apple_material.clone(),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Apple,
+            Collider,
+            ColliderKind::Apple,
+            OnGameScreen,
+        ))
+        .id()
+}
+
+pub fn obstacle_spawn_at(commands: &mut Commands, position: Vec2) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: position.extend(0.0),
+                    scale: SEGMENT_SIZE.extend(1.0),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: WALL_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            Collider,
+            ColliderKind::Wall,
+            OnGameScreen,
+        ))
+        .id()
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{collision::CollisionEvent, settings::GameSettings, toast::Toast, GameState};
+
+// long enough to react to a late turn, short enough that the head barely sinks in
+const GRACE_SECONDS: f32 = 0.12;
+
+/// Assist mode, toggled with F11. A lethal hit is held back for a short grace
+/// window, and turning away before it runs out forgives it.
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingCollision>()
+            .add_systems(Update, toggle_assist)
+            .add_systems(OnEnter(GameState::GameOver), clear_pending_collision);
+    }
+}
+
+/// The lethal hit waiting out its grace window, if any.
+#[derive(Resource, Default)]
+pub struct PendingCollision(Option<(CollisionEvent, Timer)>);
+
+impl PendingCollision {
+    /// Holds `hit` back and returns whichever held hit outlasted its window.
+    /// A turn drops the held hit; one still touching the head after the turn
+    /// starts a fresh window.
+    pub fn hold(
+        &mut self,
+        hit: Option<CollisionEvent>,
+        turned: bool,
+        delta: Duration,
+    ) -> Option<CollisionEvent> {
+        if turned {
+            self.0 = None;
+        }
+        if let Some((held, window)) = &mut self.0 {
+            if window.tick(delta).finished() {
+                let held = *held;
+                self.0 = None;
+                return Some(held);
+            }
+        } else if let Some(hit) = hit {
+            self.0 = Some((hit, Timer::from_seconds(GRACE_SECONDS, TimerMode::Once)));
+        }
+        None
+    }
+}
+
+fn toggle_assist(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F11) {
+        settings.accessibility.assist = !settings.accessibility.assist;
+        toasts.send(Toast(if settings.accessibility.assist {
+            "Assist mode on".to_string()
+        } else {
+            "Assist mode off".to_string()
+        }));
+    }
+}
+
+fn clear_pending_collision(mut pending: ResMut<PendingCollision>) {
+    pending.0 = None;
+}
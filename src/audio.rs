@@ -0,0 +1,139 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    secrets::SecretProgress,
+    settings::GameSettings,
+    Ending, GameState, RunEnd,
+};
+
+const EAT_SOUND: &str = "sounds/eat.ogg";
+const HONK_SOUND: &str = "sounds/honk.ogg";
+const DEATH_SOUND: &str = "sounds/death.ogg";
+const MUSIC: &str = "music/background.ogg";
+// the music sits under the sound effects
+const MUSIC_VOLUME: f32 = 0.5;
+
+/// Sound: a crunch for every apple eaten, a jingle when a run ends and music
+/// looping while a run is being played, paused on every other screen. The
+/// volume comes from the settings file. The comic sound pack secret swaps the
+/// crunch for a honk.
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundAssets>()
+            .add_systems(
+                Update,
+                play_eat_sound
+                    .after(DetectCollisions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), play_death_jingle)
+            .add_systems(OnEnter(GameState::Playing), play_music)
+            .add_systems(OnExit(GameState::Playing), pause_music)
+            .add_systems(
+                Update,
+                apply_volume.run_if(resource_changed::<GameSettings>),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct SoundAssets {
+    eat: Handle<AudioSource>,
+    honk: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+    music: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        SoundAssets {
+            eat: asset_server.load(EAT_SOUND),
+            honk: asset_server.load(HONK_SOUND),
+            death: asset_server.load(DEATH_SOUND),
+            music: asset_server.load(MUSIC),
+        }
+    }
+}
+
+#[derive(Component)]
+struct BackgroundMusic;
+
+fn play_eat_sound(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    sounds: Res<SoundAssets>,
+    secrets: Res<SecretProgress>,
+) {
+    // one crunch however many apples went down this frame
+    let ate = collision_events
+        .read()
+        .any(|event| event.kind == ColliderKind::Apple);
+    if !ate {
+        return;
+    }
+    let sound = if secrets.enabled("comic_sounds") {
+        sounds.honk.clone()
+    } else {
+        sounds.eat.clone()
+    };
+    commands.spawn(AudioBundle {
+        source: sound,
+        settings: PlaybackSettings::DESPAWN,
+    });
+}
+
+fn play_death_jingle(mut commands: Commands, sounds: Res<SoundAssets>, run_end: Res<RunEnd>) {
+    // won runs and the credits or help coming back through game over stay quiet
+    if run_end.unrecorded().and_then(Ending::death_cause).is_none() {
+        return;
+    }
+    commands.spawn(AudioBundle {
+        source: sounds.death.clone(),
+        settings: PlaybackSettings::DESPAWN,
+    });
+}
+
+fn play_music(
+    mut commands: Commands,
+    sounds: Res<SoundAssets>,
+    music: Query<Option<&AudioSink>, With<BackgroundMusic>>,
+) {
+    match music.get_single() {
+        Ok(Some(sink)) => sink.play(),
+        // still loading, it starts as soon as it can
+        Ok(None) => {}
+        Err(_) => {
+            commands.spawn((
+                AudioBundle {
+                    source: sounds.music.clone(),
+                    // the global volume from the settings scales this as it starts
+                    settings: PlaybackSettings::LOOP.with_volume(Volume::new(MUSIC_VOLUME)),
+                },
+                BackgroundMusic,
+            ));
+        }
+    }
+}
+
+fn pause_music(music: Query<&AudioSink, With<BackgroundMusic>>) {
+    for sink in &music {
+        sink.pause();
+    }
+}
+
+// sounds pick up the global volume as they start; the music is already playing
+fn apply_volume(
+    settings: Res<GameSettings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    music: Query<&AudioSink, With<BackgroundMusic>>,
+) {
+    let volume = settings.audio.volume.max(0.0);
+    global_volume.volume = Volume::new(volume);
+    for sink in &music {
+        sink.set_volume(volume * MUSIC_VOLUME);
+    }
+}
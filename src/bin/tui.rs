@@ -0,0 +1,206 @@
+//! Terminal frontend: the game's simulation core stepped on a grid and drawn
+//! with ratatui, so it runs anywhere a terminal does, SSH included. Pass a
+//! number to seed the apple placement.
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use bevy::math::{bounding::Aabb2d, vec2, Vec2};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{
+    backend::CrosstermBackend,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Terminal,
+};
+use snake_bevy::sim;
+
+const TICK: Duration = Duration::from_millis(100);
+const CELL_SIZE: f32 = 20.0;
+// a hair under half a cell, so neighbouring cells never overlap
+const HALF_SIZE: Vec2 = vec2(9.0, 9.0);
+// the walls sit on the arena edge, over half of each outer cell, so only
+// stepping past the edge should hit them
+const WALL_TEST_HALF_SIZE: Vec2 = vec2(4.0, 4.0);
+const WALL_THICKNESS: f32 = 10.0;
+const APPLE_ATTEMPTS: usize = 64;
+
+struct Game {
+    rng: StdRng,
+    head: Vec2,
+    heading: Vec2,
+    body: Vec<Vec2>,
+    apple: Vec2,
+    score: u32,
+    over: bool,
+}
+
+impl Game {
+    fn new(seed: u64) -> Self {
+        let mut game = Game {
+            rng: StdRng::seed_from_u64(seed),
+            head: vec2(20.0, 40.0),
+            heading: Vec2::Y,
+            body: (0..3)
+                .map(|index| vec2(20.0, 20.0 - CELL_SIZE * index as f32))
+                .collect(),
+            apple: Vec2::ZERO,
+            score: 0,
+            over: false,
+        };
+        game.place_apple();
+        game
+    }
+
+    fn place_apple(&mut self) {
+        let mut occupied = self.body.clone();
+        occupied.push(self.head);
+        if let Some(apple) = sim::free_apple_position(
+            &mut self.rng,
+            &occupied,
+            HALF_SIZE,
+            WALL_THICKNESS,
+            APPLE_ATTEMPTS,
+        ) {
+            // snapped to the grid the head moves on
+            self.apple = (apple / CELL_SIZE).round() * CELL_SIZE;
+        }
+    }
+
+    fn turn(&mut self, heading: Vec2) {
+        // turning back into the body isn't a move
+        if heading != -self.heading {
+            self.heading = heading;
+        }
+    }
+
+    fn step(&mut self) {
+        if self.over {
+            return;
+        }
+        let previous_head = self.head;
+        self.head += self.heading * CELL_SIZE;
+        let tail = self.body.last().copied().unwrap_or(previous_head);
+        sim::follow_head(&mut self.body, previous_head);
+
+        let wall_test = Aabb2d::new(self.head, WALL_TEST_HALF_SIZE);
+        let head = Aabb2d::new(self.head, HALF_SIZE);
+        if sim::first_collision(wall_test, &sim::walls(WALL_THICKNESS)).is_some() {
+            self.over = true;
+        } else if sim::collided_with_wall_apple(head, Aabb2d::new(self.apple, HALF_SIZE)).is_some()
+        {
+            self.score += 1;
+            self.body.push(tail);
+            self.place_apple();
+        }
+    }
+}
+
+fn cell(arena: Aabb2d, position: Vec2) -> (usize, usize) {
+    let column = ((position.x - arena.min.x) / CELL_SIZE).floor().max(0.0);
+    let row = ((arena.max.y - position.y) / CELL_SIZE).floor().max(0.0);
+    (column as usize, row as usize)
+}
+
+fn render(game: &Game) -> Paragraph<'static> {
+    let arena = sim::arena();
+    let columns = ((arena.max.x - arena.min.x) / CELL_SIZE) as usize;
+    let rows = ((arena.max.y - arena.min.y) / CELL_SIZE) as usize;
+
+    // each cell is two characters wide so the arena looks square
+    let mut grid = vec![vec![("  ", Color::Reset); columns]; rows];
+    let mut put = |position: Vec2, glyph: &'static str, color: Color| {
+        let (column, row) = cell(arena, position);
+        if let Some(slot) = grid.get_mut(row).and_then(|cells| cells.get_mut(column)) {
+            *slot = (glyph, color);
+        }
+    };
+    put(game.apple, "()", Color::Red);
+    for &segment in &game.body {
+        put(segment, "[]", Color::Green);
+    }
+    put(game.head, "@@", Color::LightGreen);
+
+    let lines: Vec<Line> = grid
+        .into_iter()
+        .map(|cells| {
+            Line::from(
+                cells
+                    .into_iter()
+                    .map(|(glyph, color)| Span::styled(glyph, Style::default().fg(color)))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    let title = if game.over {
+        format!(
+            " Game over: {} apples, Space to restart, Q to quit ",
+            game.score
+        )
+    } else {
+        format!(" Apples Eaten: {} ", game.score)
+    };
+    Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::LightRed))
+            .title(title),
+    )
+}
+
+fn play(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, seed: u64) -> io::Result<()> {
+    let mut game = Game::new(seed);
+    let mut next_tick = Instant::now() + TICK;
+
+    loop {
+        terminal.draw(|frame| frame.render_widget(render(&game), frame.size()))?;
+
+        let timeout = next_tick.saturating_duration_since(Instant::now());
+        if event::poll(timeout)? {
+            // terminals only report presses, so a turn holds until the next one
+            let pressed = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => Some(key.code),
+                _ => None,
+            };
+            match pressed {
+                Some(KeyCode::Char('q') | KeyCode::Esc) => return Ok(()),
+                Some(KeyCode::Up | KeyCode::Char('w')) => game.turn(Vec2::Y),
+                Some(KeyCode::Down | KeyCode::Char('s')) => game.turn(Vec2::NEG_Y),
+                Some(KeyCode::Left | KeyCode::Char('a')) => game.turn(Vec2::NEG_X),
+                Some(KeyCode::Right | KeyCode::Char('d')) => game.turn(Vec2::X),
+                Some(KeyCode::Char(' ')) if game.over => game = Game::new(seed),
+                _ => {}
+            }
+        }
+
+        if Instant::now() >= next_tick {
+            game.step();
+            next_tick += TICK;
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let seed = std::env::args()
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(rand::random);
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .and_then(|mut terminal| play(&mut terminal, seed));
+
+    // put the terminal back even when the game failed
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    result
+}
@@ -0,0 +1,184 @@
+use bevy::{
+    math::bounding::{Aabb2d, IntersectsVolume},
+    prelude::*,
+};
+use rand::seq::SliceRandom;
+
+use crate::{
+    arena::WallThickness,
+    collision::{ColliderKind, DetectCollisions},
+    move_snake, new_run,
+    settings::GameSettings,
+    sim,
+    toast::Toast,
+    visual_cues::Cue,
+    GameRng, GameState, OnGameScreen, Scoreboard, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+const BOUNTY_POINTS: u32 = 5;
+// seconds between bounties, and how long each one waits to be taken
+const BOUNTY_INTERVAL_SECONDS: f32 = 15.0;
+const BOUNTY_LIFETIME_SECONDS: f32 = 8.0;
+// the segments right behind the head are always next to it
+const NECK_SEGMENTS: usize = 3;
+// bounties never land right in front of the head
+const HEAD_CLEARANCE: f32 = 60.0;
+const BOUNTY_COLOR: Color = Color::rgb(1.0, 0.84, 0.0);
+const GLOW_COLOR: Color = Color::rgba(1.0, 0.84, 0.0, 0.25);
+const GLOW_SCALE: f32 = 2.2;
+
+/// Bounty apples spawner bias: now and then a glowing, high-value apple is
+/// placed on purpose somewhere risky, in a corner, between obstacles or
+/// against the snake's own body, and vanishes if it isn't taken in time.
+pub struct BountyApplesPlugin;
+
+impl Plugin for BountyApplesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BountySpawner>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawner.run_if(new_run))
+            .add_systems(
+                Update,
+                (
+                    spawn_bounties,
+                    collect_bounties.after(move_snake).before(DetectCollisions),
+                    expire_bounties,
+                )
+                    .chain()
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.bounty_apples)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct BountySpawner(Timer);
+
+impl Default for BountySpawner {
+    fn default() -> Self {
+        BountySpawner(Timer::from_seconds(
+            BOUNTY_INTERVAL_SECONDS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+#[derive(Component)]
+struct BountyApple(Timer);
+
+fn reset_spawner(mut spawner: ResMut<BountySpawner>) {
+    *spawner = BountySpawner::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_bounties(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    mut rng: ResMut<GameRng>,
+    mut spawner: ResMut<BountySpawner>,
+    bounties: Query<(), With<BountyApple>>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    body: Query<&Transform, With<SnakeBodySegment>>,
+    colliders: Query<(&Transform, &ColliderKind)>,
+    mut cues: EventWriter<Cue>,
+) {
+    if !spawner.0.tick(time.delta()).just_finished() || !bounties.is_empty() {
+        return;
+    }
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let head = head.translation.truncate();
+
+    let walls: Vec<Aabb2d> = colliders
+        .iter()
+        .filter(|(_, kind)| **kind == ColliderKind::Wall)
+        .map(|(transform, _)| {
+            Aabb2d::new(
+                transform.translation.truncate(),
+                transform.scale.truncate() / 2.0,
+            )
+        })
+        .collect();
+    // body segments come in the order they follow the head
+    let segments: Vec<Vec2> = body
+        .iter()
+        .skip(NECK_SEGMENTS)
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let cells: Vec<Vec2> = sim::risky_cells(&walls, &segments, wall_thickness.0)
+        .into_iter()
+        .filter(|cell| cell.distance(head) > HEAD_CLEARANCE)
+        .collect();
+    let Some(&cell) = cells.choose(&mut rng.0) else {
+        return;
+    };
+
+    commands
+        .spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(cell.extend(-2.0)),
+                sprite: Sprite {
+                    color: BOUNTY_COLOR,
+                    custom_size: Some(settings.hitboxes.apple_half_size() * 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            BountyApple(Timer::from_seconds(
+                BOUNTY_LIFETIME_SECONDS,
+                TimerMode::Once,
+            )),
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                transform: Transform::from_xyz(0.0, 0.0, -0.1),
+                sprite: Sprite {
+                    color: GLOW_COLOR,
+                    custom_size: Some(SEGMENT_SIZE * GLOW_SCALE),
+                    ..default()
+                },
+                ..default()
+            });
+        });
+    cues.send(Cue::BonusSpawned(cell));
+}
+
+fn collect_bounties(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut toasts: EventWriter<Toast>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    bounties: Query<(Entity, &Transform), With<BountyApple>>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let head = Aabb2d::new(head.translation.truncate(), settings.hitboxes.head() / 2.0);
+    for (entity, transform) in &bounties {
+        let bounty = Aabb2d::new(
+            transform.translation.truncate(),
+            settings.hitboxes.apple_half_size(),
+        );
+        if head.intersects(&bounty) {
+            commands.entity(entity).despawn_recursive();
+            scoreboard.score += BOUNTY_POINTS;
+            toasts.send(Toast(format!("Bounty collected! +{BOUNTY_POINTS}")));
+        }
+    }
+}
+
+fn expire_bounties(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bounties: Query<(Entity, &mut BountyApple)>,
+) {
+    for (entity, mut bounty) in &mut bounties {
+        if bounty.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+use crate::{storage, toast::Toast};
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, screenshot_hotkey);
+    }
+}
+
+/// Timestamped output file inside `folder` of the data directory.
+pub fn capture_path(folder: &str, prefix: &str, extension: &str) -> PathBuf {
+    let file_name = format!(
+        "{prefix}-{}.{extension}",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f")
+    );
+    match storage::data_dir() {
+        Some(dir) => {
+            let dir = dir.join(folder);
+            if let Err(err) = std::fs::create_dir_all(&dir) {
+                warn!("failed to create {}: {err}", dir.display());
+            }
+            dir.join(file_name)
+        }
+        // web builds hand screenshots to the browser as a download
+        None => PathBuf::from(file_name),
+    }
+}
+
+/// Writes an export to the path `capture_path` picked for it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_capture(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+// the page has no files to write to; screenshots only get out because bevy
+// turns them into downloads
+#[cfg(target_arch = "wasm32")]
+pub fn write_capture(_path: &Path, _contents: impl AsRef<[u8]>) -> Result<(), String> {
+    Err("saving files isn't available in the browser".to_string())
+}
+
+fn screenshot_hotkey(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut toasts: EventWriter<Toast>,
+    mut saved_to: Local<Option<PathBuf>>,
+) {
+    // confirm a frame late so the toast doesn't end up in the picture
+    if let Some(path) = saved_to.take() {
+        toasts.send(Toast(format!("Screenshot saved to {}", path.display())));
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Ok(window) = main_window.get_single() else {
+        return;
+    };
+
+    let path = capture_path("screenshots", "screenshot", "png");
+    match screenshot_manager.save_screenshot_to_disk(window, &path) {
+        Ok(()) => *saved_to = Some(path),
+        Err(err) => warn!("screenshot failed: {err}"),
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use chrono::{Datelike, Duration as ChronoDuration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collision::ColliderKind, display_final_score, input::KeyBindings, menu::MenuItem,
+    practice::PracticeMode, rules::GameRules, storage, GameRng, GameState, OnGameOverScreen,
+    OnGameScreen, Scoreboard, SnakeHead,
+};
+
+pub const WEEKLY_BEST_KEY: &str = "weekly";
+const FOG_RADIUS: f32 = 120.0;
+const HASTE_MULTIPLIER: f32 = 1.35;
+const DOUBLE_SPEED_MULTIPLIER: f32 = 2.0;
+const TINY_HITBOX_SCALE: f32 = 0.5;
+
+/// Weekly challenge: a seed and mutator set fixed for the current ISO week,
+/// started from the game-over screen with W.
+pub struct WeeklyChallengePlugin;
+
+impl Plugin for WeeklyChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mutators>()
+            .init_resource::<ActiveChallenge>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    record_weekly_best.before(display_final_score),
+                    display_weekly_challenge.after(display_final_score),
+                ),
+            )
+            .add_systems(
+                Update,
+                challenge_input.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_challenge_banner,
+                    apply_fog.run_if(|mutators: Res<Mutators>| mutators.fog),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Rule changes for the current run.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct Mutators {
+    // swaps left with right and up with down
+    pub mirror_controls: bool,
+    // hides apples and hazards away from the head
+    pub fog: bool,
+    pub haste: bool,
+    // the rest are only picked on the mutators screen
+    pub double_speed: bool,
+    // the snake wraps through the edges
+    pub no_walls: bool,
+    pub tiny_hitbox: bool,
+}
+
+impl Mutators {
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.mirror_controls, "Mirror"),
+            (self.fog, "Fog"),
+            (self.haste, "Haste"),
+            (self.double_speed, "Double speed"),
+            (self.no_walls, "No walls"),
+            (self.tiny_hitbox, "Tiny hitbox"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+        .collect()
+    }
+
+    /// The classic rules with these mutators applied.
+    pub fn rules(&self) -> GameRules {
+        let mut rules = GameRules::classic();
+        if self.haste {
+            rules.speed = rules.speed.faster(HASTE_MULTIPLIER);
+        }
+        if self.double_speed {
+            rules.speed = rules.speed.faster(DOUBLE_SPEED_MULTIPLIER);
+        }
+        rules.wrap_edges = self.no_walls;
+        if self.tiny_hitbox {
+            rules.head_hitbox_scale = TINY_HITBOX_SCALE;
+        }
+        rules
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct ChallengeWeek {
+    year: i32,
+    week: u32,
+}
+
+impl ChallengeWeek {
+    fn current() -> Self {
+        let week = Utc::now().iso_week();
+        ChallengeWeek {
+            year: week.year(),
+            week: week.week(),
+        }
+    }
+
+    fn seed(self) -> u64 {
+        (self.year as u64) * 100 + self.week as u64
+    }
+
+    fn mutators(self) -> Mutators {
+        // every week gets at least one mutator
+        let bits = self.seed() % 7 + 1;
+        Mutators {
+            mirror_controls: bits & 1 != 0,
+            fog: bits & 2 != 0,
+            haste: bits & 4 != 0,
+            ..Mutators::default()
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveChallenge(Option<ChallengeWeek>);
+
+impl ActiveChallenge {
+    pub fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn end(&mut self) {
+        self.0 = None;
+    }
+
+    /// ISO week of the running challenge, e.g. "2024_W07".
+    pub fn week_label(&self) -> Option<String> {
+        self.0
+            .map(|week| format!("{}_W{:02}", week.year, week.week))
+    }
+
+    /// The seed the running challenge's run was played with.
+    pub fn seed(&self) -> Option<u64> {
+        self.0.map(ChallengeWeek::seed)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct WeeklyBest {
+    week: ChallengeWeek,
+    score: u32,
+}
+
+impl WeeklyBest {
+    fn load(week: ChallengeWeek) -> u32 {
+        storage::load_ron::<WeeklyBest>(WEEKLY_BEST_KEY)
+            .filter(|best| best.week == week)
+            .map_or(0, |best| best.score)
+    }
+}
+
+/// Combines two saved weekly bests: the later week wins, and within the same
+/// week the higher score does.
+#[cfg(all(
+    any(feature = "cloud-sync", feature = "steam"),
+    not(target_arch = "wasm32")
+))]
+pub fn merge_weekly_best(local: &str, remote: &str) -> Option<String> {
+    let local: WeeklyBest = ron::from_str(local).ok()?;
+    let remote: WeeklyBest = ron::from_str(remote).ok()?;
+    let key = |best: &WeeklyBest| (best.week.year, best.week.week, best.score);
+    let best = if key(&remote) > key(&local) {
+        remote
+    } else {
+        local
+    };
+    ron::to_string(&best).ok()
+}
+
+#[derive(Component)]
+struct ChallengeBanner;
+
+fn until_next_rotation() -> String {
+    let now = Utc::now();
+    let days = 7 - now.weekday().num_days_from_monday() as i64;
+    let next_monday = (now.date_naive() + ChronoDuration::days(days)).and_time(NaiveTime::MIN);
+    let remaining = next_monday - now.naive_utc();
+    format!("{}d {}h", remaining.num_days(), remaining.num_hours() % 24)
+}
+
+fn challenge_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut commands: Commands,
+    mut challenge: ResMut<ActiveChallenge>,
+    mut mutators: ResMut<Mutators>,
+    mut rules: ResMut<GameRules>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // confirm restarts a normal run, which the main game-over input handles
+    if keyboard_input.just_pressed(bindings.confirm) {
+        challenge.0 = None;
+        *mutators = Mutators::default();
+    } else if keyboard_input.just_pressed(KeyCode::KeyW) {
+        let week = ChallengeWeek::current();
+        challenge.0 = Some(week);
+        *mutators = week.mutators();
+        commands.insert_resource(GameRng::seeded(week.seed()));
+        next_state.set(GameState::Playing);
+    } else {
+        return;
+    }
+
+    *rules = mutators.rules();
+}
+
+fn record_weekly_best(
+    challenge: Res<ActiveChallenge>,
+    practice: Res<PracticeMode>,
+    scoreboard: Res<Scoreboard>,
+) {
+    let Some(week) = challenge.0 else {
+        return;
+    };
+    if practice.is_active() {
+        return;
+    }
+    if scoreboard.score > WeeklyBest::load(week) {
+        storage::save_ron(
+            WEEKLY_BEST_KEY,
+            &WeeklyBest {
+                week,
+                score: scoreboard.score,
+            },
+        );
+    }
+}
+
+fn display_weekly_challenge(mut commands: Commands) {
+    let week = ChallengeWeek::current();
+    let message = format!(
+        "Press W for week {} challenge ({}), best {}, rotates in {}",
+        week.week,
+        week.mutators().names().join(" + "),
+        WeeklyBest::load(week),
+        until_next_rotation()
+    );
+
+    commands.spawn((
+        TextBundle::from_section(
+            message,
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.8, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(20.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyW),
+        OnGameOverScreen,
+    ));
+}
+
+fn spawn_challenge_banner(
+    mut commands: Commands,
+    challenge: Res<ActiveChallenge>,
+    mutators: Res<Mutators>,
+    banners: Query<(), With<ChallengeBanner>>,
+) {
+    let Some(week) = challenge.0 else {
+        return;
+    };
+    if !banners.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            format!(
+                "Weekly challenge {}-W{:02}: {}",
+                week.year,
+                week.week,
+                mutators.names().join(" + ")
+            ),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.8, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ChallengeBanner,
+        OnGameScreen,
+    ));
+}
+
+fn apply_fog(
+    snake_heads: Query<&Transform, With<SnakeHead>>,
+    mut hidden: Query<(&Transform, &ColliderKind, &mut Visibility), Without<SnakeHead>>,
+) {
+    let Ok(head) = snake_heads.get_single() else {
+        return;
+    };
+    let head = head.translation.truncate();
+
+    for (transform, kind, mut visibility) in &mut hidden {
+        if *kind == ColliderKind::Wall {
+            continue;
+        }
+        let seen = transform.translation.truncate().distance(head) <= FOG_RADIUS;
+        let target = if seen {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    math::vec2,
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, Rgba, RgbaImage,
+};
+
+use crate::{
+    capture::{capture_path, write_capture},
+    toast::Toast,
+    Apple, GameState, SnakeBodySegment, SnakeHead, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL,
+    SEGMENT_SIZE, TOP_WALL,
+};
+
+const CLIP_SECONDS: u32 = 30;
+const CLIP_FPS: u32 = 15;
+// world units to GIF pixels; keeps a 30 second clip to a few megabytes
+const CLIP_SCALE: f32 = 0.25;
+
+const BACKGROUND: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const HEAD_COLOR: Rgba<u8> = Rgba([120, 255, 120, 255]);
+const BODY_COLOR: Rgba<u8> = Rgba([0, 200, 0, 255]);
+const APPLE_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+pub struct ClipPlugin;
+
+impl Plugin for ClipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClipRecorder>()
+            .add_systems(OnExit(GameState::GameOver), clear_clip)
+            .add_systems(
+                Update,
+                record_clip_frame.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, export_clip);
+    }
+}
+
+/// Rolling buffer of the last `CLIP_SECONDS` of play, stored as positions
+/// and rasterized only when a clip is exported.
+#[derive(Resource)]
+struct ClipRecorder {
+    frames: VecDeque<ClipFrame>,
+    timer: Timer,
+}
+
+impl Default for ClipRecorder {
+    fn default() -> Self {
+        ClipRecorder {
+            frames: VecDeque::with_capacity((CLIP_SECONDS * CLIP_FPS) as usize),
+            timer: Timer::from_seconds(1.0 / CLIP_FPS as f32, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ClipFrame {
+    head: Vec<Vec2>,
+    body: Vec<Vec2>,
+    apples: Vec<Vec2>,
+}
+
+impl ClipFrame {
+    fn rasterize(&self) -> RgbaImage {
+        let width = ((RIGHT_WALL - LEFT_WALL) * CLIP_SCALE) as u32;
+        let height = ((TOP_WALL - BOTTOM_WALL) * CLIP_SCALE) as u32;
+        let mut image = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+        for &apple in &self.apples {
+            fill_cell(&mut image, apple, APPLE_COLOR);
+        }
+        for &segment in &self.body {
+            fill_cell(&mut image, segment, BODY_COLOR);
+        }
+        for &head in &self.head {
+            fill_cell(&mut image, head, HEAD_COLOR);
+        }
+        image
+    }
+}
+
+fn fill_cell(image: &mut RgbaImage, center: Vec2, color: Rgba<u8>) {
+    let half_size = SEGMENT_SIZE * CLIP_SCALE / 2.0;
+    // image rows grow downwards while world y grows upwards
+    let pixel = vec2(
+        (center.x - LEFT_WALL) * CLIP_SCALE,
+        (TOP_WALL - center.y) * CLIP_SCALE,
+    );
+    let min = (pixel - half_size).max(Vec2::ZERO);
+    let max = (pixel + half_size).min(vec2(image.width() as f32, image.height() as f32));
+
+    for y in min.y as u32..max.y as u32 {
+        for x in min.x as u32..max.x as u32 {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+fn encode_gif(frames: &[ClipFrame], path: &Path) -> Result<(), String> {
+    let mut gif = Vec::new();
+    // the encoder finishes the file as it's dropped
+    {
+        let mut encoder = GifEncoder::new(&mut gif);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|err| err.to_string())?;
+
+        let delay = Delay::from_numer_denom_ms(1000, CLIP_FPS);
+        encoder
+            .encode_frames(
+                frames
+                    .iter()
+                    .map(|frame| Frame::from_parts(frame.rasterize(), 0, 0, delay)),
+            )
+            .map_err(|err| err.to_string())?;
+    }
+    write_capture(path, gif)
+}
+
+fn clear_clip(mut recorder: ResMut<ClipRecorder>) {
+    recorder.frames.clear();
+}
+
+fn record_clip_frame(
+    time: Res<Time>,
+    mut recorder: ResMut<ClipRecorder>,
+    head_query: Query<&Transform, With<SnakeHead>>,
+    body_query: Query<&Transform, With<SnakeBodySegment>>,
+    apple_query: Query<&Transform, With<Apple>>,
+) {
+    if !recorder.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let positions = |transform: &Transform| transform.translation.truncate();
+    let frame = ClipFrame {
+        head: head_query.iter().map(positions).collect(),
+        body: body_query.iter().map(positions).collect(),
+        apples: apple_query.iter().map(positions).collect(),
+    };
+
+    if recorder.frames.len() == (CLIP_SECONDS * CLIP_FPS) as usize {
+        recorder.frames.pop_front();
+    }
+    recorder.frames.push_back(frame);
+}
+
+fn export_clip(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    recorder: Res<ClipRecorder>,
+    mut toasts: EventWriter<Toast>,
+    mut export: Local<Option<Task<Result<PathBuf, String>>>>,
+) {
+    if let Some(task) = export.as_mut() {
+        if let Some(result) = block_on(future::poll_once(task)) {
+            match result {
+                Ok(path) => toasts.send(Toast(format!("Clip saved to {}", path.display()))),
+                Err(err) => toasts.send(Toast(format!("Clip export failed: {err}"))),
+            };
+            *export = None;
+        }
+        return;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::F10) || recorder.frames.is_empty() {
+        return;
+    }
+    // encoding holds up the page's only thread, for a file it can't save
+    if cfg!(target_arch = "wasm32") {
+        toasts.send(Toast("Clips can't be saved in the browser".to_string()));
+        return;
+    }
+
+    let frames: Vec<ClipFrame> = recorder.frames.iter().cloned().collect();
+    let path = capture_path("clips", "clip", "gif");
+    *export = Some(
+        AsyncComputeTaskPool::get().spawn(async move { encode_gif(&frames, &path).map(|()| path) }),
+    );
+    toasts.send(Toast("Exporting clip...".to_string()));
+}
@@ -0,0 +1,157 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::{merge_weekly_best, WEEKLY_BEST_KEY},
+    progression::PROGRESSION_KEY,
+    settings::{save_settings, CloudSyncSettings, GameSettings, SETTINGS_KEY},
+    storage,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const SYNCED_KEYS: [&str; 3] = [PROGRESSION_KEY, SETTINGS_KEY, WEEKLY_BEST_KEY];
+
+/// Pushes the saves to the cloud copy again on exit. `run` pulls them with
+/// `sync` before anything is loaded and hands over what failed, since logging
+/// isn't up yet at that point.
+pub struct CloudSyncPlugin {
+    pub startup_failures: Vec<String>,
+}
+
+impl Plugin for CloudSyncPlugin {
+    fn build(&self, app: &mut App) {
+        for failure in &self.startup_failures {
+            warn!("{failure}");
+        }
+        app.add_systems(Last, sync_on_exit.after(save_settings));
+    }
+}
+
+/// A save as stored remotely, stamped so the newer copy wins.
+#[derive(Serialize, Deserialize)]
+struct SyncedSave {
+    modified: u64,
+    contents: String,
+}
+
+struct Remote {
+    agent: ureq::Agent,
+    endpoint: String,
+    authorization: String,
+}
+
+impl Remote {
+    fn new(settings: &CloudSyncSettings) -> Option<Self> {
+        if settings.endpoint.is_empty() {
+            return None;
+        }
+        Some(Remote {
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+            endpoint: settings.endpoint.trim_end_matches('/').to_string(),
+            authorization: settings.authorization.clone(),
+        })
+    }
+
+    fn request(&self, method: &str, key: &str) -> ureq::Request {
+        let request = self
+            .agent
+            .request(method, &format!("{}/{key}.ron", self.endpoint));
+        if self.authorization.is_empty() {
+            request
+        } else {
+            request.set("Authorization", &self.authorization)
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<Option<SyncedSave>, String> {
+        match self.request("GET", key).call() {
+            Ok(response) => {
+                let body = response.into_string().map_err(|err| err.to_string())?;
+                ron::from_str(&body)
+                    .map(Some)
+                    .map_err(|err| err.to_string())
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn put(&self, key: &str, save: &SyncedSave) -> Result<(), String> {
+        let body = ron::to_string(save).map_err(|err| err.to_string())?;
+        self.request("PUT", key)
+            .send_string(&body)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Brings the local and remote saves in step and returns what couldn't be
+/// synced. The newer copy wins, except weekly bests, which are merged.
+pub fn sync(settings: &CloudSyncSettings) -> Vec<String> {
+    let Some(remote) = Remote::new(settings) else {
+        return Vec::new();
+    };
+    SYNCED_KEYS
+        .into_iter()
+        .filter_map(|key| {
+            sync_key(&remote, key)
+                .err()
+                .map(|err| format!("cloud sync of {key} failed: {err}"))
+        })
+        .collect()
+}
+
+fn sync_key(remote: &Remote, key: &str) -> Result<(), String> {
+    let local = storage::load_raw(key).map(|contents| SyncedSave {
+        modified: storage::modified(key).map_or(0, unix_seconds),
+        contents,
+    });
+
+    match (local, remote.get(key)?) {
+        (None, None) => Ok(()),
+        (Some(local), None) => remote.put(key, &local),
+        (None, Some(remote_save)) => {
+            storage::save_raw(key, &remote_save.contents);
+            Ok(())
+        }
+        (Some(local), Some(remote_save)) if local.contents == remote_save.contents => Ok(()),
+        (Some(local), Some(remote_save)) => {
+            let merged = if key == WEEKLY_BEST_KEY {
+                merge_weekly_best(&local.contents, &remote_save.contents)
+            } else {
+                None
+            };
+            if let Some(contents) = merged {
+                storage::save_raw(key, &contents);
+                remote.put(
+                    key,
+                    &SyncedSave {
+                        modified: unix_seconds(SystemTime::now()),
+                        contents,
+                    },
+                )
+            } else if remote_save.modified > local.modified {
+                storage::save_raw(key, &remote_save.contents);
+                Ok(())
+            } else {
+                remote.put(key, &local)
+            }
+        }
+    }
+}
+
+fn sync_on_exit(mut exit_events: EventReader<AppExit>, settings: Res<GameSettings>) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+    for failure in sync(&settings.cloud_sync) {
+        warn!("{failure}");
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::{
+    math::bounding::{Aabb2d, IntersectsVolume},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{collider_half_size, settings::GameSettings, Collider, SnakeHead, SEGMENT_SIZE};
+
+// one cell per segment-sized square
+const CELL_SIZE: Vec2 = SEGMENT_SIZE;
+
+/// What the snake head runs into; every `Collider` other than the head carries one.
+#[derive(Component, Reflect, Debug, PartialEq, Eq, Clone, Copy)]
+#[reflect(Component)]
+pub enum ColliderKind {
+    Wall,
+    Apple,
+    Poison,
+    // AI snakes, which are eaten or eat the player depending on length
+    Rival,
+    // the snake's own body, which only ends the run when the rules say so
+    Body,
+}
+
+impl ColliderKind {
+    /// Whether running into it always ends the run.
+    pub fn is_lethal(self) -> bool {
+        matches!(
+            self,
+            ColliderKind::Wall | ColliderKind::Poison | ColliderKind::Body
+        )
+    }
+}
+
+/// Systems that turn head overlaps into `CollisionEvent`s; consumers run after it.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct DetectCollisions;
+
+/// The snake head ran into `collider` this tick.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionEvent {
+    pub collider: Entity,
+    pub kind: ColliderKind,
+}
+
+/// Colliders bucketed by the grid cells their boxes cover, so the head only
+/// has to be tested against whatever shares its cells.
+#[derive(Resource, Default)]
+pub struct CollisionGrid {
+    cells: HashMap<IVec2, Vec<(Entity, ColliderKind, Aabb2d)>>,
+    placed: HashMap<Entity, Vec<IVec2>>,
+}
+
+impl CollisionGrid {
+    pub fn insert(&mut self, entity: Entity, kind: ColliderKind, bounds: Aabb2d) {
+        self.remove(entity);
+
+        let cells = covered_cells(bounds);
+        for cell in &cells {
+            self.cells
+                .entry(*cell)
+                .or_default()
+                .push((entity, kind, bounds));
+        }
+        self.placed.insert(entity, cells);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(cells) = self.placed.remove(&entity) else {
+            return;
+        };
+        for cell in cells {
+            if let Some(occupants) = self.cells.get_mut(&cell) {
+                occupants.retain(|(occupant, _, _)| *occupant != entity);
+                if occupants.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// First collider of `kind` overlapping `bounds`, looking only at the cells it covers.
+    #[cfg_attr(feature = "physics", allow(dead_code))]
+    pub fn first_hit(&self, bounds: Aabb2d, kind: ColliderKind) -> Option<Entity> {
+        covered_cells(bounds)
+            .into_iter()
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .find(|(_, occupant_kind, collider)| {
+                *occupant_kind == kind && bounds.intersects(collider)
+            })
+            .map(|(entity, _, _)| *entity)
+    }
+}
+
+fn covered_cells(bounds: Aabb2d) -> Vec<IVec2> {
+    let min = (bounds.min / CELL_SIZE).floor().as_ivec2();
+    let max = (bounds.max / CELL_SIZE).floor().as_ivec2();
+    (min.x..=max.x)
+        .flat_map(|x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+        .collect()
+}
+
+/// Keeps the grid in step with spawned, moved and despawned colliders, and
+/// with hitbox settings changes. Runs every frame, not just while playing, so
+/// teardown despawns aren't missed.
+#[allow(clippy::type_complexity)]
+pub fn sync_collision_grid(
+    mut grid: ResMut<CollisionGrid>,
+    settings: Res<GameSettings>,
+    mut removed: RemovedComponents<Collider>,
+    moved: Query<
+        (Entity, &Transform, &ColliderKind),
+        (With<Collider>, Without<SnakeHead>, Changed<Transform>),
+    >,
+    all: Query<(Entity, &Transform, &ColliderKind), (With<Collider>, Without<SnakeHead>)>,
+) {
+    for entity in removed.read() {
+        grid.remove(entity);
+    }
+
+    let mut place = |(entity, transform, &kind): (Entity, &Transform, &ColliderKind)| {
+        let bounds = Aabb2d::new(
+            transform.translation.truncate(),
+            collider_half_size(transform, kind == ColliderKind::Apple, &settings.hitboxes),
+        );
+        grid.insert(entity, kind, bounds);
+    };
+    if settings.is_changed() {
+        all.iter().for_each(&mut place);
+    } else {
+        moved.iter().for_each(&mut place);
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display_final_score, score_update, storage, Ending, GameState, OnGameOverScreen, RunEnd,
+    Scoreboard, SnakeBodySegment,
+};
+
+const CONDITIONS_KEY: &str = "conditions";
+
+/// Declarative win and lose conditions, checked every tick of a run. Until
+/// levels carry their own, a `conditions.ron` next to the saves applies to
+/// every run, e.g. `(win: Some(All([ReachLength(30), SurviveSeconds(90)])))`.
+pub struct ConditionsPlugin;
+
+impl Plugin for ConditionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelConditions::load())
+            .init_resource::<ConditionRun>()
+            .add_systems(
+                Update,
+                evaluate_conditions
+                    .after(score_update)
+                    .run_if(in_state(GameState::Playing).and_then(has_conditions)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_outcome.after(display_final_score),
+            )
+            .add_systems(OnExit(GameState::GameOver), reset_condition_run);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RunCondition {
+    ReachLength(usize),
+    ReachScore(u32),
+    SurviveSeconds(f32),
+    // met once the run has gone on longer than this, e.g. as a lose condition
+    TimeLimit(f32),
+    All(Vec<RunCondition>),
+    Any(Vec<RunCondition>),
+}
+
+/// What the conditions are checked against.
+#[derive(Debug, Clone, Copy)]
+pub struct RunProgress {
+    pub length: usize,
+    pub score: u32,
+    pub seconds: f32,
+}
+
+impl RunCondition {
+    pub fn met(&self, progress: &RunProgress) -> bool {
+        match self {
+            RunCondition::ReachLength(length) => progress.length >= *length,
+            RunCondition::ReachScore(score) => progress.score >= *score,
+            RunCondition::SurviveSeconds(seconds) => progress.seconds >= *seconds,
+            RunCondition::TimeLimit(seconds) => progress.seconds > *seconds,
+            RunCondition::All(conditions) => {
+                conditions.iter().all(|condition| condition.met(progress))
+            }
+            RunCondition::Any(conditions) => {
+                conditions.iter().any(|condition| condition.met(progress))
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            RunCondition::ReachLength(length) => format!("reach length {length}"),
+            RunCondition::ReachScore(score) => format!("eat {score} apples"),
+            RunCondition::SurviveSeconds(seconds) => format!("survive {seconds:.0} seconds"),
+            RunCondition::TimeLimit(seconds) => format!("{seconds:.0} seconds passed"),
+            RunCondition::All(conditions) => describe_all(conditions, " and "),
+            RunCondition::Any(conditions) => describe_all(conditions, " or "),
+        }
+    }
+}
+
+fn describe_all(conditions: &[RunCondition], separator: &str) -> String {
+    let parts: Vec<String> = conditions.iter().map(RunCondition::describe).collect();
+    parts.join(separator)
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct LevelConditions {
+    pub win: Option<RunCondition>,
+    pub lose: Option<RunCondition>,
+}
+
+impl LevelConditions {
+    fn load() -> Self {
+        storage::load_ron(CONDITIONS_KEY).unwrap_or_default()
+    }
+}
+
+/// How the last run ended, when a condition ended it.
+#[derive(Default, Debug)]
+enum RunOutcome {
+    #[default]
+    Undecided,
+    Won(String),
+    Lost(String),
+}
+
+#[derive(Resource, Default, Debug)]
+struct ConditionRun {
+    // time spent playing, not counting pauses
+    seconds: f32,
+    outcome: RunOutcome,
+}
+
+fn has_conditions(conditions: Res<LevelConditions>) -> bool {
+    conditions.win.is_some() || conditions.lose.is_some()
+}
+
+fn evaluate_conditions(
+    time: Res<Time>,
+    conditions: Res<LevelConditions>,
+    scoreboard: Res<Scoreboard>,
+    segments: Query<(), With<SnakeBodySegment>>,
+    mut run: ResMut<ConditionRun>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    run.seconds += time.delta_seconds();
+    let progress = RunProgress {
+        length: segments.iter().count() + 1,
+        score: scoreboard.score,
+        seconds: run.seconds,
+    };
+
+    // losing is checked first, so meeting both on the same tick is a loss
+    let (decided, ending) =
+        if let Some(lose) = conditions.lose.as_ref().filter(|lose| lose.met(&progress)) {
+            (RunOutcome::Lost(lose.describe()), Ending::Over)
+        } else if let Some(win) = conditions.win.as_ref().filter(|win| win.met(&progress)) {
+            (RunOutcome::Won(win.describe()), Ending::Won)
+        } else {
+            return;
+        };
+    run.outcome = decided;
+    run_end.end(ending);
+    next_state.set(GameState::GameOver);
+}
+
+fn display_outcome(mut commands: Commands, run: Res<ConditionRun>) {
+    let (message, color) = match &run.outcome {
+        RunOutcome::Undecided => return,
+        RunOutcome::Won(goal) => (format!("Level complete: {goal}"), Color::GOLD),
+        RunOutcome::Lost(reason) => (format!("Level failed: {reason}"), Color::rgb(1.0, 0.5, 0.5)),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(50.0),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                message,
+                TextStyle {
+                    font_size: 30.0,
+                    color,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn reset_condition_run(mut run: ResMut<ConditionRun>) {
+    *run = ConditionRun::default();
+}
@@ -0,0 +1,375 @@
+use std::{collections::BTreeMap, path::Path};
+
+use bevy::{
+    ecs::system::SystemState, math::bounding::Aabb2d, prelude::*, window::ReceivedCharacter,
+};
+
+use crate::{
+    apple_spawn_free,
+    arena::{occupied_boxes, GridPosition, WallThickness},
+    collision::ColliderKind,
+    pool::EntityPool,
+    replay::Replay,
+    rules::GameRules,
+    settings::GameSettings,
+    snake_segment_spawn,
+    snapshot::WorldSnapshot,
+    GameAssets, GameRng, GameState, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+const HISTORY_LINES: usize = 12;
+
+/// Drop-down cheat console toggled with the backquote key. Only built into
+/// debug profiles.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .register_console_command("help", "help", help_command)
+            .register_console_command("spawn", "spawn apple [count]", spawn_command)
+            .register_console_command("set", "set speed <multiplier>", set_command)
+            .register_console_command("grow", "grow [segments]", grow_command)
+            .register_console_command("goto", "goto <menu|playing|paused|gameover>", goto_command)
+            .register_console_command("seed", "seed <number>", seed_command)
+            .register_console_command("snapshot", "snapshot", snapshot_command)
+            .register_console_command("restore", "restore", restore_command)
+            .register_console_command("replay", "replay <file>", replay_command)
+            .add_systems(
+                Update,
+                (
+                    toggle_console,
+                    console_text_input.run_if(console_open),
+                    run_console_commands,
+                    update_console_ui,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Runs with exclusive world access and returns the line printed back, or an error.
+pub type ConsoleCommandFn = fn(&mut World, &[&str]) -> Result<String, String>;
+
+struct ConsoleCommand {
+    usage: &'static str,
+    run: ConsoleCommandFn,
+}
+
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, ConsoleCommand>);
+
+pub trait RegisterConsoleCommand {
+    fn register_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self;
+}
+
+impl RegisterConsoleCommand for App {
+    fn register_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(name, ConsoleCommand { usage, run });
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    submitted: Vec<String>,
+}
+
+// the last `snapshot`, put back by `restore`
+#[derive(Resource)]
+struct ConsoleSnapshot(WorldSnapshot);
+
+#[derive(Component)]
+struct ConsoleUi;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn console_open(console: Res<Console>) -> bool {
+    console.open
+}
+
+fn toggle_console(keyboard_input: Res<ButtonInput<KeyCode>>, mut console: ResMut<Console>) {
+    if keyboard_input.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+    }
+}
+
+fn console_text_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+) {
+    for event in characters.read() {
+        for character in event.char.chars() {
+            if character != '`' && !character.is_control() {
+                console.input.push(character);
+            }
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        console.input.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.submitted.push(line);
+        }
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    let lines = std::mem::take(&mut world.resource_mut::<Console>().submitted);
+
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            continue;
+        };
+
+        let run = world
+            .resource::<ConsoleCommands>()
+            .0
+            .get(*name)
+            .map(|command| command.run);
+        let output = match run {
+            Some(run) => run(world, args).unwrap_or_else(|err| format!("error: {err}")),
+            None => format!("unknown command '{name}', try 'help'"),
+        };
+
+        let mut console = world.resource_mut::<Console>();
+        console.history.push(format!("> {line}"));
+        console.history.push(output);
+    }
+}
+
+fn update_console_ui(
+    mut commands: Commands,
+    console: Res<Console>,
+    consoles: Query<Entity, With<ConsoleUi>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.open {
+        for entity in &consoles {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let skip = console.history.len().saturating_sub(HISTORY_LINES);
+    let mut contents = console.history[skip..].join("\n");
+    contents.push_str(&format!("\n> {}_", console.input));
+
+    if consoles.is_empty() {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.),
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                    z_index: ZIndex::Global(10),
+                    ..default()
+                },
+                ConsoleUi,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_section(
+                        contents,
+                        TextStyle {
+                            font_size: 16.0,
+                            color: Color::GREEN,
+                            ..default()
+                        },
+                    ),
+                    ConsoleText,
+                ));
+            });
+    } else if console.is_changed() {
+        for mut text in &mut texts {
+            text.sections[0].value.clone_from(&contents);
+        }
+    }
+}
+
+fn parse_count(args: &[&str]) -> Result<usize, String> {
+    match args.first() {
+        Some(count) => count
+            .parse()
+            .map_err(|_| format!("'{count}' is not a count")),
+        None => Ok(1),
+    }
+}
+
+fn help_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let usages: Vec<&str> = world
+        .resource::<ConsoleCommands>()
+        .0
+        .values()
+        .map(|command| command.usage)
+        .collect();
+    Ok(usages.join("\n"))
+}
+
+#[allow(clippy::type_complexity)]
+fn spawn_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let Some((&"apple", rest)) = args.split_first() else {
+        return Err("usage: spawn apple [count]".to_string());
+    };
+    let count = parse_count(rest)?;
+
+    let mut state: SystemState<(
+        Commands,
+        Res<GameAssets>,
+        Res<GameSettings>,
+        ResMut<GameRng>,
+        Res<WallThickness>,
+        Query<&GridPosition, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+        Query<(&Transform, &ColliderKind)>,
+    )> = SystemState::new(world);
+    let mut spawned = 0;
+    {
+        let (mut commands, game_assets, settings, mut rng, wall_thickness, snake, colliders) =
+            state.get_mut(world);
+        let mut occupied = occupied_boxes(&snake, &colliders, &settings.hitboxes);
+        while spawned < count {
+            let Some(cell) = apple_spawn_free(
+                &mut commands,
+                &game_assets,
+                &mut rng,
+                wall_thickness.0,
+                &occupied,
+            ) else {
+                break;
+            };
+            occupied.push(Aabb2d::new(cell, SEGMENT_SIZE / 2.0));
+            spawned += 1;
+        }
+    }
+    state.apply(world);
+
+    Ok(format!("spawned {spawned} apple(s)"))
+}
+
+fn set_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let ["speed", multiplier] = args else {
+        return Err("usage: set speed <multiplier>".to_string());
+    };
+    let multiplier: f32 = multiplier
+        .parse()
+        .ok()
+        .filter(|multiplier: &f32| *multiplier > 0.0)
+        .ok_or_else(|| format!("'{multiplier}' is not a positive number"))?;
+
+    world.resource_mut::<GameRules>().speed = GameRules::classic().speed.faster(multiplier);
+    Ok(format!("speed set to {multiplier}x"))
+}
+
+fn grow_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let count = parse_count(args)?;
+    let head = world
+        .query_filtered::<&Transform, With<SnakeHead>>()
+        .get_single(world)
+        .map(|transform| transform.translation)
+        .map_err(|_| "no snake to grow".to_string())?;
+
+    let mut state: SystemState<(
+        Commands,
+        Res<GameAssets>,
+        ResMut<EntityPool<SnakeBodySegment>>,
+    )> = SystemState::new(world);
+    {
+        let (mut commands, game_assets, mut segment_pool) = state.get_mut(world);
+        for _ in 0..count {
+            snake_segment_spawn(
+                &mut commands,
+                &game_assets,
+                &mut segment_pool,
+                head.x,
+                head.y,
+            );
+        }
+    }
+    state.apply(world);
+
+    Ok(format!("grew by {count} segment(s)"))
+}
+
+fn goto_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let state = match args.first() {
+        Some(&"menu") => GameState::MainMenu,
+        Some(&"playing") => GameState::Playing,
+        Some(&"paused") => GameState::Paused,
+        Some(&"gameover") => GameState::GameOver,
+        _ => return Err("usage: goto <menu|playing|paused|gameover>".to_string()),
+    };
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(state.clone());
+    Ok(format!("switching to {state:?}"))
+}
+
+fn seed_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let seed: u64 = args
+        .first()
+        .and_then(|seed| seed.parse().ok())
+        .ok_or_else(|| "usage: seed <number>".to_string())?;
+    world.insert_resource(GameRng::seeded(seed));
+    Ok(format!("rng seeded with {seed}"))
+}
+
+fn snapshot_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let snapshot = WorldSnapshot::capture(world);
+    let ron = snapshot.to_ron(world)?;
+    world.insert_resource(ConsoleSnapshot(snapshot));
+    Ok(format!("snapshot taken ({} bytes as RON)", ron.len()))
+}
+
+fn replay_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [path] = args else {
+        return Err("usage: replay <file>".to_string());
+    };
+    let replay = Replay::load(Path::new(path))?;
+    let rules = if replay.rules_fingerprint == world.resource::<GameRules>().fingerprint() {
+        "the current rules"
+    } else {
+        "different rules"
+    };
+    Ok(format!(
+        "replay from {}: seed {}, {} turns, played under {rules}",
+        replay.game_version,
+        replay.seed,
+        replay.inputs.len()
+    ))
+}
+
+fn restore_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let ConsoleSnapshot(snapshot) = world
+        .remove_resource::<ConsoleSnapshot>()
+        .ok_or_else(|| "no snapshot yet, take one with 'snapshot'".to_string())?;
+    let restored = snapshot.restore(world).map_err(|err| err.to_string());
+    world.insert_resource(ConsoleSnapshot(snapshot));
+    restored.map(|()| "snapshot restored".to_string())
+}
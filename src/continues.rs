@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+
+use crate::{
+    arena::GridPosition,
+    despawn_screen,
+    hardcore::HardcoreMode,
+    input::{key_name, KeyBindings},
+    pool::EntityPool,
+    practice::PracticeMode,
+    progression::Progression,
+    versus::Versus,
+    GameState, RunEnd, Snake, SnakeBodySegment, SnakeHead,
+};
+
+const CONTINUE_COST: u32 = 50;
+const COUNTDOWN_SECONDS: f32 = 5.0;
+const LOST_SEGMENTS: usize = 3;
+
+/// Once per run, a death can be bought back with coins: the run resumes a
+/// move before the death, heading the same way, three tail segments shorter. Hardcore and practice
+/// runs never get the offer.
+pub struct ContinuePlugin;
+
+impl Plugin for ContinuePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContinueOffer>()
+            // after every system that could have ended the run this frame
+            .add_systems(
+                PostUpdate,
+                offer_continue.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::ContinuePrompt), display_continue_prompt)
+            .add_systems(
+                Update,
+                (answer_continue_prompt, update_countdown)
+                    .chain()
+                    .run_if(in_state(GameState::ContinuePrompt)),
+            )
+            .add_systems(
+                OnExit(GameState::ContinuePrompt),
+                (despawn_screen::<ContinueOverlay>, resume_run),
+            )
+            .add_systems(OnExit(GameState::GameOver), reset_offer);
+    }
+}
+
+#[derive(Resource)]
+struct ContinueOffer {
+    used: bool,
+    accepted: bool,
+    countdown: Timer,
+}
+
+impl Default for ContinueOffer {
+    fn default() -> Self {
+        ContinueOffer {
+            used: false,
+            accepted: false,
+            countdown: Timer::from_seconds(COUNTDOWN_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ContinueOverlay;
+
+#[derive(Component)]
+struct CountdownText;
+
+#[allow(clippy::too_many_arguments)]
+fn offer_continue(
+    mut offer: ResMut<ContinueOffer>,
+    progression: Res<Progression>,
+    hardcore: Res<HardcoreMode>,
+    practice: Res<PracticeMode>,
+    versus: Res<Versus>,
+    run_end: Res<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // quitting a practice run or winning also heads to game over
+    let died = next_state.0 == Some(GameState::GameOver) && run_end.death_cause().is_some();
+    if !died
+        || offer.used
+        || progression.coins < CONTINUE_COST
+        || hardcore.is_active()
+        || practice.is_active()
+        || versus.is_active()
+    {
+        return;
+    }
+    offer.used = true;
+    offer.countdown.reset();
+    next_state.set(GameState::ContinuePrompt);
+}
+
+fn display_continue_prompt(
+    mut commands: Commands,
+    progression: Res<Progression>,
+    bindings: Res<KeyBindings>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                ..default()
+            },
+            ContinueOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Continue?",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("{COUNTDOWN_SECONDS:.0}"),
+                    TextStyle {
+                        font_size: 60.0,
+                        color: Color::rgb(1.0, 0.8, 0.3),
+                        ..default()
+                    },
+                ),
+                CountdownText,
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Press {} or tap to spend {CONTINUE_COST} of your {} coins and lose {LOST_SEGMENTS} segments, Esc to give up",
+                    key_name(bindings.confirm),
+                    progression.coins
+                ),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn answer_continue_prompt(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    touches: Res<Touches>,
+    mut offer: ResMut<ContinueOffer>,
+    mut progression: ResMut<Progression>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(bindings.confirm) || touches.any_just_pressed() {
+        progression.coins -= CONTINUE_COST;
+        progression.save();
+        offer.accepted = true;
+        *run_end = RunEnd::default();
+        next_state.set(GameState::Playing);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn update_countdown(
+    time: Res<Time>,
+    mut offer: ResMut<ContinueOffer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut texts: Query<&mut Text, With<CountdownText>>,
+) {
+    if offer.accepted {
+        return;
+    }
+    if offer.countdown.tick(time.delta()).finished() {
+        next_state.set(GameState::GameOver);
+    }
+    let remaining = offer.countdown.remaining_secs().ceil();
+    for mut text in &mut texts {
+        text.sections[0].value = format!("{remaining:.0}");
+    }
+}
+
+// the head is still touching whatever killed it, so the snake takes back its
+// last move, head and body each a cell back along the body, and carries on
+// heading the same way
+#[allow(clippy::type_complexity)]
+fn resume_run(
+    mut commands: Commands,
+    mut offer: ResMut<ContinueOffer>,
+    mut snake: ResMut<Snake>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    mut snake_heads: Query<&mut GridPosition, With<SnakeHead>>,
+    mut segments: Query<(Entity, &mut GridPosition), (With<SnakeBodySegment>, Without<SnakeHead>)>,
+) {
+    if !std::mem::take(&mut offer.accepted) {
+        return;
+    }
+    snake.turn_queue.clear();
+
+    // segments follow the head in query order, so the tail comes last
+    let (entities, cells): (Vec<Entity>, Vec<GridPosition>) = segments
+        .iter()
+        .map(|(entity, cell)| (entity, *cell))
+        .unzip();
+    let kept = entities.len().saturating_sub(LOST_SEGMENTS);
+    for &entity in &entities[kept..] {
+        segment_pool.release(&mut commands, entity);
+    }
+
+    for mut head in &mut snake_heads {
+        *head = match cells.first() {
+            Some(&cell) => cell,
+            // nothing behind the head to step back onto
+            None => GridPosition(head.0 - snake.direction.vector().as_ivec2()),
+        };
+    }
+    for ((_, mut cell), &behind) in segments.iter_mut().zip(cells.iter().skip(1)).take(kept) {
+        *cell = behind;
+    }
+}
+
+fn reset_offer(mut offer: ResMut<ContinueOffer>) {
+    *offer = ContinueOffer::default();
+}
@@ -0,0 +1,219 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{display_final_score, input::key_name, menu::MenuItem, GameState, OnGameOverScreen};
+
+// C is Continue on the main menu, so the credits have another key there
+pub const MENU_CREDITS_KEY: KeyCode = KeyCode::KeyR;
+const GAME_OVER_CREDITS_KEY: KeyCode = KeyCode::KeyC;
+const AUTO_SCROLL_SPEED: f32 = 40.0;
+const MANUAL_SCROLL_SPEED: f32 = 300.0;
+
+const CREDITS: &[(&str, &[&str])] = &[
+    ("Snake", &["a toy snake game built with Bevy"]),
+    (
+        "Contributors",
+        &["fmendez", "and everyone who sent a pull request"],
+    ),
+    (
+        "Assets",
+        &["Fira Mono by Mozilla and Carrois Apostrophe, SIL Open Font License 1.1"],
+    ),
+    (
+        "Built with",
+        &[
+            "Bevy, MIT or Apache-2.0",
+            "rand, MIT or Apache-2.0",
+            "serde and ron, MIT or Apache-2.0",
+            "chrono, MIT or Apache-2.0",
+            "image, MIT or Apache-2.0",
+        ],
+    ),
+    ("Thanks for playing!", &[]),
+];
+
+/// Scrolling credits, opened with R from the main menu or C from the
+/// game-over screen. Up and Down scroll by hand; Escape or Space skips back
+/// to whichever screen they were opened from.
+pub struct CreditsPlugin;
+
+impl Plugin for CreditsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CreditsOpenedFrom>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_credits_hint.after(display_final_score),
+            )
+            .add_systems(
+                Update,
+                open_credits
+                    .run_if(in_state(GameState::MainMenu).or_else(in_state(GameState::GameOver))),
+            )
+            .add_systems(OnEnter(GameState::Credits), spawn_credits)
+            .add_systems(OnExit(GameState::Credits), despawn_credits)
+            .add_systems(Update, scroll_credits.run_if(in_state(GameState::Credits)));
+    }
+}
+
+#[derive(Component)]
+struct CreditsScreen;
+
+/// The screen the credits go back to.
+#[derive(Resource)]
+struct CreditsOpenedFrom(GameState);
+
+impl Default for CreditsOpenedFrom {
+    fn default() -> Self {
+        CreditsOpenedFrom(GameState::GameOver)
+    }
+}
+
+/// The column of credit lines, rolled upwards by moving its top edge.
+#[derive(Component)]
+struct CreditsRoll {
+    offset: f32,
+}
+
+fn display_credits_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Press {} for credits", key_name(GAME_OVER_CREDITS_KEY)),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(20.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(GAME_OVER_CREDITS_KEY),
+        OnGameOverScreen,
+    ));
+}
+
+fn open_credits(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut opened_from: ResMut<CreditsOpenedFrom>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let key = if *state.get() == GameState::MainMenu {
+        MENU_CREDITS_KEY
+    } else {
+        GAME_OVER_CREDITS_KEY
+    };
+    if keyboard_input.just_pressed(key) {
+        opened_from.0 = state.get().clone();
+        next_state.set(GameState::Credits);
+    }
+}
+
+fn spawn_credits(mut commands: Commands, windows: Query<&Window, With<PrimaryWindow>>) {
+    // the roll starts just below the bottom edge
+    let offset = windows
+        .get_single()
+        .map(|window| window.height())
+        .unwrap_or_default();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            CreditsScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(offset),
+                            width: Val::Percent(100.),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(8.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    CreditsRoll { offset },
+                ))
+                .with_children(|roll| {
+                    for (heading, lines) in CREDITS {
+                        roll.spawn(
+                            TextBundle::from_section(
+                                *heading,
+                                TextStyle {
+                                    font_size: 36.0,
+                                    color: Color::rgb(0.5, 0.5, 1.0),
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::top(Val::Px(40.0)),
+                                ..default()
+                            }),
+                        );
+                        for line in *lines {
+                            roll.spawn(TextBundle::from_section(
+                                *line,
+                                TextStyle {
+                                    font_size: 20.0,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            ));
+                        }
+                    }
+                });
+        });
+}
+
+fn scroll_credits(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    opened_from: Res<CreditsOpenedFrom>,
+    mut rolls: Query<(&mut CreditsRoll, &mut Style, &Node)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::Space]) {
+        next_state.set(opened_from.0.clone());
+        return;
+    }
+
+    let speed = if keyboard_input.pressed(KeyCode::ArrowUp) {
+        -MANUAL_SCROLL_SPEED
+    } else if keyboard_input.pressed(KeyCode::ArrowDown) {
+        MANUAL_SCROLL_SPEED
+    } else {
+        AUTO_SCROLL_SPEED
+    };
+
+    for (mut roll, mut style, node) in &mut rolls {
+        roll.offset -= speed * time.delta_seconds();
+        style.top = Val::Px(roll.offset);
+        // done once the last line has rolled off the top
+        if node.size().y > 0.0 && roll.offset < -node.size().y {
+            next_state.set(opened_from.0.clone());
+        }
+    }
+}
+
+fn despawn_credits(mut commands: Commands, screens: Query<Entity, With<CreditsScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
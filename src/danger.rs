@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::ColliderKind, move_snake, new_run, settings::GameSettings, GameState, OnGameScreen,
+    SafeArea, Scoreboard, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+// the segments right behind the head are always next to it
+const NECK_SEGMENTS: usize = 3;
+const POINTS_PER_MOVE: f32 = 0.2;
+// each move in a row spent in danger adds this to the multiplier, up to the max
+const MULTIPLIER_STEP: f32 = 0.1;
+const MAX_MULTIPLIER: f32 = 3.0;
+const FLAME_COLOR: Color = Color::rgb(1.0, 0.5, 0.1);
+const FLAME_SIZE: f32 = 14.0;
+
+/// Danger bonus modifier: every move the head makes in a cell next to a wall
+/// or its own body pays a fraction of a point, and the longer the streak the
+/// bigger the multiplier, shown as a flame in the HUD.
+pub struct DangerBonusPlugin;
+
+impl Plugin for DangerBonusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DangerStreak>()
+            .add_systems(OnEnter(GameState::Playing), reset_streak.run_if(new_run))
+            .add_systems(
+                Update,
+                (
+                    reward_danger.after(move_snake),
+                    update_flame.run_if(resource_changed::<DangerStreak>),
+                )
+                    .chain()
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.danger_bonus)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+struct DangerStreak {
+    moves: u32,
+    // bonuses are fractional, so the remainder carries to the next move
+    carry: f32,
+}
+
+impl DangerStreak {
+    fn multiplier(&self) -> f32 {
+        (1.0 + self.moves as f32 * MULTIPLIER_STEP).min(MAX_MULTIPLIER)
+    }
+}
+
+#[derive(Component)]
+struct DangerFlame;
+
+#[derive(Component)]
+struct DangerFlameText;
+
+fn reset_streak(mut streak: ResMut<DangerStreak>) {
+    *streak = DangerStreak::default();
+}
+
+fn in_danger(
+    head: Vec2,
+    walls: impl IntoIterator<Item = (Vec2, Vec2)>,
+    body: impl IntoIterator<Item = Vec2>,
+) -> bool {
+    let reach = SEGMENT_SIZE.x;
+    // within a cell of a wall's surface
+    let by_wall = walls
+        .into_iter()
+        .any(|(center, half_size)| ((head - center).abs() - half_size).max_element() <= reach);
+    by_wall
+        || body
+            .into_iter()
+            .skip(NECK_SEGMENTS)
+            .any(|segment| (head - segment).abs().max_element() <= reach)
+}
+
+fn reward_danger(
+    mut streak: ResMut<DangerStreak>,
+    mut scoreboard: ResMut<Scoreboard>,
+    heads: Query<&Transform, (With<SnakeHead>, Changed<Transform>)>,
+    colliders: Query<(&Transform, &ColliderKind)>,
+    body: Query<&Transform, With<SnakeBodySegment>>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let walls = colliders
+        .iter()
+        .filter(|(_, kind)| **kind == ColliderKind::Wall)
+        .map(|(transform, _)| {
+            (
+                transform.translation.truncate(),
+                transform.scale.truncate() / 2.0,
+            )
+        });
+    // body segments come in the order they follow the head
+    let segments = body
+        .iter()
+        .map(|transform| transform.translation.truncate());
+    if !in_danger(head.translation.truncate(), walls, segments) {
+        if streak.moves > 0 {
+            streak.moves = 0;
+        }
+        return;
+    }
+
+    streak.moves += 1;
+    let earned = streak.carry + POINTS_PER_MOVE * streak.multiplier();
+    if earned >= 1.0 {
+        scoreboard.score += earned.floor() as u32;
+    }
+    streak.carry = earned.fract();
+}
+
+fn update_flame(
+    mut commands: Commands,
+    streak: Res<DangerStreak>,
+    safe_area: Res<SafeArea>,
+    flames: Query<Entity, With<DangerFlame>>,
+    mut texts: Query<&mut Text, With<DangerFlameText>>,
+) {
+    if streak.moves == 0 {
+        for flame in &flames {
+            commands.entity(flame).despawn_recursive();
+        }
+        return;
+    }
+    let label = format!("x{:.1}", streak.multiplier());
+    if let Ok(mut text) = texts.get_single_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(40.0 + safe_area.top),
+                    right: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            DangerFlame,
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(FLAME_SIZE),
+                    height: Val::Px(FLAME_SIZE),
+                    ..default()
+                },
+                background_color: FLAME_COLOR.into(),
+                ..default()
+            });
+            parent.spawn((
+                TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font_size: 18.0,
+                        color: FLAME_COLOR,
+                        ..default()
+                    },
+                ),
+                DangerFlameText,
+            ));
+        });
+}
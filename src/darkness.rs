@@ -0,0 +1,118 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::{settings::GameSettings, Apple, GameState, OnGameScreen, SnakeBodySegment, SnakeHead};
+
+const LIGHT_RADIUS: f32 = 170.0;
+const LIGHT_SHRINK_PER_SEGMENT: f32 = 2.0;
+const MIN_LIGHT_RADIUS: f32 = 70.0;
+// width of the fade from light to dark at the edge of the circle
+const LIGHT_SOFTNESS: f32 = 40.0;
+// wide enough to cover the arena at any window size
+const OVERLAY_SIZE: f32 = 4000.0;
+// the overlay sits above the arena, and apples glow through it
+const OVERLAY_Z: f32 = 5.0;
+const GLOWING_APPLE_Z: f32 = 6.0;
+
+/// Darkness modifier: the arena is dark except for a circle of light around
+/// the head that shrinks as the snake grows. Apples glow through the dark.
+pub struct DarknessPlugin;
+
+impl Plugin for DarknessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<DarknessMaterial>::default())
+            .add_systems(
+                Update,
+                (spawn_darkness, (follow_head_with_light, light_up_apples))
+                    .chain()
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.darkness)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Overlay material drawn by `shaders/darkness.wgsl`.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct DarknessMaterial {
+    #[uniform(0)]
+    center: Vec2,
+    #[uniform(0)]
+    radius: f32,
+    #[uniform(0)]
+    softness: f32,
+    #[uniform(0)]
+    color: Vec4,
+}
+
+impl Material2d for DarknessMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/darkness.wgsl".into()
+    }
+}
+
+#[derive(Component)]
+struct DarknessOverlay;
+
+fn light_radius(segments: usize) -> f32 {
+    (LIGHT_RADIUS - segments as f32 * LIGHT_SHRINK_PER_SEGMENT).max(MIN_LIGHT_RADIUS)
+}
+
+fn spawn_darkness(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DarknessMaterial>>,
+    overlays: Query<(), With<DarknessOverlay>>,
+) {
+    if !overlays.is_empty() {
+        return;
+    }
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Rectangle::new(OVERLAY_SIZE, OVERLAY_SIZE))),
+            material: materials.add(DarknessMaterial {
+                center: Vec2::ZERO,
+                radius: LIGHT_RADIUS,
+                softness: LIGHT_SOFTNESS,
+                color: Vec4::new(0.0, 0.0, 0.0, 0.97),
+            }),
+            transform: Transform::from_xyz(0.0, 0.0, OVERLAY_Z),
+            ..default()
+        },
+        DarknessOverlay,
+        OnGameScreen,
+    ));
+}
+
+fn follow_head_with_light(
+    mut materials: ResMut<Assets<DarknessMaterial>>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    segments: Query<(), With<SnakeBodySegment>>,
+    overlays: Query<&Handle<DarknessMaterial>, With<DarknessOverlay>>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let center = head.translation.truncate();
+    let radius = light_radius(segments.iter().count());
+    for handle in &overlays {
+        let Some(material) = materials.get(handle) else {
+            continue;
+        };
+        // only touched when it moves, so the material isn't re-uploaded every frame
+        if material.center != center || material.radius != radius {
+            if let Some(material) = materials.get_mut(handle) {
+                material.center = center;
+                material.radius = radius;
+            }
+        }
+    }
+}
+
+fn light_up_apples(mut apples: Query<&mut Transform, Added<Apple>>) {
+    for mut transform in &mut apples {
+        transform.translation.z = GLOWING_APPLE_Z;
+    }
+}
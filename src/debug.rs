@@ -0,0 +1,198 @@
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    math::vec2,
+    prelude::*,
+};
+
+use crate::{
+    collider_half_size, rules::GameRules, settings::GameSettings, Apple, Collider, GameState,
+    Snake, SnakeBodySegment, SnakeHead, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, SEGMENT_SIZE, TOP_WALL,
+};
+
+const SNAKE_SEGMENTS: DiagnosticPath = DiagnosticPath::const_new("snake/segments");
+const APPLES: DiagnosticPath = DiagnosticPath::const_new("snake/apples");
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+
+        app.register_diagnostic(Diagnostic::new(SNAKE_SEGMENTS))
+            .register_diagnostic(Diagnostic::new(APPLES))
+            .init_resource::<DebugOverlay>()
+            .init_resource::<HitboxGizmos>()
+            .add_systems(
+                Update,
+                (
+                    toggle_hitbox_gizmos,
+                    draw_hitbox_gizmos.run_if(|gizmos: Res<HitboxGizmos>| gizmos.visible),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    measure_gameplay,
+                    toggle_debug_overlay,
+                    update_debug_overlay.run_if(|overlay: Res<DebugOverlay>| overlay.visible),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct DebugOverlay {
+    visible: bool,
+}
+
+#[derive(Component)]
+struct DebugOverlayText;
+
+#[derive(Resource, Default)]
+struct HitboxGizmos {
+    visible: bool,
+}
+
+fn measure_gameplay(
+    mut diagnostics: Diagnostics,
+    segments: Query<(), With<SnakeBodySegment>>,
+    apples: Query<(), With<Apple>>,
+) {
+    diagnostics.add_measurement(&SNAKE_SEGMENTS, || segments.iter().count() as f64);
+    diagnostics.add_measurement(&APPLES, || apples.iter().count() as f64);
+}
+
+fn toggle_debug_overlay(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    texts: Query<Entity, With<DebugOverlayText>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+
+    if !overlay.visible {
+        for entity in &texts {
+            commands.entity(entity).despawn_recursive();
+        }
+    } else if texts.is_empty() {
+        commands.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::YELLOW,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            }),
+            DebugOverlayText,
+        ));
+    }
+}
+
+fn update_debug_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    snake: Res<Snake>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<DebugOverlayText>>,
+) {
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+
+    let report = format!(
+        "FPS: {:.0}\nFrame time: {:.2} ms\nEntities: {:.0}\nSegments: {:.0}\nApples: {:.0}\nTick rate: {:.1}/s\nState: {:?}",
+        smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+        smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+        smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+        smoothed(&SNAKE_SEGMENTS),
+        smoothed(&APPLES),
+        1.0 / snake.move_cooldown.duration().as_secs_f32(),
+        state.get(),
+    );
+
+    for mut text in &mut texts {
+        text.sections[0].value.clone_from(&report);
+    }
+}
+
+fn toggle_hitbox_gizmos(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut hitbox_gizmos: ResMut<HitboxGizmos>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        hitbox_gizmos.visible = !hitbox_gizmos.visible;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_hitbox_gizmos(
+    mut gizmos: Gizmos,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    snake_head_query: Query<&Transform, With<SnakeHead>>,
+    collider_query: Query<(&Transform, Option<&Apple>), (With<Collider>, Without<SnakeHead>)>,
+) {
+    let grid_color = Color::rgba(1.0, 1.0, 1.0, 0.08);
+    let mut x = LEFT_WALL;
+    while x <= RIGHT_WALL {
+        gizmos.line_2d(vec2(x, BOTTOM_WALL), vec2(x, TOP_WALL), grid_color);
+        x += SEGMENT_SIZE.x;
+    }
+    let mut y = BOTTOM_WALL;
+    while y <= TOP_WALL {
+        gizmos.line_2d(vec2(LEFT_WALL, y), vec2(RIGHT_WALL, y), grid_color);
+        y += SEGMENT_SIZE.y;
+    }
+
+    // same boxes `check_for_collisions` intersects
+    for transform in &snake_head_query {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            settings.hitboxes.head() * rules.head_hitbox_scale,
+            Color::CYAN,
+        );
+        if settings.hitboxes.forgiving {
+            gizmos.rect_2d(
+                transform.translation.truncate(),
+                0.0,
+                settings.hitboxes.lethal_head() * rules.head_hitbox_scale,
+                Color::ORANGE_RED,
+            );
+        }
+    }
+    for (transform, maybe_apple) in &collider_query {
+        let color = if maybe_apple.is_some() {
+            Color::YELLOW
+        } else {
+            Color::FUCHSIA
+        };
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            collider_half_size(transform, maybe_apple.is_some(), &settings.hitboxes) * 2.0,
+            color,
+        );
+    }
+}
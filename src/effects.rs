@@ -0,0 +1,319 @@
+use std::f32::consts::TAU;
+
+use bevy::{math::vec2, prelude::*, transform::TransformSystem};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    settings::GameSettings,
+    Apple, GameAssets, GameState, OnGameScreen, RunEnd, Snake, SnakeBodySegment, SnakeHead,
+    SEGMENT_SIZE,
+};
+
+const SPARKS: usize = 16;
+const SPARK_SIZE: f32 = 5.0;
+const SPARK_SPEED: f32 = 180.0;
+// how much of its speed a spark keeps each second
+const SPARK_DRAG: f32 = 0.05;
+const SPARK_SECONDS: f32 = 0.5;
+const SHAKE_SECONDS: f32 = 0.35;
+const SHAKE_DISTANCE: f32 = 8.0;
+const FLASH_SECONDS: f32 = 0.6;
+// on-off blinks per second
+const FLASH_RATE: f32 = 10.0;
+const FLASH_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.85);
+
+/// How a run is drawn, kept apart from the rules: nothing here touches a
+/// `Transform` the game logic reads. The snake slides between cells instead
+/// of stepping, its head turned the way it's going and its tail towards the
+/// body, by drawing each piece between the cell it left and the one it's in
+/// as the move timer runs. Eaten apples burst into sparks, and a death
+/// flashes the snake and shakes the camera, unless reduced motion is set in
+/// the settings file.
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraShake>()
+            .add_systems(
+                Update,
+                burst_eaten_apples
+                    .after(DetectCollisions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), start_death_effects)
+            .add_systems(Update, (fly_sparks, blink_death_flash))
+            // drawn positions overwrite what the transform propagation worked out
+            .add_systems(
+                PostUpdate,
+                ((start_sliding, slide_snake).chain(), shake_camera)
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// The cells a snake piece is drawn between, as arena positions.
+#[derive(Component)]
+struct Sliding {
+    from: Vec2,
+    to: Vec2,
+}
+
+#[derive(Component)]
+struct Spark {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+struct DeathFlash(Timer);
+
+#[derive(Resource, Default)]
+struct CameraShake(Option<Timer>);
+
+#[allow(clippy::type_complexity)]
+fn start_sliding(
+    mut commands: Commands,
+    pieces: Query<(Entity, &Transform), Or<(Added<SnakeHead>, Added<SnakeBodySegment>)>>,
+) {
+    for (entity, transform) in &pieces {
+        let position = transform.translation.truncate();
+        commands.entity(entity).insert(Sliding {
+            from: position,
+            to: position,
+        });
+    }
+}
+
+// where a piece is drawn `progress` of the way through the current move
+fn slide(sliding: &mut Sliding, transform: &Transform, moved: bool, progress: f32) -> Vec2 {
+    let position = transform.translation.truncate();
+    if moved {
+        sliding.from = sliding.to;
+        sliding.to = position;
+    } else if position != sliding.to {
+        // put somewhere between moves, like by a continue
+        sliding.from = position;
+        sliding.to = position;
+    }
+    // wrapping through an edge, or growing out from under the head, is drawn as the jump
+    if sliding.from.distance(sliding.to) > SEGMENT_SIZE.x * 1.5 {
+        return position;
+    }
+    sliding.from.lerp(sliding.to, progress)
+}
+
+// the head and tail sprites face up
+fn facing(direction: Vec2) -> Quat {
+    Quat::from_rotation_z(Vec2::Y.angle_between(direction))
+}
+
+fn drawn_at(transform: &Transform, position: Vec2, rotation: Quat) -> GlobalTransform {
+    GlobalTransform::from(Transform {
+        translation: position.extend(transform.translation.z),
+        rotation,
+        scale: transform.scale,
+    })
+}
+
+// only swapped when it differs, so the material isn't marked changed every frame
+fn dress(mut material: Mut<Handle<ColorMaterial>>, look: &Handle<ColorMaterial>) {
+    if *material != *look {
+        *material = look.clone();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn slide_snake(
+    snake: Res<Snake>,
+    game_assets: Res<GameAssets>,
+    mut heads: Query<
+        (
+            Ref<Transform>,
+            &mut GlobalTransform,
+            &mut Sliding,
+            &mut Handle<ColorMaterial>,
+        ),
+        With<SnakeHead>,
+    >,
+    mut segments: Query<
+        (
+            &Transform,
+            &mut GlobalTransform,
+            &mut Sliding,
+            &mut Handle<ColorMaterial>,
+        ),
+        (With<SnakeBodySegment>, Without<SnakeHead>),
+    >,
+) {
+    let Ok((head_transform, mut head_global, mut head_sliding, head_material)) =
+        heads.get_single_mut()
+    else {
+        return;
+    };
+    // every move of the head is a move of the whole snake
+    let moved = head_transform.is_changed();
+    let progress = snake.move_cooldown.fraction();
+    let head = slide(&mut head_sliding, &head_transform, moved, progress);
+    let head_rotation = facing(snake.direction.vector());
+    *head_global = drawn_at(&head_transform, head, head_rotation);
+    dress(head_material, &game_assets.snake_head_material);
+
+    // neck first and tail last, the order the body follows the head in
+    let tail_index = segments.iter().count().saturating_sub(1);
+    let mut ahead = head;
+    for (index, (transform, mut global, mut sliding, material)) in segments.iter_mut().enumerate() {
+        let position = slide(&mut sliding, transform, moved, progress);
+        if index == tail_index {
+            let towards_body = ahead - position;
+            let rotation = if towards_body.length_squared() > 0.0 {
+                facing(towards_body)
+            } else {
+                head_rotation
+            };
+            *global = drawn_at(transform, position, rotation);
+            dress(material, &game_assets.snake_tail_material);
+        } else {
+            *global = drawn_at(transform, position, Quat::IDENTITY);
+            dress(material, &game_assets.snake_material);
+        }
+        ahead = position;
+    }
+}
+
+fn burst_eaten_apples(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    settings: Res<GameSettings>,
+    materials: Res<Assets<ColorMaterial>>,
+    apples: Query<(&Transform, &Handle<ColorMaterial>), With<Apple>>,
+) {
+    let mut rng = thread_rng();
+    let sparks = (SPARKS as f32 * settings.graphics.quality().effect_density()) as usize;
+    for event in collision_events.read() {
+        if event.kind != ColliderKind::Apple {
+            continue;
+        }
+        // the eaten apple is only replaced once this frame's commands run
+        let Ok((transform, material)) = apples.get(event.collider) else {
+            continue;
+        };
+        let color = materials
+            .get(material)
+            .map_or(Color::RED, |material| material.color);
+        for _ in 0..sparks {
+            let angle = rng.gen_range(0.0..TAU);
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        translation: transform.translation.truncate().extend(1.0),
+                        scale: Vec3::new(SPARK_SIZE, SPARK_SIZE, 1.0),
+                        ..default()
+                    },
+                    sprite: Sprite { color, ..default() },
+                    ..default()
+                },
+                Spark {
+                    velocity: Vec2::from_angle(angle) * SPARK_SPEED * rng.gen_range(0.4..1.0),
+                    lifetime: Timer::from_seconds(SPARK_SECONDS, TimerMode::Once),
+                },
+                OnGameScreen,
+            ));
+        }
+    }
+}
+
+fn fly_sparks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut sparks: Query<(Entity, &mut Transform, &mut Sprite, &mut Spark)>,
+) {
+    for (entity, mut transform, mut sprite, mut spark) in &mut sparks {
+        if spark.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        spark.velocity *= SPARK_DRAG.powf(time.delta_seconds());
+        transform.translation += (spark.velocity * time.delta_seconds()).extend(0.0);
+        let remaining = spark.lifetime.fraction_remaining();
+        transform.scale = Vec3::new(SPARK_SIZE * remaining, SPARK_SIZE * remaining, 1.0);
+        sprite.color.set_a(remaining);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn start_death_effects(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    run_end: Res<RunEnd>,
+    mut shake: ResMut<CameraShake>,
+    snake: Query<&GlobalTransform, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+) {
+    // pausing and winning leave Playing too
+    if run_end.death_cause().is_none() {
+        return;
+    }
+    if !settings.accessibility.reduced_motion {
+        shake.0 = Some(Timer::from_seconds(SHAKE_SECONDS, TimerMode::Once));
+    }
+    // drawn over the snake where it died, outliving it into the game-over screen
+    for piece in &snake {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: piece.translation().truncate().extend(2.0),
+                    scale: SEGMENT_SIZE.extend(1.0),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: FLASH_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            DeathFlash(Timer::from_seconds(FLASH_SECONDS, TimerMode::Once)),
+        ));
+    }
+}
+
+fn blink_death_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut DeathFlash, &mut Visibility)>,
+) {
+    for (entity, mut flash, mut visibility) in &mut flashes {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let lit = ((flash.0.elapsed_secs() * FLASH_RATE) as u32).is_multiple_of(2);
+        visibility.set_if_neq(if lit {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<(&Transform, &mut GlobalTransform), With<Camera2d>>,
+) {
+    let Some(timer) = shake.0.as_mut() else {
+        return;
+    };
+    let finished = timer.tick(time.delta()).finished();
+    // eases out, ending exactly where the camera really is
+    let distance = SHAKE_DISTANCE * timer.fraction_remaining();
+    let mut rng = thread_rng();
+    for (transform, mut global) in &mut cameras {
+        let offset = vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * distance;
+        *global = GlobalTransform::from(
+            transform.with_translation(transform.translation + offset.extend(0.0)),
+        );
+    }
+    if finished {
+        shake.0 = None;
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::{asset::UntypedAssetLoadFailedEvent, prelude::*};
+
+use crate::{
+    input::{key_name, KeyBindings},
+    storage::{self, UnreadableSave},
+    GameState,
+};
+
+/// Shows recoverable problems (unreadable saves, assets that failed to load,
+/// broken game state) on an error screen instead of panicking.
+pub struct ErrorScreenPlugin;
+
+impl Plugin for ErrorScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameError>()
+            .init_resource::<ErrorReport>()
+            .add_systems(Update, collect_errors)
+            .add_systems(
+                Update,
+                (show_error_screen, error_screen_input)
+                    .chain()
+                    .run_if(in_state(GameState::Error)),
+            )
+            .add_systems(OnExit(GameState::Error), despawn_error_screen);
+    }
+}
+
+/// Something went wrong that the player should hear about; the message is shown as is.
+#[derive(Event)]
+pub struct GameError(pub String);
+
+#[derive(Resource)]
+struct ErrorReport {
+    problems: Vec<String>,
+    unreadable: Vec<UnreadableSave>,
+    return_to: GameState,
+}
+
+impl Default for ErrorReport {
+    fn default() -> Self {
+        ErrorReport {
+            problems: Vec::new(),
+            unreadable: Vec::new(),
+            return_to: GameState::Playing,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ErrorScreen;
+
+fn collect_errors(
+    mut game_errors: EventReader<GameError>,
+    mut asset_failures: EventReader<UntypedAssetLoadFailedEvent>,
+    state: Res<State<GameState>>,
+    mut report: ResMut<ErrorReport>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let unreadable = storage::take_unreadable();
+    let mut problems: Vec<String> = unreadable
+        .iter()
+        .map(|save| format!("Saved {} data is unreadable: {}", save.key, save.error))
+        .collect();
+    problems.extend(
+        asset_failures
+            .read()
+            .map(|failure| format!("Couldn't load {}: {}", failure.path, failure.error)),
+    );
+    problems.extend(game_errors.read().map(|error| error.0.clone()));
+    if problems.is_empty() {
+        return;
+    }
+
+    for problem in &problems {
+        error!("{problem}");
+    }
+    report.problems.extend(problems);
+    report.unreadable.extend(unreadable);
+    if *state.get() != GameState::Error {
+        report.return_to = state.get().clone();
+        next_state.set(GameState::Error);
+    }
+}
+
+fn show_error_screen(
+    mut commands: Commands,
+    report: Res<ErrorReport>,
+    bindings: Res<KeyBindings>,
+    screens: Query<Entity, With<ErrorScreen>>,
+) {
+    if !report.is_changed() && !screens.is_empty() {
+        return;
+    }
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let mut options = format!("Press {} to continue", key_name(bindings.confirm));
+    if !report.unreadable.is_empty() {
+        options.push_str(" with a backup of the unreadable saves, or R to reset them");
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    padding: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            ErrorScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Something went wrong",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::RED,
+                    ..default()
+                },
+            ));
+            for problem in &report.problems {
+                parent.spawn(TextBundle::from_section(
+                    problem.clone(),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                options,
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn error_screen_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut report: ResMut<ErrorReport>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let reset = keyboard_input.just_pressed(KeyCode::KeyR) && !report.unreadable.is_empty();
+    if !reset && !keyboard_input.just_pressed(bindings.confirm) {
+        return;
+    }
+
+    for save in &report.unreadable {
+        if reset {
+            save.discard();
+        } else {
+            save.back_up();
+        }
+    }
+    next_state.set(report.return_to.clone());
+    *report = ErrorReport::default();
+}
+
+fn despawn_error_screen(mut commands: Commands, screens: Query<Entity, With<ErrorScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,99 @@
+use bevy::{math::bounding::Aabb2d, prelude::*};
+
+use crate::{
+    arena::WallThickness,
+    collider_half_size,
+    collision::{sync_collision_grid, ColliderKind, DetectCollisions},
+    move_snake,
+    settings::GameSettings,
+    sim, Apple, Collider, GameState, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+// apples step once for every this many snake moves
+const FLEE_EVERY_MOVES: u32 = 3;
+
+/// Fleeing-apples modifier: every few moves each apple steps one cell away
+/// from the head, sliding along walls when cornered, so eating becomes a chase.
+pub struct FleeingApplesPlugin;
+
+impl Plugin for FleeingApplesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            flee_from_head
+                .after(move_snake)
+                .before(sync_collision_grid)
+                .before(DetectCollisions)
+                .run_if(|settings: Res<GameSettings>| settings.modifiers.fleeing_apples)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn flee_from_head(
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    // snake moves since the apples last stepped
+    mut moves: Local<u32>,
+    heads: Query<Ref<Transform>, With<SnakeHead>>,
+    body: Query<&Transform, (With<SnakeBodySegment>, Without<SnakeHead>)>,
+    colliders: Query<&Transform, (With<Collider>, With<ColliderKind>, Without<Apple>)>,
+    mut apples: Query<&mut Transform, (With<Apple>, Without<SnakeHead>, Without<SnakeBodySegment>)>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    if !head.is_changed() {
+        return;
+    }
+    *moves += 1;
+    if *moves < FLEE_EVERY_MOVES {
+        return;
+    }
+    *moves = 0;
+
+    let hitboxes = &settings.hitboxes;
+    let head_position = head.translation.truncate();
+    let mut blocked: Vec<Aabb2d> = colliders
+        .iter()
+        .map(|transform| {
+            Aabb2d::new(
+                transform.translation.truncate(),
+                collider_half_size(transform, false, hitboxes),
+            )
+        })
+        .chain(
+            body.iter()
+                .map(|transform| transform.translation.truncate())
+                .chain([head_position])
+                .map(|position| Aabb2d::new(position, SEGMENT_SIZE / 2.0)),
+        )
+        .collect();
+    // apples keep out of each other's way too, each checked against where
+    // the ones before it ended up
+    let first_apple = blocked.len();
+    blocked.extend(apples.iter().map(|transform| {
+        Aabb2d::new(
+            transform.translation.truncate(),
+            collider_half_size(transform, true, hitboxes),
+        )
+    }));
+
+    for (index, mut transform) in apples.iter_mut().enumerate() {
+        let half_size = collider_half_size(&transform, true, hitboxes);
+        blocked.remove(first_apple + index);
+        let next = sim::flee_step(
+            transform.translation.truncate(),
+            head_position,
+            half_size,
+            &blocked,
+            wall_thickness.0,
+        );
+        blocked.insert(first_apple + index, Aabb2d::new(next, half_size));
+        if next != transform.translation.truncate() {
+            transform.translation.x = next.x;
+            transform.translation.y = next.y;
+        }
+    }
+}
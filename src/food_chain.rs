@@ -0,0 +1,307 @@
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        reflect::ReflectMapEntities,
+        system::SystemParam,
+    },
+    math::vec2,
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    arena::WallThickness,
+    collision::{sync_collision_grid, ColliderKind, CollisionEvent, DetectCollisions},
+    move_snake,
+    pool::EntityPool,
+    settings::GameSettings,
+    sim, snake_segment_spawn, Collider, Direction, Ending, GameAssets, GameRng, GameState,
+    OnGameScreen, RunEnd, Scoreboard, SnakeBodySegment, SnakeHead, BOTTOM_WALL, LEFT_WALL,
+    RIGHT_WALL, SEGMENT_SIZE, TOP_WALL,
+};
+
+const MAX_RIVALS: usize = 3;
+const RIVAL_LENGTHS: std::ops::RangeInclusive<usize> = 2..=7;
+const RIVAL_STEP_SECONDS: f32 = 0.2;
+const RIVAL_SPAWN_SECONDS: f32 = 8.0;
+const RIVAL_TURN_CHANCE: f64 = 0.15;
+// rivals never appear right on top of the player
+const RIVAL_SPAWN_CLEARANCE: f32 = 150.0;
+const RIVAL_HEAD_COLOR: Color = Color::rgb(1.0, 0.55, 0.0);
+const RIVAL_BODY_COLOR: Color = Color::rgb(0.75, 0.45, 0.1);
+
+/// Food-chain modifier: small AI snakes wander the arena. The player eats one
+/// by being strictly longer, absorbing its length, and dies to anything else.
+pub struct FoodChainPlugin;
+
+impl Plugin for FoodChainPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RivalClock>()
+            .register_type::<Rival>()
+            .add_systems(OnExit(GameState::GameOver), reset_rival_clock)
+            .add_systems(
+                Update,
+                (
+                    spawn_rivals
+                        .run_if(|settings: Res<GameSettings>| settings.modifiers.food_chain),
+                    move_rivals
+                        .after(move_snake)
+                        .before(sync_collision_grid)
+                        .before(DetectCollisions),
+                    resolve_rival_collisions.after(DetectCollisions),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Head of an AI snake, which owns the body segments trailing it.
+#[derive(Component, Reflect)]
+#[reflect(Component, MapEntities)]
+struct Rival {
+    direction: Direction,
+    body: Vec<Entity>,
+}
+
+impl MapEntities for Rival {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for segment in &mut self.body {
+            *segment = entity_mapper.map_entity(*segment);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct RivalClock {
+    step: Timer,
+    spawn: Timer,
+}
+
+impl Default for RivalClock {
+    fn default() -> Self {
+        RivalClock {
+            step: Timer::from_seconds(RIVAL_STEP_SECONDS, TimerMode::Repeating),
+            spawn: Timer::from_seconds(RIVAL_SPAWN_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct PlayerSnake<'w, 's> {
+    head: Query<'w, 's, &'static Transform, With<SnakeHead>>,
+    body: Query<'w, 's, (), With<SnakeBodySegment>>,
+    game_assets: Res<'w, GameAssets>,
+    segment_pool: ResMut<'w, EntityPool<SnakeBodySegment>>,
+}
+
+impl PlayerSnake<'_, '_> {
+    fn length(&self) -> usize {
+        self.body.iter().count() + 1
+    }
+
+    fn grow(&mut self, commands: &mut Commands, segments: usize) {
+        let Ok(head) = self.head.get_single() else {
+            return;
+        };
+        let position = head.translation;
+        for _ in 0..segments {
+            snake_segment_spawn(
+                commands,
+                &self.game_assets,
+                &mut self.segment_pool,
+                position.x,
+                position.y,
+            );
+        }
+    }
+}
+
+fn step(direction: Direction) -> Vec2 {
+    match direction {
+        Direction::Up => vec2(0.0, SEGMENT_SIZE.y),
+        Direction::Down => vec2(0.0, -SEGMENT_SIZE.y),
+        Direction::Left => vec2(-SEGMENT_SIZE.x, 0.0),
+        Direction::Right => vec2(SEGMENT_SIZE.x, 0.0),
+    }
+}
+
+fn turns(direction: Direction) -> [Direction; 2] {
+    match direction {
+        Direction::Up | Direction::Down => [Direction::Left, Direction::Right],
+        Direction::Left | Direction::Right => [Direction::Up, Direction::Down],
+    }
+}
+
+fn inside_arena(position: Vec2, wall_thickness: f32) -> bool {
+    let margin = wall_thickness + SEGMENT_SIZE.x;
+    (LEFT_WALL + margin..=RIGHT_WALL - margin).contains(&position.x)
+        && (BOTTOM_WALL + margin..=TOP_WALL - margin).contains(&position.y)
+}
+
+fn rival_sprite(position: Vec2, color: Color) -> SpriteBundle {
+    SpriteBundle {
+        transform: Transform {
+            translation: position.extend(-1.0),
+            scale: SEGMENT_SIZE.extend(1.0),
+            ..default()
+        },
+        sprite: Sprite { color, ..default() },
+        ..default()
+    }
+}
+
+fn reset_rival_clock(mut clock: ResMut<RivalClock>) {
+    *clock = RivalClock::default();
+}
+
+fn spawn_rivals(
+    mut commands: Commands,
+    time: Res<Time>,
+    wall_thickness: Res<WallThickness>,
+    mut clock: ResMut<RivalClock>,
+    mut rng: ResMut<GameRng>,
+    rivals: Query<(), With<Rival>>,
+    player_heads: Query<&Transform, With<SnakeHead>>,
+) {
+    if !clock.spawn.tick(time.delta()).just_finished() || rivals.iter().count() >= MAX_RIVALS {
+        return;
+    }
+    let Ok(player_head) = player_heads.get_single() else {
+        return;
+    };
+
+    let wall_thickness = wall_thickness.0;
+    let length = rng.0.gen_range(RIVAL_LENGTHS);
+    let direction = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ][rng.0.gen_range(0..4)];
+    let trail = -step(direction);
+
+    let spot = (0..10).find_map(|_| {
+        let random = sim::random_apple_position(&mut rng.0, wall_thickness);
+        let head = (random / SEGMENT_SIZE).round() * SEGMENT_SIZE;
+        let tail = head + trail * (length - 1) as f32;
+        let clear = head.distance(player_head.translation.truncate()) > RIVAL_SPAWN_CLEARANCE;
+        (clear && inside_arena(head, wall_thickness) && inside_arena(tail, wall_thickness))
+            .then_some(head)
+    });
+    let Some(head) = spot else {
+        return;
+    };
+
+    let body = (1..length)
+        .map(|index| {
+            commands
+                .spawn((
+                    rival_sprite(head + trail * index as f32, RIVAL_BODY_COLOR),
+                    Collider,
+                    ColliderKind::Rival,
+                    OnGameScreen,
+                ))
+                .id()
+        })
+        .collect();
+    commands.spawn((
+        rival_sprite(head, RIVAL_HEAD_COLOR),
+        Rival { direction, body },
+        Collider,
+        ColliderKind::Rival,
+        OnGameScreen,
+    ));
+}
+
+fn move_rivals(
+    time: Res<Time>,
+    wall_thickness: Res<WallThickness>,
+    mut clock: ResMut<RivalClock>,
+    mut rng: ResMut<GameRng>,
+    mut rivals: Query<(&mut Rival, &mut Transform)>,
+    mut bodies: Query<&mut Transform, Without<Rival>>,
+) {
+    if !clock.step.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let wall_thickness = wall_thickness.0;
+    for (mut rival, mut head_transform) in &mut rivals {
+        let head = head_transform.translation.truncate();
+
+        // wander: mostly keep going, sometimes turn, and always turn away from walls
+        let [left, right] = turns(rival.direction);
+        let (first, second) = if rng.0.gen_bool(0.5) {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        let preferred = if rng.0.gen_bool(RIVAL_TURN_CHANCE) {
+            [first, rival.direction, second]
+        } else {
+            [rival.direction, first, second]
+        };
+        rival.direction = preferred
+            .into_iter()
+            .find(|&direction| inside_arena(head + step(direction), wall_thickness))
+            .unwrap_or_else(|| rival.direction.opposite());
+
+        let next = head + step(rival.direction);
+        head_transform.translation.x = next.x;
+        head_transform.translation.y = next.y;
+
+        let mut positions: Vec<Vec2> = rival
+            .body
+            .iter()
+            .filter_map(|&segment| bodies.get(segment).ok())
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        sim::follow_head(&mut positions, head);
+        for (&segment, position) in rival.body.iter().zip(positions) {
+            if let Ok(mut transform) = bodies.get_mut(segment) {
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+            }
+        }
+    }
+}
+
+fn resolve_rival_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut commands: Commands,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player: PlayerSnake,
+    rivals: Query<(Entity, &Rival)>,
+) {
+    let mut eaten = Vec::new();
+    for event in collision_events.read() {
+        if event.kind != ColliderKind::Rival {
+            continue;
+        }
+        let Some((rival_entity, rival)) = rivals
+            .iter()
+            .find(|(head, rival)| *head == event.collider || rival.body.contains(&event.collider))
+        else {
+            continue;
+        };
+        if eaten.contains(&rival_entity) {
+            continue;
+        }
+
+        let rival_length = rival.body.len() + 1;
+        if player.length() > rival_length {
+            eaten.push(rival_entity);
+            commands.entity(rival_entity).despawn();
+            for &segment in &rival.body {
+                commands.entity(segment).despawn();
+            }
+            player.grow(&mut commands, rival_length);
+            scoreboard.score += rival_length as u32;
+        } else {
+            run_end.end(Ending::Died(ColliderKind::Rival));
+            next_state.set(GameState::GameOver);
+        }
+    }
+}
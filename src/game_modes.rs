@@ -0,0 +1,411 @@
+use std::{collections::HashSet, ops::RangeInclusive, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::{ArenaGrid, GridPosition},
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    display_final_score, new_run, obstacle_spawn_at, setup,
+    toast::Toast,
+    visual_cues::Cue,
+    Apple, Ending, GameRng, GameState, OnGameOverScreen, OnGameScreen, RunEnd, SafeArea,
+    SnakeBodySegment, SnakeHead,
+};
+
+const TIME_ATTACK_SECONDS: f32 = 90.0;
+const SURVIVAL_BLOCK_SECONDS: f32 = 10.0;
+// lasting this long wins a survival run
+const SURVIVAL_GOAL_SECONDS: f32 = 180.0;
+// block length in cells
+const BLOCK_LENGTHS: RangeInclusive<i32> = 2..=4;
+// cells around the head kept open when a block drops in
+const HEAD_CLEARANCE: i32 = 3;
+// a block that can't be placed after this many tries waits for the next one
+const BLOCK_TRIES: u32 = 20;
+// the clock turns red once it gets this low
+const HURRY_SECONDS: f32 = 10.0;
+const CLOCK_COLOR: Color = Color::WHITE;
+const HURRY_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const SUMMARY_COLOR: Color = Color::rgb(1.0, 0.84, 0.0);
+
+/// Game modes, picked on the main menu with M. Classic is the plain game.
+/// Time attack gives 90 seconds to eat as many apples as possible, with the
+/// time left shown under the score; running out ends the run, dying before
+/// that ends it early. Survival drops a new obstacle block into the arena
+/// every 10 seconds, away from the head, the snake and the apples, and
+/// lasting three minutes wins. The game-over screen sums up how the mode's
+/// run went.
+pub struct GameModesPlugin;
+
+impl Plugin for GameModesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>()
+            .init_resource::<ModeRun>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_mode_run.after(setup).run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    count_mode_apples.after(DetectCollisions),
+                    tick_mode_clock,
+                    end_time_attack.run_if(mode_is(GameMode::TimeAttack)),
+                    (drop_survival_blocks, end_survival).run_if(mode_is(GameMode::Survival)),
+                    update_mode_clock,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    record_mode_death.before(display_final_score),
+                    display_mode_summary.after(display_final_score),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    TimeAttack,
+    Survival,
+}
+
+impl GameMode {
+    const ALL: [GameMode; 3] = [GameMode::Classic, GameMode::TimeAttack, GameMode::Survival];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::Survival => "Survival",
+        }
+    }
+
+    /// The mode after this one on the menu, back to the first after the last.
+    pub fn next(self) -> Self {
+        let index = GameMode::ALL
+            .iter()
+            .position(|&mode| mode == self)
+            .unwrap_or_default();
+        GameMode::ALL[(index + 1) % GameMode::ALL.len()]
+    }
+
+    /// The how-to-play line for the mode, none for classic.
+    pub fn rule(self) -> Option<&'static str> {
+        match self {
+            GameMode::Classic => None,
+            GameMode::TimeAttack => {
+                Some("Time attack: eat as many apples as you can in 90 seconds.")
+            }
+            GameMode::Survival => Some(
+                "Survival: a new obstacle block drops in every 10 seconds; last three minutes to win.",
+            ),
+        }
+    }
+}
+
+fn mode_is(mode: GameMode) -> impl Fn(Res<GameMode>) -> bool {
+    move |current: Res<GameMode>| *current == mode
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModeOutcome {
+    TimeUp,
+    Survived,
+    Died,
+}
+
+/// The run being played, kept for the game-over summary. The clock runs
+/// down the time attack's time, or the time left to survive.
+#[derive(Resource, Debug)]
+pub struct ModeRun {
+    mode: GameMode,
+    clock: Timer,
+    next_block: Timer,
+    blocks: u32,
+    apples: u32,
+    outcome: Option<ModeOutcome>,
+}
+
+impl Default for ModeRun {
+    fn default() -> Self {
+        ModeRun::new(GameMode::Classic)
+    }
+}
+
+impl ModeRun {
+    fn new(mode: GameMode) -> Self {
+        let seconds = match mode {
+            GameMode::Survival => SURVIVAL_GOAL_SECONDS,
+            GameMode::Classic | GameMode::TimeAttack => TIME_ATTACK_SECONDS,
+        };
+        ModeRun {
+            mode,
+            clock: Timer::from_seconds(seconds, TimerMode::Once),
+            next_block: Timer::from_seconds(SURVIVAL_BLOCK_SECONDS, TimerMode::Repeating),
+            blocks: 0,
+            apples: 0,
+            outcome: None,
+        }
+    }
+
+    /// How far into the run the clock is.
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    /// Picks the run back up `elapsed` in with `apples` already eaten, the
+    /// next block as far along as it was.
+    pub fn resume_at(&mut self, elapsed: Duration, apples: u32) {
+        self.apples = apples;
+        self.clock.set_elapsed(elapsed);
+        let block = Duration::from_secs_f32(elapsed.as_secs_f32() % SURVIVAL_BLOCK_SECONDS);
+        self.next_block.set_elapsed(block);
+    }
+}
+
+#[derive(Component)]
+struct ModeClockText;
+
+#[derive(Component)]
+struct SurvivalBlock;
+
+pub fn start_mode_run(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    safe_area: Res<SafeArea>,
+    mut run: ResMut<ModeRun>,
+) {
+    *run = ModeRun::new(*mode);
+    if *mode == GameMode::Classic {
+        return;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 40.0,
+                color: CLOCK_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(82.0 + safe_area.top),
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        ModeClockText,
+        OnGameScreen,
+    ));
+}
+
+fn count_mode_apples(mut collision_events: EventReader<CollisionEvent>, mut run: ResMut<ModeRun>) {
+    run.apples += collision_events
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .count() as u32;
+}
+
+fn tick_mode_clock(time: Res<Time>, mut run: ResMut<ModeRun>) {
+    run.clock.tick(time.delta());
+}
+
+fn end_time_attack(
+    mut run: ResMut<ModeRun>,
+    mut run_end: ResMut<RunEnd>,
+    mut toasts: EventWriter<Toast>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if run.clock.just_finished() {
+        run.outcome = Some(ModeOutcome::TimeUp);
+        run_end.end(Ending::Over);
+        toasts.send(Toast("Time's up!".to_string()));
+        next_state.set(GameState::GameOver);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn drop_survival_blocks(
+    mut commands: Commands,
+    time: Res<Time>,
+    grid: Res<ArenaGrid>,
+    mut rng: ResMut<GameRng>,
+    mut run: ResMut<ModeRun>,
+    snake: Query<(&GridPosition, Has<SnakeHead>), Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    apples: Query<&Transform, With<Apple>>,
+    blocks: Query<&Transform, With<SurvivalBlock>>,
+    mut cues: EventWriter<Cue>,
+) {
+    if !run.next_block.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let head = snake
+        .iter()
+        .find(|(_, is_head)| *is_head)
+        .map(|(cell, _)| cell.0);
+    let occupied: HashSet<IVec2> = snake
+        .iter()
+        .map(|(cell, _)| cell.0)
+        .chain(
+            apples
+                .iter()
+                .chain(&blocks)
+                .map(|transform| GridPosition::containing(transform.translation.truncate()).0),
+        )
+        .collect();
+    let fits = |cell: &IVec2| {
+        cell.cmpge(grid.min).all()
+            && cell.cmple(grid.max).all()
+            && !head.is_some_and(|head| (*cell - head).abs().max_element() <= HEAD_CLEARANCE)
+            && !occupied.contains(cell)
+    };
+
+    for _ in 0..BLOCK_TRIES {
+        let start = IVec2::new(
+            rng.0.gen_range(grid.min.x..=grid.max.x),
+            rng.0.gen_range(grid.min.y..=grid.max.y),
+        );
+        let step = if rng.0.gen_bool(0.5) {
+            IVec2::X
+        } else {
+            IVec2::Y
+        };
+        let length = rng.0.gen_range(BLOCK_LENGTHS);
+        let cells: Vec<IVec2> = (0..length).map(|offset| start + step * offset).collect();
+        if !cells.iter().all(fits) {
+            continue;
+        }
+        for cell in cells {
+            let block = obstacle_spawn_at(&mut commands, GridPosition(cell).translation());
+            commands.entity(block).insert(SurvivalBlock);
+        }
+        run.blocks += 1;
+        cues.send(Cue::Callout("New block".to_string()));
+        return;
+    }
+}
+
+fn end_survival(
+    mut run: ResMut<ModeRun>,
+    mut run_end: ResMut<RunEnd>,
+    mut toasts: EventWriter<Toast>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if run.clock.just_finished() {
+        run.outcome = Some(ModeOutcome::Survived);
+        run_end.end(Ending::Won);
+        toasts.send(Toast("You survived!".to_string()));
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn update_mode_clock(run: Res<ModeRun>, mut texts: Query<&mut Text, With<ModeClockText>>) {
+    let label = match run.mode {
+        GameMode::Classic => return,
+        GameMode::TimeAttack => "Time",
+        GameMode::Survival => "Survive",
+    };
+    let seconds = run.clock.remaining_secs();
+    let hurry = run.mode == GameMode::TimeAttack && seconds <= HURRY_SECONDS;
+    let message = format!(
+        "{label} {:.0}:{:02.0}",
+        (seconds / 60.0).floor(),
+        (seconds % 60.0).floor()
+    );
+    for mut text in &mut texts {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+            text.sections[0].style.color = if hurry { HURRY_COLOR } else { CLOCK_COLOR };
+        }
+    }
+}
+
+fn record_mode_death(run_end: Res<RunEnd>, mut run: ResMut<ModeRun>) {
+    // coming back from the credits or help re-enters game over without a new run
+    if run_end.unrecorded().and_then(Ending::death_cause).is_none() {
+        return;
+    }
+    run.outcome = Some(ModeOutcome::Died);
+}
+
+fn display_mode_summary(mut commands: Commands, run: Res<ModeRun>) {
+    let Some(outcome) = run.outcome else {
+        return;
+    };
+    let elapsed = run.clock.elapsed_secs();
+    let left = run.clock.remaining_secs();
+    let (headline, detail) = match (run.mode, outcome) {
+        (GameMode::Classic, _) => return,
+        (GameMode::TimeAttack, ModeOutcome::Died) => (
+            "Time attack: out early".to_string(),
+            format!(
+                "{} apples with {:.0} seconds left",
+                run.apples,
+                left.floor()
+            ),
+        ),
+        (GameMode::TimeAttack, _) => (
+            "Time attack: time's up".to_string(),
+            format!("{} apples in {TIME_ATTACK_SECONDS:.0} seconds", run.apples),
+        ),
+        (GameMode::Survival, ModeOutcome::Died) => (
+            "Survival: crushed".to_string(),
+            format!(
+                "Lasted {:.0}:{:02.0} against {} blocks",
+                (elapsed / 60.0).floor(),
+                (elapsed % 60.0).floor(),
+                run.blocks
+            ),
+        ),
+        (GameMode::Survival, _) => (
+            "Survival: you made it!".to_string(),
+            format!("Outlasted {} blocks with {} apples", run.blocks, run.apples),
+        ),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(18.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                headline,
+                TextStyle {
+                    font_size: 40.0,
+                    color: SUMMARY_COLOR,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                detail,
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
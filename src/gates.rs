@@ -0,0 +1,328 @@
+use bevy::{
+    math::bounding::{Aabb2d, IntersectsVolume},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::GridPosition,
+    collision::{sync_collision_grid, ColliderKind, DetectCollisions},
+    gravity::apply_gravity_rules,
+    move_snake, new_run,
+    rules::{GameRules, SnakeStart},
+    settings::GameSettings,
+    setup, storage,
+    toast::Toast,
+    Collider, GameState, OnGameScreen, SafeArea, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+const LEVEL_KEY: &str = "level";
+const KEY_SIZE: f32 = 12.0;
+const HUD_ICON_SIZE: f32 = 16.0;
+
+/// Keys and the locked gates they open, laid out by a `level.ron` next to
+/// the saves, e.g. `(keys: [(color: Red, cell: (5, 5))], gates: [(color: Red,
+/// cells: [(8, 0), (8, 1)], solid: true)])`. Cells count from the arena
+/// center in segment-sized steps. Solid gates stop the snake, the others are
+/// as lethal as walls; picking up the matching key opens them for the rest
+/// of the run. Collected keys show in the HUD. A level can also move the
+/// snake's start, e.g. `start: Some((cell: (-5, 0), length: 6, facing: Right))`.
+pub struct KeysAndGatesPlugin;
+
+impl Plugin for KeysAndGatesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelLayout::load())
+            .init_resource::<KeyInventory>()
+            .init_resource::<SnakeBeforeMove>()
+            .register_type::<LevelKey>()
+            .register_type::<Gate>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    spawn_keys_and_gates,
+                    // after every preset that swaps the rules in whole
+                    apply_level_start.after(apply_gravity_rules),
+                )
+                    .before(setup)
+                    .run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    remember_snake.before(move_snake),
+                    (stop_at_solid_gates, collect_keys)
+                        .chain()
+                        .after(move_snake)
+                        .before(sync_collision_grid)
+                        .before(DetectCollisions),
+                    update_key_icons.run_if(resource_changed::<KeyInventory>),
+                )
+                    .run_if(in_state(GameState::Playing).and_then(has_keys_or_gates)),
+            );
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Green,
+    Yellow,
+}
+
+impl KeyColor {
+    fn color(self) -> Color {
+        match self {
+            KeyColor::Red => Color::rgb(0.9, 0.2, 0.2),
+            KeyColor::Blue => Color::rgb(0.25, 0.45, 1.0),
+            KeyColor::Green => Color::rgb(0.2, 0.8, 0.3),
+            KeyColor::Yellow => Color::rgb(1.0, 0.85, 0.1),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyPlacement {
+    pub color: KeyColor,
+    pub cell: (i32, i32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GatePlacement {
+    pub color: KeyColor,
+    pub cells: Vec<(i32, i32)>,
+    // stops the snake instead of ending the run
+    #[serde(default)]
+    pub solid: bool,
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct LevelLayout {
+    pub keys: Vec<KeyPlacement>,
+    pub gates: Vec<GatePlacement>,
+    // the mode's own start when it's not set
+    pub start: Option<SnakeStart>,
+}
+
+impl LevelLayout {
+    fn load() -> Self {
+        storage::load_ron(LEVEL_KEY).unwrap_or_default()
+    }
+}
+
+/// Keys picked up this run, in the order they were found.
+#[derive(Resource, Default, Debug)]
+pub struct KeyInventory(pub Vec<KeyColor>);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct LevelKey(KeyColor);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Gate {
+    color: KeyColor,
+    solid: bool,
+}
+
+#[derive(Component)]
+struct KeyIcons;
+
+// where the snake was before this tick's move, to put it back at a solid gate
+#[derive(Resource, Default)]
+struct SnakeBeforeMove(Vec<(Entity, GridPosition)>);
+
+fn has_keys_or_gates(layout: Res<LevelLayout>) -> bool {
+    !layout.keys.is_empty() || !layout.gates.is_empty()
+}
+
+pub fn apply_level_start(layout: Res<LevelLayout>, mut rules: ResMut<GameRules>) {
+    if let Some(start) = layout.start {
+        rules.start = start;
+    }
+}
+
+fn cell_position((x, y): (i32, i32)) -> Vec2 {
+    Vec2::new(x as f32, y as f32) * SEGMENT_SIZE
+}
+
+fn spawn_keys_and_gates(
+    mut commands: Commands,
+    layout: Res<LevelLayout>,
+    safe_area: Res<SafeArea>,
+    mut inventory: ResMut<KeyInventory>,
+) {
+    inventory.0.clear();
+    if layout.keys.is_empty() && layout.gates.is_empty() {
+        return;
+    }
+
+    for key in &layout.keys {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: cell_position(key.cell).extend(-1.0),
+                    rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_4),
+                    scale: Vec3::new(KEY_SIZE, KEY_SIZE, 1.0),
+                },
+                sprite: Sprite {
+                    color: key.color.color(),
+                    ..default()
+                },
+                ..default()
+            },
+            LevelKey(key.color),
+            OnGameScreen,
+        ));
+    }
+    for gate in &layout.gates {
+        for &cell in &gate.cells {
+            let mut tile = commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        translation: cell_position(cell).extend(0.0),
+                        scale: SEGMENT_SIZE.extend(1.0),
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        // solid gates are drawn fainter than the lethal ones
+                        color: gate
+                            .color
+                            .color()
+                            .with_a(if gate.solid { 0.5 } else { 0.9 }),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Gate {
+                    color: gate.color,
+                    solid: gate.solid,
+                },
+                OnGameScreen,
+            ));
+            if !gate.solid {
+                tile.insert((Collider, ColliderKind::Wall));
+            }
+        }
+    }
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0 + safe_area.top),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        },
+        KeyIcons,
+        OnGameScreen,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn remember_snake(
+    mut before: ResMut<SnakeBeforeMove>,
+    snake: Query<(Entity, &GridPosition), Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+) {
+    before.0.clear();
+    before
+        .0
+        .extend(snake.iter().map(|(entity, cell)| (entity, *cell)));
+}
+
+#[allow(clippy::type_complexity)]
+fn stop_at_solid_gates(
+    settings: Res<GameSettings>,
+    before: Res<SnakeBeforeMove>,
+    gates: Query<(&Transform, &Gate)>,
+    mut snake: Query<
+        (&mut GridPosition, &mut Transform, Has<SnakeHead>),
+        (Or<(With<SnakeHead>, With<SnakeBodySegment>)>, Without<Gate>),
+    >,
+) {
+    let Some((_, head, _)) = snake.iter().find(|(_, _, is_head)| *is_head) else {
+        return;
+    };
+    let head = Aabb2d::new(head.translation.truncate(), settings.hitboxes.head() / 2.0);
+    let blocked = gates.iter().any(|(transform, gate)| {
+        gate.solid
+            && head.intersects(&Aabb2d::new(
+                transform.translation.truncate(),
+                transform.scale.truncate() / 2.0,
+            ))
+    });
+    if !blocked {
+        return;
+    }
+
+    // the transforms too, so the checks that follow see the snake put back
+    for &(entity, previous) in &before.0 {
+        if let Ok((mut cell, mut transform, _)) = snake.get_mut(entity) {
+            if *cell != previous {
+                *cell = previous;
+                transform.translation = previous.translation().extend(transform.translation.z);
+            }
+        }
+    }
+}
+
+fn collect_keys(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    mut inventory: ResMut<KeyInventory>,
+    mut toasts: EventWriter<Toast>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    keys: Query<(Entity, &Transform, &LevelKey)>,
+    gates: Query<(Entity, &Gate)>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let head = Aabb2d::new(head.translation.truncate(), settings.hitboxes.head() / 2.0);
+    for (entity, transform, &LevelKey(color)) in &keys {
+        let key = Aabb2d::new(
+            transform.translation.truncate(),
+            Vec2::splat(KEY_SIZE / 2.0),
+        );
+        if !head.intersects(&key) {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        inventory.0.push(color);
+        for (gate, _) in gates.iter().filter(|(_, gate)| gate.color == color) {
+            commands.entity(gate).despawn();
+        }
+        toasts.send(Toast(format!("{color:?} key found: {color:?} gates open")));
+    }
+}
+
+fn update_key_icons(
+    mut commands: Commands,
+    inventory: Res<KeyInventory>,
+    containers: Query<Entity, With<KeyIcons>>,
+) {
+    for container in &containers {
+        commands
+            .entity(container)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for color in &inventory.0 {
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(HUD_ICON_SIZE),
+                            height: Val::Px(HUD_ICON_SIZE),
+                            ..default()
+                        },
+                        background_color: color.color().into(),
+                        ..default()
+                    });
+                }
+            });
+    }
+}
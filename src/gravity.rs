@@ -0,0 +1,94 @@
+use bevy::{math::bounding::Aabb2d, prelude::*};
+
+use crate::{
+    collider_half_size,
+    collision::ColliderKind,
+    kids::apply_kids_presets,
+    new_run,
+    rules::{has_gravity, GameRules},
+    settings::GameSettings,
+    setup, sim,
+    toast::Toast,
+    Apple, Collider, GameState,
+};
+
+const GRAVITY_PLATFORMS: usize = 6;
+
+/// Gravity mode, toggled with N from the game-over screen: the snake falls
+/// unless it climbs, apples drop onto ledges, and every run gets a few ledges
+/// to drop them onto. Layered over whatever rules the run was going to use.
+pub struct GravityModePlugin;
+
+impl Plugin for GravityModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_gravity_mode.run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            apply_gravity_rules
+                .after(apply_kids_presets)
+                .before(setup)
+                .run_if(new_run),
+        )
+        .add_systems(
+            Update,
+            drop_new_apples
+                .run_if(has_gravity)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn toggle_gravity_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        settings.modifiers.gravity = !settings.modifiers.gravity;
+        toasts.send(Toast(if settings.modifiers.gravity {
+            "Gravity on from the next run".to_string()
+        } else {
+            "Gravity off from the next run".to_string()
+        }));
+    }
+}
+
+pub fn apply_gravity_rules(settings: Res<GameSettings>, mut rules: ResMut<GameRules>) {
+    let gravity = settings.modifiers.gravity;
+    let platforms = if gravity { GRAVITY_PLATFORMS } else { 0 };
+    // only touched when it changes anything, so the rules don't look changed every run
+    if rules.gravity != gravity || rules.platforms != platforms {
+        rules.gravity = gravity;
+        rules.platforms = platforms;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn drop_new_apples(
+    settings: Res<GameSettings>,
+    walls: Query<(&Transform, &ColliderKind), (With<Collider>, Without<Apple>)>,
+    mut apples: Query<&mut Transform, (With<Apple>, Added<Apple>)>,
+) {
+    if apples.is_empty() {
+        return;
+    }
+    let supports: Vec<Aabb2d> = walls
+        .iter()
+        .filter(|(_, kind)| **kind == ColliderKind::Wall)
+        .map(|(transform, _)| {
+            Aabb2d::new(
+                transform.translation.truncate(),
+                collider_half_size(transform, false, &settings.hitboxes),
+            )
+        })
+        .collect();
+    for mut transform in &mut apples {
+        let half_size = collider_half_size(&transform, true, &settings.hitboxes);
+        let position = transform.translation.truncate();
+        let rested = sim::resting_position(position, half_size, &supports);
+        transform.translation.y = rested.y;
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::{ActiveChallenge, Mutators},
+    display_final_score,
+    menu::MenuItem,
+    progression::Progression,
+    rules::GameRules,
+    settings::GameSettings,
+    storage, GameState, OnGameOverScreen, OnGameScreen, RunEnd, SafeArea, Scoreboard,
+};
+
+const HARDCORE_SCORES_KEY: &str = "hardcore";
+const TABLE_SIZE: usize = 10;
+const SHOWN_SCORES: usize = 5;
+const SKULL_PIXEL: f32 = 3.0;
+const SKULL: [&str; 7] = [
+    " ##### ", "#######", "#  #  #", "#######", " ## ## ", " ##### ", " # # # ",
+];
+
+/// Hardcore runs, started with X from the game-over screen: a faster tick, no
+/// continues, power-ups or weekly mutators, and a score table of their own.
+/// A run stops counting for that table as soon as anything relaxes its rules.
+pub struct HardcorePlugin;
+
+impl Plugin for HardcorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HardcoreMode>()
+            .insert_resource(HardcoreScores::load())
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    record_hardcore_score.before(display_final_score),
+                    display_hardcore_scores.after(display_final_score),
+                ),
+            )
+            .add_systems(
+                Update,
+                request_hardcore.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnExit(GameState::GameOver), start_or_end_hardcore)
+            .add_systems(
+                Update,
+                (disqualify_relaxed_runs, spawn_skull)
+                    .run_if(in_state(GameState::Playing).and_then(hardcore_run)),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct HardcoreMode {
+    active: bool,
+    requested: bool,
+    // cleared for good once the run bends the rules
+    eligible: bool,
+}
+
+impl HardcoreMode {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Whether the run still qualifies for the hardcore table and leaderboard.
+    pub fn counts(&self) -> bool {
+        self.active && self.eligible
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct HardcoreScore {
+    score: u32,
+    date: String,
+    // the profile name at the time; older tables don't have one
+    #[serde(default)]
+    name: String,
+}
+
+/// Best hardcore scores, highest first.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct HardcoreScores {
+    scores: Vec<HardcoreScore>,
+}
+
+impl HardcoreScores {
+    fn load() -> Self {
+        storage::load_ron(HARDCORE_SCORES_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(HARDCORE_SCORES_KEY, self);
+    }
+
+    fn insert(&mut self, score: u32, name: &str) {
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let index = self.scores.partition_point(|entry| entry.score >= score);
+        let name = name.to_string();
+        self.scores
+            .insert(index, HardcoreScore { score, date, name });
+        self.scores.truncate(TABLE_SIZE);
+    }
+}
+
+#[derive(Component)]
+struct SkullIcon;
+
+fn hardcore_run(hardcore: Res<HardcoreMode>) -> bool {
+    hardcore.active
+}
+
+fn request_hardcore(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut hardcore: ResMut<HardcoreMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyX) {
+        hardcore.requested = true;
+        next_state.set(GameState::Playing);
+    }
+}
+
+// any other way out of the game-over screen leaves hardcore behind
+fn start_or_end_hardcore(
+    mut hardcore: ResMut<HardcoreMode>,
+    mut challenge: ResMut<ActiveChallenge>,
+    mut mutators: ResMut<Mutators>,
+    mut rules: ResMut<GameRules>,
+) {
+    let requested = hardcore.requested;
+    let was_active = hardcore.active;
+    *hardcore = HardcoreMode {
+        active: requested,
+        requested: false,
+        eligible: requested,
+    };
+    if requested {
+        challenge.end();
+        *mutators = Mutators::default();
+        *rules = GameRules::hardcore();
+    } else if was_active {
+        *rules = GameRules::classic();
+    }
+}
+
+// forgiving hitboxes, assist mode, a slower game speed or a slowed-down snake,
+// e.g. from the console
+fn disqualify_relaxed_runs(
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    mut hardcore: ResMut<HardcoreMode>,
+) {
+    if !hardcore.eligible {
+        return;
+    }
+    if settings.hitboxes.forgiving
+        || settings.accessibility.assist
+        || settings.accessibility.game_speed < 1.0
+        || rules.speed.base_interval > GameRules::hardcore().speed.base_interval
+    {
+        hardcore.eligible = false;
+        info!("this hardcore run no longer counts for the hardcore table");
+    }
+}
+
+fn spawn_skull(
+    mut commands: Commands,
+    safe_area: Res<SafeArea>,
+    skulls: Query<(), With<SkullIcon>>,
+) {
+    if !skulls.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0 + safe_area.top),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            SkullIcon,
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::px(7, SKULL_PIXEL),
+                        grid_template_rows: RepeatedGridTrack::px(7, SKULL_PIXEL),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|skull| {
+                    for pixel in SKULL.iter().flat_map(|row| row.chars()) {
+                        let color = if pixel == '#' {
+                            Color::WHITE
+                        } else {
+                            Color::NONE
+                        };
+                        skull.spawn(NodeBundle {
+                            background_color: color.into(),
+                            ..default()
+                        });
+                    }
+                });
+        });
+}
+
+fn record_hardcore_score(
+    hardcore: Res<HardcoreMode>,
+    run_end: Res<RunEnd>,
+    scoreboard: Res<Scoreboard>,
+    progression: Res<Progression>,
+    mut scores: ResMut<HardcoreScores>,
+) {
+    // coming back from the credits or help re-enters game over without a new run
+    if !hardcore.counts() || run_end.unrecorded().is_none() {
+        return;
+    }
+    scores.insert(scoreboard.score, &progression.profile.name);
+    scores.save();
+}
+
+fn display_hardcore_scores(
+    mut commands: Commands,
+    hardcore: Res<HardcoreMode>,
+    scores: Res<HardcoreScores>,
+) {
+    let mut lines = vec!["Press X for hardcore".to_string()];
+    if hardcore.is_active() {
+        if !hardcore.counts() {
+            lines.push("This run bent the rules and wasn't recorded".to_string());
+        }
+        lines.extend(
+            scores
+                .scores
+                .iter()
+                .take(SHOWN_SCORES)
+                .enumerate()
+                .map(|(rank, entry)| {
+                    format!(
+                        "{}. {}  {}  {}",
+                        rank + 1,
+                        entry.score,
+                        entry.name,
+                        entry.date
+                    )
+                }),
+        );
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            lines.join("\n"),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(120.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyX),
+        OnGameOverScreen,
+    ));
+}
@@ -0,0 +1,294 @@
+//! Headless runs for bots and tests: the whole game without a window,
+//! driven by `SnakeCommand`s and observed through a `GameStateSnapshot`.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::{AssetPlugin, UntypedAssetLoadFailedEvent},
+    audio::AudioSource,
+    input::InputPlugin,
+    prelude::*,
+    render::{texture::Image, view::screenshot::ScreenshotManager},
+    sprite::ColorMaterial,
+    text::Font,
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitWindows,
+};
+
+use crate::{
+    arena::{GridPosition, WallThickness},
+    move_snake,
+    pool::Pooled,
+    sim, storage, Apple, Direction, GameRng, GameState, RunEnd, Scoreboard, Snake,
+    SnakeBodySegment, SnakeGamePlugin, SnakeHead, MOVE_INTERVAL,
+};
+
+pub const CLI_FLAG: &str = "--headless";
+// the built-in bot gives up on runs that only go in circles
+const MAX_TICKS: u64 = 100_000;
+
+/// Plays runs of the full game, `SnakeGamePlugin` with its settings and
+/// rules, on top of `MinimalPlugins` and without a window. Every
+/// `App::update` advances the clock by one move, so the same seed and
+/// commands always play out the same. Saves are neither read nor written:
+/// the run uses the default settings. Turns are queued the way the keyboard
+/// queues them. A bot sends its command with `app.world.send_event`, calls
+/// `app.update()` and reads the `GameStateSnapshot` resource to decide on
+/// the next one.
+pub struct HeadlessPlugin {
+    pub seed: u64,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // before the settings and saves are loaded while building the game
+        storage::disable();
+        app.add_plugins((
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+            AssetPlugin::default(),
+            WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            },
+        ))
+        // what the render, UI, audio and winit plugins would have set up for
+        // the game's systems, with nothing to draw or play
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Image>()
+        .init_asset::<Font>()
+        .init_asset::<AudioSource>()
+        .init_resource::<UiScale>()
+        .init_resource::<GlobalVolume>()
+        .init_resource::<ScreenshotManager>()
+        .init_resource::<Msaa>()
+        .init_non_send_resource::<WinitWindows>()
+        .add_plugins(SnakeGamePlugin)
+        .insert_resource(GameRng::seeded(self.seed))
+        // the clock follows the updates, not the wall clock
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            MOVE_INTERVAL,
+        )))
+        .add_event::<SnakeCommand>()
+        .init_resource::<GameStateSnapshot>()
+        .add_systems(Startup, start_run)
+        // failed loads are reported before this, in `UpdateAssets`
+        .add_systems(PreUpdate, ignore_asset_failures)
+        .add_systems(Update, apply_commands.before(move_snake))
+        .add_systems(Last, take_snapshot);
+    }
+}
+
+/// Input for the headless run, read once per update.
+#[derive(Event, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SnakeCommand {
+    Up,
+    Down,
+    Left,
+    Right,
+    // starts a new run once the last one is over, with the rolls carrying on
+    Restart,
+}
+
+/// The board after the latest update. Cells count from the arena center in
+/// segment-sized steps, like the level files.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct GameStateSnapshot {
+    /// Head first.
+    pub snake: Vec<IVec2>,
+    /// None once the snake fills the board.
+    pub apple: Option<IVec2>,
+    pub score: u32,
+    /// Moves the snake has made this run.
+    pub tick: u64,
+    pub over: bool,
+    /// The first and last cells the snake can move on, inclusive.
+    pub bounds: (IVec2, IVec2),
+}
+
+// nothing is drawn or played, so files that don't load are no error
+fn ignore_asset_failures(mut failures: ResMut<Events<UntypedAssetLoadFailedEvent>>) {
+    failures.clear();
+}
+
+// straight past the main menu
+fn start_run(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn apply_commands(
+    mut commands: EventReader<SnakeCommand>,
+    state: Res<State<GameState>>,
+    mut snake: ResMut<Snake>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for command in commands.read() {
+        let direction = match command {
+            SnakeCommand::Up => Direction::Up,
+            SnakeCommand::Down => Direction::Down,
+            SnakeCommand::Left => Direction::Left,
+            SnakeCommand::Right => Direction::Right,
+            SnakeCommand::Restart => {
+                if *state.get() == GameState::GameOver {
+                    next_state.set(GameState::Playing);
+                }
+                continue;
+            }
+        };
+        snake.queue_turn(direction);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn take_snapshot(
+    state: Res<State<GameState>>,
+    run_end: Res<RunEnd>,
+    scoreboard: Res<Scoreboard>,
+    wall_thickness: Res<WallThickness>,
+    heads: Query<Ref<GridPosition>, With<SnakeHead>>,
+    segments: Query<&GridPosition, (With<SnakeBodySegment>, Without<Pooled>)>,
+    apples: Query<&Transform, With<Apple>>,
+    mut snapshot: ResMut<GameStateSnapshot>,
+) {
+    // the board is cleared for the game-over screen, so the last one stays
+    let Ok(head) = heads.get_single() else {
+        snapshot.over = *state.get() != GameState::Playing;
+        return;
+    };
+    if head.is_added() {
+        snapshot.tick = 0;
+    } else if head.is_changed() {
+        snapshot.tick += 1;
+    }
+    snapshot.snake = std::iter::once(head.0)
+        .chain(segments.iter().map(|cell| cell.0))
+        .collect();
+    snapshot.apple = apples
+        .iter()
+        .next()
+        .map(|apple| GridPosition::containing(apple.translation.truncate()).0);
+    snapshot.score = scoreboard.score;
+    snapshot.over = run_end.ending.is_some() || *state.get() != GameState::Playing;
+    snapshot.bounds = sim::grid_bounds(wall_thickness.0);
+}
+
+/// A bot that heads for the apple along whichever safe move gets closest,
+/// looking only one cell ahead.
+pub fn greedy_command(snapshot: &GameStateSnapshot) -> Option<SnakeCommand> {
+    let apple = snapshot.apple?;
+    let head = snapshot.snake[0];
+    let (min, max) = snapshot.bounds;
+    [
+        (SnakeCommand::Up, IVec2::Y),
+        (SnakeCommand::Down, IVec2::NEG_Y),
+        (SnakeCommand::Left, IVec2::NEG_X),
+        (SnakeCommand::Right, IVec2::X),
+    ]
+    .into_iter()
+    .map(|(command, step)| (command, head + step))
+    .filter(|(_, cell)| {
+        // the tail moves out of the way in the same tick
+        let body = &snapshot.snake[..snapshot.snake.len() - 1];
+        cell.cmpge(min).all() && cell.cmple(max).all() && !body.contains(cell)
+    })
+    .min_by_key(|(_, cell)| {
+        let d = (apple - *cell).abs();
+        d.x + d.y
+    })
+    .map(|(command, _)| command)
+}
+
+/// Plays one run with the greedy bot, started by passing `--headless` and
+/// optionally a seed on the command line, and prints how it went.
+pub fn run() {
+    let seed = std::env::args()
+        .skip_while(|arg| arg != CLI_FLAG)
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(rand::random);
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessPlugin { seed }));
+    app.update();
+    loop {
+        let snapshot = app.world.resource::<GameStateSnapshot>();
+        if snapshot.over || snapshot.tick >= MAX_TICKS {
+            println!(
+                "seed {seed}: {} apples in {} ticks{}",
+                snapshot.score,
+                snapshot.tick,
+                if snapshot.apple.is_none() {
+                    ", board filled"
+                } else {
+                    ""
+                }
+            );
+            return;
+        }
+        if let Some(command) = greedy_command(snapshot) {
+            app.world.send_event(command);
+        }
+        app.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game_modes::GameMode, Ending};
+
+    fn headless_app(seed: u64) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HeadlessPlugin { seed }));
+        app
+    }
+
+    // with the greedy bot, to the end of the run
+    fn play(app: &mut App) -> GameStateSnapshot {
+        app.update();
+        loop {
+            let snapshot = app.world.resource::<GameStateSnapshot>();
+            if snapshot.over || snapshot.tick >= MAX_TICKS {
+                return snapshot.clone();
+            }
+            if let Some(command) = greedy_command(snapshot) {
+                app.world.send_event(command);
+            }
+            app.update();
+        }
+    }
+
+    #[test]
+    fn seeded_run_plays_out_the_same() {
+        let snapshot = play(&mut headless_app(7));
+        assert_eq!(snapshot.score, 41);
+        assert_eq!(snapshot.tick, 919);
+        // the greedy bot boxes itself in rather than hitting a wall
+        let (head, body) = snapshot.snake.split_first().unwrap();
+        assert!(body.contains(head));
+        assert_eq!(snapshot, play(&mut headless_app(7)));
+    }
+
+    #[test]
+    fn time_attack_ends_when_the_clock_runs_out() {
+        let mut app = headless_app(7);
+        app.insert_resource(GameMode::TimeAttack);
+        let snapshot = play(&mut app);
+        assert_eq!(app.world.resource::<RunEnd>().ending, Some(Ending::Over));
+        assert_eq!(snapshot.score, 41);
+        assert_eq!(snapshot.tick, 900);
+
+        // the next run gets a clock of its own, rather than playing on until
+        // the bot crashes
+        app.world.send_event(SnakeCommand::Restart);
+        while app.world.resource::<GameStateSnapshot>().over {
+            app.update();
+        }
+        play(&mut app);
+        assert_eq!(app.world.resource::<RunEnd>().ending, Some(Ending::Over));
+    }
+}
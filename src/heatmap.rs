@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display_final_score, menu::MenuItem, storage, teardown_game_screen, Ending, GameState,
+    OnGameOverScreen, RunEnd, SnakeHead, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, SEGMENT_SIZE,
+    TOP_WALL,
+};
+
+const HEATMAP_KEY: &str = "deaths";
+const COOL_COLOR: Color = Color::rgba(1.0, 0.9, 0.2, 0.35);
+const HOT_COLOR: Color = Color::rgba(1.0, 0.1, 0.1, 0.9);
+
+/// Counts deaths per arena cell and shows them as a heatmap from the
+/// game-over screen with M.
+pub struct DeathHeatmapPlugin;
+
+impl Plugin for DeathHeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DeathHeatmap::load())
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    record_death.before(teardown_game_screen),
+                    display_heatmap_hint.after(display_final_score),
+                ),
+            )
+            .add_systems(Update, toggle_heatmap.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// Deaths so far, keyed by segment-sized cell.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct DeathHeatmap {
+    cells: BTreeMap<(i32, i32), u32>,
+}
+
+impl DeathHeatmap {
+    fn load() -> Self {
+        storage::load_ron(HEATMAP_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(HEATMAP_KEY, self);
+    }
+}
+
+#[derive(Component)]
+struct HeatmapOverlay;
+
+fn record_death(
+    run_end: Res<RunEnd>,
+    mut heatmap: ResMut<DeathHeatmap>,
+    snake_heads: Query<&Transform, With<SnakeHead>>,
+) {
+    // wins and runs ended from the console aren't the player's habit
+    if run_end.unrecorded().and_then(Ending::death_cause).is_none() {
+        return;
+    }
+    let Ok(head) = snake_heads.get_single() else {
+        return;
+    };
+
+    let cell = (head.translation.truncate() / SEGMENT_SIZE)
+        .round()
+        .as_ivec2();
+    *heatmap.cells.entry((cell.x, cell.y)).or_default() += 1;
+    heatmap.save();
+}
+
+fn display_heatmap_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Press M for the death map",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(70.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyM),
+        OnGameOverScreen,
+    ));
+}
+
+fn toggle_heatmap(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    heatmap: Res<DeathHeatmap>,
+    overlays: Query<Entity, With<HeatmapOverlay>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyM) {
+        return;
+    }
+    if !overlays.is_empty() {
+        for entity in &overlays {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    // the run has been torn down, so the overlay brings its own arena backdrop
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.1, 0.1, 0.1, 0.9),
+                custom_size: Some(Vec2::new(RIGHT_WALL - LEFT_WALL, TOP_WALL - BOTTOM_WALL)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 5.0),
+            ..default()
+        },
+        HeatmapOverlay,
+        OnGameOverScreen,
+    ));
+
+    let most = heatmap.cells.values().copied().max().unwrap_or_default();
+    for (&(x, y), &deaths) in &heatmap.cells {
+        let heat = deaths as f32 / most as f32;
+        let color = Vec4::from(COOL_COLOR.as_rgba_f32()).lerp(HOT_COLOR.as_rgba_f32().into(), heat);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba_from_array(color),
+                    custom_size: Some(SEGMENT_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    (IVec2::new(x, y).as_vec2() * SEGMENT_SIZE).extend(6.0),
+                ),
+                ..default()
+            },
+            HeatmapOverlay,
+            OnGameOverScreen,
+        ));
+    }
+}
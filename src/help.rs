@@ -0,0 +1,341 @@
+use bevy::prelude::*;
+
+use crate::{
+    challenge::Mutators,
+    display_final_score,
+    game_modes::GameMode,
+    input::{key_name, ControlPreset, KeyBindings},
+    menu::MenuItem,
+    settings::{GameSettings, PortableSettings},
+    toast::Toast,
+    GameState, OnGameOverScreen,
+};
+
+const PRESET_KEYS: [KeyCode; 4] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+];
+
+/// Controls and how-to-play screen, opened from pause or the game-over
+/// screen. The controls come from the live `KeyBindings`; 1-4 pick a turn-key
+/// preset and L toggles the left-handed menu keys, saved with the settings,
+/// and R opens the rebind screen for single keys. E and I export and import
+/// the portable settings bundle.
+pub struct HelpPlugin;
+
+impl Plugin for HelpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HelpReturn>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_help_hint.after(display_final_score),
+            )
+            .add_systems(
+                Update,
+                open_help
+                    .run_if(in_state(GameState::Paused).or_else(in_state(GameState::GameOver))),
+            )
+            .add_systems(OnEnter(GameState::Help), spawn_help)
+            .add_systems(OnExit(GameState::Help), despawn_help)
+            .add_systems(
+                Update,
+                (
+                    close_help,
+                    choose_controls,
+                    transfer_settings,
+                    // shows the new keys once `apply_control_settings` has rebuilt them
+                    (despawn_help, spawn_help)
+                        .chain()
+                        .run_if(resource_changed::<KeyBindings>),
+                )
+                    .run_if(in_state(GameState::Help)),
+            );
+    }
+}
+
+/// Screen the help was opened from, and goes back to.
+#[derive(Resource)]
+struct HelpReturn(GameState);
+
+impl Default for HelpReturn {
+    fn default() -> Self {
+        HelpReturn(GameState::Paused)
+    }
+}
+
+#[derive(Component)]
+struct HelpScreen;
+
+fn rules(settings: &GameSettings, mutators: &Mutators, mode: GameMode) -> Vec<&'static str> {
+    let mut rules = vec![
+        "Eat apples to grow and score.",
+        "Running into a wall or your own body ends the run.",
+    ];
+    rules.extend(mode.rule());
+    let modifiers = &settings.modifiers;
+    for (enabled, rule) in [
+        (
+            modifiers.poison_trail,
+            "Poison trail: your tail drops poison that fades away; touching it is fatal.",
+        ),
+        (
+            modifiers.food_chain,
+            "Food chain: eat rival snakes shorter than you, and avoid the longer ones.",
+        ),
+        (
+            modifiers.king_of_the_hill,
+            "King of the hill: stay inside the glowing zone for bonus points.",
+        ),
+        (
+            modifiers.fleeing_apples,
+            "Fleeing apples: apples run away from the snake, so corner them.",
+        ),
+        (
+            modifiers.apple_chains,
+            "Apple chains: eat the colored apples in the order shown for a big bonus.",
+        ),
+        (
+            modifiers.mirrored,
+            "Mirrored: left and right are swapped, all run long or in announced bursts.",
+        ),
+        (
+            modifiers.darkness,
+            "Darkness: only a light around the head shows the way, shrinking as you grow.",
+        ),
+        (
+            modifiers.danger_bonus,
+            "Danger bonus: moving right next to a wall or your body earns points, more the longer you keep it up.",
+        ),
+        (
+            modifiers.bounty_apples,
+            "Bounty apples: glowing gold apples in risky spots are worth 5, if you get there in time.",
+        ),
+        (
+            modifiers.power_ups,
+            "Power-ups: golden apples are worth 5, boosts and slow-downs change your pace for a while, and shrinks trim your tail.",
+        ),
+        (
+            modifiers.levels,
+            "Levels: every 10 apples the obstacles clear away and the next level's layout takes their place.",
+        ),
+        (
+            modifiers.random_start,
+            "Random start: every run begins somewhere new, with room ahead to get going.",
+        ),
+        (
+            modifiers.gravity,
+            "Gravity: the snake falls unless it heads up, and apples rest on ledges.",
+        ),
+        (
+            modifiers.quota_timer,
+            "Quota timer: eat each round's quota before the clock runs out; every round asks for more.",
+        ),
+        (
+            settings.world_events.enabled,
+            "World events: apple rain, earthquakes and blackouts arrive with a warning.",
+        ),
+        (
+            mutators.mirror_controls,
+            "Mirror: the controls are reversed.",
+        ),
+        (mutators.fog, "Fog: only what is near the head is visible."),
+        (mutators.haste, "Haste: the snake moves faster."),
+        (
+            mutators.double_speed,
+            "Double speed: the snake moves twice as fast.",
+        ),
+        (
+            mutators.no_walls,
+            "No walls: leave one edge to come back on the other.",
+        ),
+        (
+            mutators.tiny_hitbox,
+            "Tiny hitbox: the head only hits what's right under it.",
+        ),
+    ] {
+        if enabled {
+            rules.push(rule);
+        }
+    }
+    rules
+}
+
+fn display_help_hint(mut commands: Commands, bindings: Res<KeyBindings>) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Press {} for help", key_name(bindings.help)),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(45.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(bindings.help),
+        OnGameOverScreen,
+    ));
+}
+
+fn open_help(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    state: Res<State<GameState>>,
+    mut help_return: ResMut<HelpReturn>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(bindings.help) {
+        help_return.0 = state.get().clone();
+        next_state.set(GameState::Help);
+    }
+}
+
+fn close_help(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    help_return: Res<HelpReturn>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([bindings.help, KeyCode::Escape]) {
+        next_state.set(help_return.0.clone());
+    }
+}
+
+fn choose_controls(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<GameSettings>) {
+    for (key, preset) in PRESET_KEYS.into_iter().zip(ControlPreset::ALL) {
+        if keyboard_input.just_pressed(key) && settings.controls.preset != preset {
+            settings.controls.preset = preset;
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyL) {
+        settings.controls.left_handed = !settings.controls.left_handed;
+    }
+}
+
+fn transfer_settings(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyE) {
+        toasts.send(Toast(match PortableSettings::export(&settings) {
+            Ok(path) => format!("Settings exported to {}", path.display()),
+            Err(err) => format!("Settings export failed: {err}"),
+        }));
+    } else if keyboard_input.just_pressed(KeyCode::KeyI) {
+        toasts.send(Toast(match PortableSettings::import(&mut settings) {
+            Ok(path) => format!("Settings imported from {}", path.display()),
+            Err(err) => format!("Settings import failed: {err}"),
+        }));
+    }
+}
+
+fn spawn_help(
+    mut commands: Commands,
+    bindings: Res<KeyBindings>,
+    settings: Res<GameSettings>,
+    mutators: Res<Mutators>,
+    mode: Res<GameMode>,
+) {
+    let heading = |text: &str| {
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size: 36.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(16.0)),
+            ..default()
+        })
+    };
+    let line = |text: String| {
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            HelpScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(heading("Controls"));
+            for (action, key) in bindings.list() {
+                parent.spawn(line(format!("{action}: {}", key_name(key))));
+            }
+            let presets: Vec<String> = PRESET_KEYS
+                .into_iter()
+                .zip(ControlPreset::ALL)
+                .map(|(key, preset)| {
+                    let marker = if preset == settings.controls.preset {
+                        "*"
+                    } else {
+                        ""
+                    };
+                    format!("{} {}{marker}", key_name(key), preset.label())
+                })
+                .collect();
+            parent.spawn(line(format!("Layout: {}", presets.join("  "))));
+            parent.spawn(line("R: rebind keys".to_string()));
+            parent.spawn(line(
+                "E: export settings, I: import the newest settings file".to_string(),
+            ));
+            parent.spawn(line(format!(
+                "L: left-handed menu keys ({})",
+                if settings.controls.left_handed {
+                    "on"
+                } else {
+                    "off"
+                }
+            )));
+            parent.spawn(heading("How to play"));
+            for rule in rules(&settings, &mutators, *mode) {
+                parent.spawn(line(rule.to_string()));
+            }
+            parent.spawn(
+                line(format!(
+                    "Press {} or Escape to go back",
+                    key_name(bindings.help)
+                ))
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(24.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn despawn_help(mut commands: Commands, screens: Query<Entity, With<HelpScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,314 @@
+use bevy::{prelude::*, window::ReceivedCharacter};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display_final_score, menu::MenuItem, practice::PracticeMode, progression::Progression,
+    sandbox::SandboxMode, storage, GameState, OnGameOverScreen, RunEnd, Scoreboard,
+};
+
+const HIGH_SCORES_KEY: &str = "high_scores";
+const TABLE_SIZE: usize = 10;
+const MAX_NAME_LEN: usize = 16;
+const NAME_KEY: KeyCode = KeyCode::KeyA;
+const LATEST_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+
+/// The ten best scores of any run, kept on disk and shown on the game-over
+/// screen. A run that makes the table is entered under the profile name,
+/// which A lets the player change on a name entry screen. Practice and
+/// sandbox runs don't count.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .init_resource::<NameEntry>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    record_high_score.before(display_final_score),
+                    display_high_scores.after(display_final_score),
+                ),
+            )
+            .add_systems(
+                Update,
+                open_name_entry.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnEnter(GameState::NameEntry), spawn_name_entry)
+            .add_systems(
+                OnExit(GameState::NameEntry),
+                (despawn_name_entry, save_high_scores),
+            )
+            .add_systems(
+                Update,
+                (
+                    enter_name,
+                    (despawn_name_entry, spawn_name_entry)
+                        .chain()
+                        .run_if(resource_changed::<HighScores>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::NameEntry)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct HighScore {
+    score: u32,
+    name: String,
+    date: String,
+}
+
+/// Best scores, highest first.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct HighScores {
+    scores: Vec<HighScore>,
+    // where the last run landed in the table, if it made it
+    #[serde(skip)]
+    latest: Option<usize>,
+}
+
+impl HighScores {
+    fn load() -> Self {
+        storage::load_ron(HIGH_SCORES_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(HIGH_SCORES_KEY, self);
+    }
+
+    fn insert(&mut self, score: u32, name: &str) {
+        // a run without an apple isn't worth a row
+        if score == 0 {
+            self.latest = None;
+            return;
+        }
+        let index = self.scores.partition_point(|entry| entry.score >= score);
+        self.latest = (index < TABLE_SIZE).then_some(index);
+        if self.latest.is_none() {
+            return;
+        }
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let name = name.to_string();
+        self.scores.insert(index, HighScore { score, name, date });
+        self.scores.truncate(TABLE_SIZE);
+    }
+}
+
+#[derive(Resource, Default)]
+struct NameEntry {
+    // set after the first frame, which still carries the key that opened the screen
+    typing: bool,
+}
+
+#[derive(Component)]
+struct NameEntryScreen;
+
+fn record_high_score(
+    run_end: Res<RunEnd>,
+    practice: Res<PracticeMode>,
+    sandbox: Res<SandboxMode>,
+    scoreboard: Res<Scoreboard>,
+    progression: Res<Progression>,
+    mut scores: ResMut<HighScores>,
+) {
+    // coming back from the credits or help re-enters game over without a new run
+    if run_end.unrecorded().is_none() {
+        return;
+    }
+    if practice.is_active() || sandbox.is_active() {
+        scores.latest = None;
+        return;
+    }
+    scores.insert(scoreboard.score, &progression.profile.name);
+    if scores.latest.is_some() {
+        scores.save();
+    }
+}
+
+fn display_high_scores(mut commands: Commands, scores: Res<HighScores>) {
+    if scores.scores.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.0),
+                    right: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "High scores",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+            for (rank, entry) in scores.scores.iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{:>2}. {:>4}  {}  {}",
+                        rank + 1,
+                        entry.score,
+                        entry.name,
+                        entry.date
+                    ),
+                    TextStyle {
+                        font_size: 16.0,
+                        color: if scores.latest == Some(rank) {
+                            LATEST_COLOR
+                        } else {
+                            Color::WHITE
+                        },
+                        ..default()
+                    },
+                ));
+            }
+            if scores.latest.is_some() {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "New high score! Press A to enter your name",
+                        TextStyle {
+                            font_size: 16.0,
+                            color: LATEST_COLOR,
+                            ..default()
+                        },
+                    ),
+                    MenuItem(NAME_KEY),
+                ));
+            }
+        });
+}
+
+fn open_name_entry(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    scores: Res<HighScores>,
+    mut entry: ResMut<NameEntry>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(NAME_KEY) && scores.latest.is_some() {
+        *entry = NameEntry::default();
+        next_state.set(GameState::NameEntry);
+    }
+}
+
+fn spawn_name_entry(mut commands: Commands, scores: Res<HighScores>) {
+    let Some(latest) = scores.latest.and_then(|index| scores.scores.get(index)) else {
+        return;
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            NameEntryScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("New high score: {}", latest.score),
+                TextStyle {
+                    font_size: 36.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!("Name: {}_", latest.name),
+                TextStyle {
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Type a name; Enter or Escape to go back",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn enter_name(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut entry: ResMut<NameEntry>,
+    mut scores: ResMut<HighScores>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !entry.typing {
+        characters.clear();
+        entry.typing = true;
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::Enter]) {
+        next_state.set(GameState::GameOver);
+        return;
+    }
+
+    let typed: Vec<char> = characters
+        .read()
+        .flat_map(|event| event.char.chars())
+        .filter(|character| !character.is_control())
+        .collect();
+    let backspace = keyboard_input.just_pressed(KeyCode::Backspace);
+    if typed.is_empty() && !backspace {
+        return;
+    }
+    let Some(index) = scores.latest else {
+        return;
+    };
+    let name = &mut scores.scores[index].name;
+    if backspace {
+        name.pop();
+    }
+    for character in typed {
+        if name.chars().count() < MAX_NAME_LEN {
+            name.push(character);
+        }
+    }
+}
+
+fn save_high_scores(mut scores: ResMut<HighScores>, progression: Res<Progression>) {
+    if let Some(index) = scores.latest {
+        let name = &mut scores.scores[index].name;
+        if name.trim().is_empty() {
+            *name = progression.profile.name.clone();
+        }
+    }
+    scores.save();
+}
+
+fn despawn_name_entry(mut commands: Commands, screens: Query<Entity, With<NameEntryScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use crate::{
+    despawn_screen,
+    input::{key_name, KeyBindings},
+    settings::GameSettings,
+    GameState,
+};
+
+/// Pauses a run nobody is playing: after the configured stretch without any
+/// key, button or touch, the game pauses with an "Are you still there?"
+/// prompt instead of letting the snake come to harm unattended. Only slow
+/// runs (kids mode or a game speed under 1x) are watched unless the setting
+/// asks for every run to be.
+pub struct IdlePausePlugin;
+
+impl Plugin for IdlePausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IdleTimer>()
+            .add_systems(OnEnter(GameState::Playing), reset_idle_timer)
+            .add_systems(Update, pause_when_idle.run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::Paused), display_idle_prompt)
+            .add_systems(OnExit(GameState::Paused), despawn_screen::<IdlePrompt>);
+    }
+}
+
+#[derive(Resource, Default)]
+struct IdleTimer {
+    idle_seconds: f32,
+    // the pause came from the timer rather than the player
+    timed_out: bool,
+}
+
+#[derive(Component)]
+struct IdlePrompt;
+
+fn watches_idle(settings: &GameSettings) -> bool {
+    let accessibility = &settings.accessibility;
+    accessibility.idle_pause_seconds > 0.0
+        && (accessibility.idle_pause_always
+            || settings.modifiers.kids_mode
+            || accessibility.game_speed < 1.0)
+}
+
+fn reset_idle_timer(mut idle: ResMut<IdleTimer>) {
+    *idle = IdleTimer::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pause_when_idle(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    touches: Res<Touches>,
+    mut idle: ResMut<IdleTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // any key, button or touch still held counts as playing
+    let active = keyboard_input.get_pressed().next().is_some()
+        || mouse_input.get_pressed().next().is_some()
+        || gamepad_input.get_pressed().next().is_some()
+        || touches.iter().next().is_some();
+    if active || !watches_idle(&settings) {
+        idle.idle_seconds = 0.0;
+        return;
+    }
+
+    idle.idle_seconds += time.delta_seconds();
+    if idle.idle_seconds >= settings.accessibility.idle_pause_seconds {
+        idle.timed_out = true;
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn display_idle_prompt(mut commands: Commands, idle: Res<IdleTimer>, bindings: Res<KeyBindings>) {
+    if !idle.timed_out {
+        return;
+    }
+    let prompt = TextBundle::from_section(
+        format!(
+            "Are you still there? Press {} to carry on",
+            key_name(bindings.confirm)
+        ),
+        TextStyle {
+            font_size: 30.0,
+            color: Color::rgb(1.0, 0.8, 0.3),
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Percent(25.0),
+        width: Val::Percent(100.0),
+        ..default()
+    })
+    .with_text_justify(JustifyText::Center);
+    // over the pause overlay
+    commands.spawn((
+        TextBundle {
+            z_index: ZIndex::Global(1),
+            ..prompt
+        },
+        IdlePrompt,
+    ));
+}
@@ -0,0 +1,411 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{DynamicEnum, FromReflect},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::Mutators,
+    mirror::InputMirror,
+    settings::{ControlSettings, GameSettings},
+    switch::SwitchHeading,
+    touch::TouchDirection,
+    Direction,
+};
+
+// how far a stick has to lean to count as a press
+pub const STICK_THRESHOLD: f32 = 0.5;
+
+/// What the player can ask of a run, whatever key or button it comes from.
+/// Each has a key in the `KeyBindings`, rebindable on the rebind screen, and
+/// all but the second player's turns a fixed gamepad button.
+#[derive(Reflect, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GameAction {
+    TurnUp,
+    TurnDown,
+    TurnLeft,
+    TurnRight,
+    Pause,
+    // also resumes from pause
+    Restart,
+    // the second snake's, in a versus round
+    PlayerTwoUp,
+    PlayerTwoDown,
+    PlayerTwoLeft,
+    PlayerTwoRight,
+}
+
+impl GameAction {
+    pub const ALL: [GameAction; 10] = [
+        GameAction::TurnUp,
+        GameAction::TurnDown,
+        GameAction::TurnLeft,
+        GameAction::TurnRight,
+        GameAction::Pause,
+        GameAction::Restart,
+        GameAction::PlayerTwoUp,
+        GameAction::PlayerTwoDown,
+        GameAction::PlayerTwoLeft,
+        GameAction::PlayerTwoRight,
+    ];
+
+    pub fn turn(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => GameAction::TurnUp,
+            Direction::Down => GameAction::TurnDown,
+            Direction::Left => GameAction::TurnLeft,
+            Direction::Right => GameAction::TurnRight,
+        }
+    }
+
+    pub fn player_two_turn(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => GameAction::PlayerTwoUp,
+            Direction::Down => GameAction::PlayerTwoDown,
+            Direction::Left => GameAction::PlayerTwoLeft,
+            Direction::Right => GameAction::PlayerTwoRight,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameAction::TurnUp => "Turn up",
+            GameAction::TurnDown => "Turn down",
+            GameAction::TurnLeft => "Turn left",
+            GameAction::TurnRight => "Turn right",
+            GameAction::Pause => "Pause",
+            GameAction::Restart => "Resume / restart",
+            GameAction::PlayerTwoUp => "Player 2 up",
+            GameAction::PlayerTwoDown => "Player 2 down",
+            GameAction::PlayerTwoLeft => "Player 2 left",
+            GameAction::PlayerTwoRight => "Player 2 right",
+        }
+    }
+
+    // pause and restart never apply on the same screen, so they share Start;
+    // the gamepad steers the first snake
+    fn gamepad_button(self) -> Option<GamepadButtonType> {
+        match self {
+            GameAction::TurnUp => Some(GamepadButtonType::DPadUp),
+            GameAction::TurnDown => Some(GamepadButtonType::DPadDown),
+            GameAction::TurnLeft => Some(GamepadButtonType::DPadLeft),
+            GameAction::TurnRight => Some(GamepadButtonType::DPadRight),
+            GameAction::Pause | GameAction::Restart => Some(GamepadButtonType::Start),
+            GameAction::PlayerTwoUp
+            | GameAction::PlayerTwoDown
+            | GameAction::PlayerTwoLeft
+            | GameAction::PlayerTwoRight => None,
+        }
+    }
+}
+
+/// Keys for the game's actions. Systems read them from here instead of
+/// naming keys, so anything that lists the controls stays in step with them.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub pause: KeyCode,
+    // resumes from pause and restarts from the game-over screen
+    pub confirm: KeyCode,
+    pub help: KeyCode,
+    pub player_two_up: KeyCode,
+    pub player_two_down: KeyCode,
+    pub player_two_left: KeyCode,
+    pub player_two_right: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::for_controls(&ControlSettings::default())
+    }
+}
+
+impl KeyBindings {
+    pub fn for_controls(controls: &ControlSettings) -> Self {
+        let [up, down, left, right] = controls.preset.turn_keys();
+        // the left-handed layout keeps the menu keys by the right hand, next to
+        // the arrows and numpad, leaving the left hand on the mouse
+        let (confirm, help) = if controls.left_handed {
+            (KeyCode::Enter, KeyCode::Slash)
+        } else {
+            (KeyCode::Space, KeyCode::KeyH)
+        };
+        // the second player takes WASD, or the arrows when the first has WASD
+        let player_two_preset = if controls.preset == ControlPreset::Wasd {
+            ControlPreset::Arrows
+        } else {
+            ControlPreset::Wasd
+        };
+        let [player_two_up, player_two_down, player_two_left, player_two_right] =
+            player_two_preset.turn_keys();
+        let mut bindings = KeyBindings {
+            up,
+            down,
+            left,
+            right,
+            pause: KeyCode::Escape,
+            confirm,
+            help,
+            player_two_up,
+            player_two_down,
+            player_two_left,
+            player_two_right,
+        };
+        // keys the settings file can't name are left on the preset's
+        for (action, name) in &controls.custom_keys {
+            if let Some(key) = key_from_name(name) {
+                *bindings.key_mut(*action) = key;
+            }
+        }
+        bindings
+    }
+
+    pub fn key(&self, action: GameAction) -> KeyCode {
+        match action {
+            GameAction::TurnUp => self.up,
+            GameAction::TurnDown => self.down,
+            GameAction::TurnLeft => self.left,
+            GameAction::TurnRight => self.right,
+            GameAction::Pause => self.pause,
+            GameAction::Restart => self.confirm,
+            GameAction::PlayerTwoUp => self.player_two_up,
+            GameAction::PlayerTwoDown => self.player_two_down,
+            GameAction::PlayerTwoLeft => self.player_two_left,
+            GameAction::PlayerTwoRight => self.player_two_right,
+        }
+    }
+
+    fn key_mut(&mut self, action: GameAction) -> &mut KeyCode {
+        match action {
+            GameAction::TurnUp => &mut self.up,
+            GameAction::TurnDown => &mut self.down,
+            GameAction::TurnLeft => &mut self.left,
+            GameAction::TurnRight => &mut self.right,
+            GameAction::Pause => &mut self.pause,
+            GameAction::Restart => &mut self.confirm,
+            GameAction::PlayerTwoUp => &mut self.player_two_up,
+            GameAction::PlayerTwoDown => &mut self.player_two_down,
+            GameAction::PlayerTwoLeft => &mut self.player_two_left,
+            GameAction::PlayerTwoRight => &mut self.player_two_right,
+        }
+    }
+
+    pub fn turn(&self, direction: Direction) -> KeyCode {
+        self.key(GameAction::turn(direction))
+    }
+
+    pub fn player_two_turn(&self, direction: Direction) -> KeyCode {
+        self.key(GameAction::player_two_turn(direction))
+    }
+
+    /// Every binding with a label, in the order a controls list shows them.
+    pub fn list(&self) -> Vec<(&'static str, KeyCode)> {
+        GameAction::ALL
+            .into_iter()
+            .map(|action| (action.label(), self.key(action)))
+            .chain([("Help", self.help)])
+            .collect()
+    }
+}
+
+/// Binds `key` to `action` in the saved controls. An action already on that
+/// key takes the one `action` had, so no two actions share a key.
+pub fn rebind(controls: &mut ControlSettings, action: GameAction, key: KeyCode) {
+    let bindings = KeyBindings::for_controls(controls);
+    let displaced = GameAction::ALL
+        .into_iter()
+        .find(|other| *other != action && bindings.key(*other) == key);
+    set_custom_key(controls, action, key);
+    if let Some(other) = displaced {
+        set_custom_key(controls, other, bindings.key(action));
+    }
+}
+
+fn set_custom_key(controls: &mut ControlSettings, action: GameAction, key: KeyCode) {
+    controls.custom_keys.retain(|(bound, _)| *bound != action);
+    controls.custom_keys.push((action, format!("{key:?}")));
+}
+
+// the settings file names keys the way `KeyCode` spells them, e.g. "KeyW"
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    KeyCode::from_reflect(&DynamicEnum::new(name, ()))
+}
+
+/// Turn-key layouts, picked on the controls screen.
+#[derive(Reflect, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ControlPreset {
+    #[default]
+    Arrows,
+    Wasd,
+    Ijkl,
+    Numpad,
+}
+
+impl ControlPreset {
+    pub const ALL: [ControlPreset; 4] = [
+        ControlPreset::Arrows,
+        ControlPreset::Wasd,
+        ControlPreset::Ijkl,
+        ControlPreset::Numpad,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlPreset::Arrows => "Arrows",
+            ControlPreset::Wasd => "WASD",
+            ControlPreset::Ijkl => "IJKL",
+            ControlPreset::Numpad => "Numpad",
+        }
+    }
+
+    // up, down, left, right
+    fn turn_keys(self) -> [KeyCode; 4] {
+        match self {
+            ControlPreset::Arrows => [
+                KeyCode::ArrowUp,
+                KeyCode::ArrowDown,
+                KeyCode::ArrowLeft,
+                KeyCode::ArrowRight,
+            ],
+            ControlPreset::Wasd => [KeyCode::KeyW, KeyCode::KeyS, KeyCode::KeyA, KeyCode::KeyD],
+            ControlPreset::Ijkl => [KeyCode::KeyI, KeyCode::KeyK, KeyCode::KeyJ, KeyCode::KeyL],
+            ControlPreset::Numpad => [
+                KeyCode::Numpad8,
+                KeyCode::Numpad2,
+                KeyCode::Numpad4,
+                KeyCode::Numpad6,
+            ],
+        }
+    }
+}
+
+/// Rebuilds the `KeyBindings` when the saved control settings change.
+pub fn apply_control_settings(settings: Res<GameSettings>, mut bindings: ResMut<KeyBindings>) {
+    let configured = KeyBindings::for_controls(&settings.controls);
+    if *bindings != configured {
+        *bindings = configured;
+    }
+}
+
+/// Short name for a key, e.g. "Up" for `ArrowUp` and "W" for `KeyW`.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    ["Arrow", "Key", "Digit"]
+        .into_iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// Direction currently held on any gamepad's d-pad or left stick.
+#[derive(Resource, Default, PartialEq)]
+pub struct GamepadDirection(pub Option<Direction>);
+
+pub fn update_gamepad_direction(
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut gamepad_direction: ResMut<GamepadDirection>,
+) {
+    let held = gamepads.iter().find_map(|gamepad| {
+        let dpad = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .find(|direction| {
+            GameAction::turn(*direction)
+                .gamepad_button()
+                .is_some_and(|button| buttons.pressed(GamepadButton::new(gamepad, button)))
+        });
+        let stick = Vec2::new(
+            axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or(0.0),
+            axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or(0.0),
+        );
+        dpad.or_else(|| stick_direction(stick))
+    });
+    gamepad_direction.set_if_neq(GamepadDirection(held));
+}
+
+fn stick_direction(stick: Vec2) -> Option<Direction> {
+    if stick.length() < STICK_THRESHOLD {
+        return None;
+    }
+    // the stick's y axis grows upwards, like the arena's
+    Some(if stick.x.abs() > stick.y.abs() {
+        if stick.x > 0.0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        }
+    } else if stick.y > 0.0 {
+        Direction::Up
+    } else {
+        Direction::Down
+    })
+}
+
+/// Whether an action was just pressed, on its key or any gamepad's button.
+/// Turns go through `TurnInput`, which also knows the stick, touch and
+/// mirroring.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    bindings: Res<'w, KeyBindings>,
+}
+
+impl ActionInput<'_> {
+    pub fn just_pressed(&self, action: GameAction) -> bool {
+        self.keyboard_input.just_pressed(self.bindings.key(action))
+            || action.gamepad_button().is_some_and(|button| {
+                self.gamepads.iter().any(|gamepad| {
+                    self.gamepad_buttons
+                        .just_pressed(GamepadButton::new(gamepad, button))
+                })
+            })
+    }
+}
+
+/// Which turns were pressed this frame, from the keyboard, a gamepad or the
+/// touch controls, or the switch heading while single-switch input is on.
+#[derive(SystemParam)]
+pub struct TurnInput<'w> {
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    touch_direction: Res<'w, TouchDirection>,
+    gamepad_direction: Res<'w, GamepadDirection>,
+    bindings: Res<'w, KeyBindings>,
+    mutators: Res<'w, Mutators>,
+    mirror: Res<'w, InputMirror>,
+    settings: Res<'w, GameSettings>,
+    switch_heading: Res<'w, SwitchHeading>,
+}
+
+impl TurnInput<'_> {
+    pub fn just_pressed(&self, direction: Direction) -> bool {
+        // mirrored controls move the opposite way to the input; the weekly
+        // mutator flips both axes and the mirrored modifier the ones it's set to
+        let input = self.mirror.apply(direction);
+        let input = if self.mutators.mirror_controls {
+            input.opposite()
+        } else {
+            input
+        };
+        if self.settings.accessibility.single_switch {
+            return self.switch_heading.is_changed() && self.switch_heading.0 == input;
+        }
+        // a swipe, d-pad press or stick push counts once, when it starts
+        self.keyboard_input.just_pressed(self.bindings.turn(input))
+            || (self.touch_direction.is_changed() && self.touch_direction.0 == Some(input))
+            || (self.gamepad_direction.is_changed() && self.gamepad_direction.0 == Some(input))
+    }
+}
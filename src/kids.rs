@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::{
+    new_run, rules::GameRules, settings::GameSettings, setup, theme::Theme, toast::Toast, GameState,
+};
+
+/// Kids mode, toggled with G from the game-over screen: a slow snake that
+/// wraps through the edges and can't bite itself, big colorful fruit and
+/// confetti every few apples. It only swaps in the kids `GameRules` and
+/// `Theme` presets as each run starts.
+pub struct KidsModePlugin;
+
+impl Plugin for KidsModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_kids_mode.run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            apply_kids_presets.before(setup).run_if(new_run),
+        );
+    }
+}
+
+fn toggle_kids_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyG) {
+        settings.modifiers.kids_mode = !settings.modifiers.kids_mode;
+        toasts.send(Toast(if settings.modifiers.kids_mode {
+            "Kids mode on from the next run".to_string()
+        } else {
+            "Kids mode off from the next run".to_string()
+        }));
+    }
+}
+
+pub fn apply_kids_presets(
+    settings: Res<GameSettings>,
+    mut rules: ResMut<GameRules>,
+    mut theme: ResMut<Theme>,
+    // whether the last run got the kids presets, so turning it off restores the defaults
+    mut applied: Local<bool>,
+) {
+    if settings.modifiers.kids_mode {
+        *rules = GameRules::kids();
+        *theme = Theme::kids();
+        *applied = true;
+    } else if *applied {
+        *rules = GameRules::classic();
+        *theme = Theme::classic();
+        *applied = false;
+    }
+}
@@ -0,0 +1,317 @@
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::{spawn_on_apple_collisions, ArenaGrid, GridPosition},
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    new_run, obstacle_spawn_at,
+    settings::GameSettings,
+    setup, storage,
+    visual_cues::Cue,
+    Apple, GameRng, GameState, OnGameScreen, SnakeBodySegment, SnakeHead,
+};
+
+const LEVELS_KEY: &str = "levels";
+const APPLES_PER_LEVEL: u32 = 10;
+// generated levels get this many more blocks each, up to the cap
+const BLOCKS_PER_LEVEL: u32 = 2;
+const MAX_BLOCKS: u32 = 16;
+// block length in cells
+const BLOCK_LENGTHS: RangeInclusive<i32> = 2..=4;
+// cells around the head kept open when a new layout drops in
+const HEAD_CLEARANCE: i32 = 3;
+const BANNER_SECONDS: f32 = 2.0;
+const BANNER_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+
+/// Levels, a modifier from the settings file: the arena gets interior
+/// obstacle blocks, and every 10 apples they're cleared for the next
+/// level's layout, announced with a banner. Layouts come in order from a
+/// `levels.ron` next to the saves, e.g. `[(obstacles: [(4, 0), (4, 1)]),
+/// (obstacles: [(-6, 3), (-5, 3), (-4, 3)])]`, with cells counting from the
+/// arena center in segment-sized steps. Without one, or past its last
+/// level, layouts are generated from the run's seed, busier every level.
+/// Cells near the head or under the snake or an apple are left open.
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelSet::load())
+            .init_resource::<CurrentLevel>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_first_level
+                    .after(setup)
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.levels)
+                    .run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    // before the eaten apple is replaced, so it doesn't land on the new layout
+                    advance_level
+                        .after(DetectCollisions)
+                        .before(spawn_on_apple_collisions),
+                    fade_level_banner,
+                )
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.levels)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct LevelDefinition {
+    obstacles: Vec<(i32, i32)>,
+}
+
+/// The hand-authored levels, played first to last.
+#[derive(Resource, Debug, Default)]
+struct LevelSet(Vec<LevelDefinition>);
+
+impl LevelSet {
+    fn load() -> Self {
+        LevelSet(storage::load_ron(LEVELS_KEY).unwrap_or_default())
+    }
+
+    /// The obstacle cells of level `number`, counting from 1.
+    fn layout(&self, number: u32, rng: &mut impl Rng, grid: &ArenaGrid) -> Vec<IVec2> {
+        match self.0.get(number as usize - 1) {
+            Some(level) => level
+                .obstacles
+                .iter()
+                .map(|&(x, y)| IVec2::new(x, y))
+                .collect(),
+            None => generate_layout(rng, number, grid),
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+struct CurrentLevel {
+    number: u32,
+    eaten: u32,
+}
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        CurrentLevel {
+            number: 1,
+            eaten: 0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct LevelObstacle;
+
+#[derive(Component)]
+struct LevelBanner(Timer);
+
+/// Straight blocks of a few cells each, more of them the higher the level.
+fn generate_layout(rng: &mut impl Rng, number: u32, grid: &ArenaGrid) -> Vec<IVec2> {
+    let blocks = (number * BLOCKS_PER_LEVEL).min(MAX_BLOCKS);
+    let mut cells = Vec::new();
+    for _ in 0..blocks {
+        let start = IVec2::new(
+            rng.gen_range(grid.min.x..=grid.max.x),
+            rng.gen_range(grid.min.y..=grid.max.y),
+        );
+        let step = if rng.gen_bool(0.5) {
+            IVec2::X
+        } else {
+            IVec2::Y
+        };
+        let length = rng.gen_range(BLOCK_LENGTHS);
+        cells.extend((0..length).map(|offset| start + step * offset));
+    }
+    cells
+}
+
+fn spawn_layout(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    levels: &LevelSet,
+    number: u32,
+    grid: &ArenaGrid,
+    head: Option<IVec2>,
+    occupied: &HashSet<IVec2>,
+) {
+    let inside = |cell: &IVec2| cell.cmpge(grid.min).all() && cell.cmple(grid.max).all();
+    let near_head = |cell: &IVec2| {
+        head.is_some_and(|head| (*cell - head).abs().max_element() <= HEAD_CLEARANCE)
+    };
+    let cells: HashSet<IVec2> = levels
+        .layout(number, &mut rng.0, grid)
+        .into_iter()
+        .filter(|cell| inside(cell) && !near_head(cell) && !occupied.contains(cell))
+        .collect();
+    for cell in cells {
+        let obstacle = obstacle_spawn_at(commands, GridPosition(cell).translation());
+        commands.entity(obstacle).insert(LevelObstacle);
+    }
+}
+
+fn level_banner(number: u32) -> impl Bundle {
+    (
+        TextBundle::from_section(
+            format!("Level {number}"),
+            TextStyle {
+                font_size: 48.0,
+                color: BANNER_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(30.0),
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        LevelBanner(Timer::from_seconds(BANNER_SECONDS, TimerMode::Once)),
+        OnGameScreen,
+    )
+}
+
+// the snake and apples setup placed, to keep the layout off them
+#[allow(clippy::type_complexity)]
+fn occupied_cells(
+    snake: &Query<(&GridPosition, Has<SnakeHead>), Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    apples: &Query<&Transform, With<Apple>>,
+) -> (Option<IVec2>, HashSet<IVec2>) {
+    let head = snake
+        .iter()
+        .find(|(_, is_head)| *is_head)
+        .map(|(cell, _)| cell.0);
+    let occupied = snake
+        .iter()
+        .map(|(cell, _)| cell.0)
+        .chain(
+            apples
+                .iter()
+                .map(|transform| GridPosition::containing(transform.translation.truncate()).0),
+        )
+        .collect();
+    (head, occupied)
+}
+
+#[allow(clippy::type_complexity)]
+fn start_first_level(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    levels: Res<LevelSet>,
+    grid: Res<ArenaGrid>,
+    mut level: ResMut<CurrentLevel>,
+    snake: Query<(&GridPosition, Has<SnakeHead>), Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    apples: Query<&Transform, With<Apple>>,
+) {
+    *level = CurrentLevel::default();
+    let (head, occupied) = occupied_cells(&snake, &apples);
+    spawn_layout(
+        &mut commands,
+        &mut rng,
+        &levels,
+        level.number,
+        &grid,
+        head,
+        &occupied,
+    );
+    commands.spawn(level_banner(level.number));
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn advance_level(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut rng: ResMut<GameRng>,
+    levels: Res<LevelSet>,
+    grid: Res<ArenaGrid>,
+    mut level: ResMut<CurrentLevel>,
+    mut cues: EventWriter<Cue>,
+    obstacles: Query<Entity, With<LevelObstacle>>,
+    banners: Query<Entity, With<LevelBanner>>,
+    snake: Query<(&GridPosition, Has<SnakeHead>), Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    apples: Query<&Transform, With<Apple>>,
+) {
+    let apples_eaten = collision_events
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .count() as u32;
+    if apples_eaten == 0 {
+        return;
+    }
+    level.eaten += apples_eaten;
+    if level.eaten < APPLES_PER_LEVEL {
+        return;
+    }
+
+    level.number += 1;
+    level.eaten = 0;
+    for entity in obstacles.iter().chain(&banners) {
+        commands.entity(entity).despawn_recursive();
+    }
+    let (head, occupied) = occupied_cells(&snake, &apples);
+    spawn_layout(
+        &mut commands,
+        &mut rng,
+        &levels,
+        level.number,
+        &grid,
+        head,
+        &occupied,
+    );
+    commands.spawn(level_banner(level.number));
+    cues.send(Cue::Callout(format!("Level {}", level.number)));
+}
+
+fn fade_level_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut LevelBanner, &mut Text)>,
+) {
+    for (entity, mut banner, mut text) in &mut banners {
+        if banner.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = banner.0.fraction_remaining();
+        text.sections[0].style.color = BANNER_COLOR.with_a(alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{HeadlessPlugin, SnakeCommand};
+
+    #[test]
+    fn every_run_starts_at_the_first_level() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HeadlessPlugin { seed: 7 }));
+        app.world.resource_mut::<GameSettings>().modifiers.levels = true;
+        app.update();
+        let mut obstacles = app.world.query_filtered::<(), With<LevelObstacle>>();
+        assert!(obstacles.iter(&app.world).count() > 0);
+
+        *app.world.resource_mut::<CurrentLevel>() = CurrentLevel {
+            number: 3,
+            eaten: 25,
+        };
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::GameOver);
+        app.update();
+        app.world.send_event(SnakeCommand::Restart);
+        app.update();
+        app.update();
+
+        let level = app.world.resource::<CurrentLevel>();
+        assert_eq!((level.number, level.eaten), (1, 0));
+        assert!(obstacles.iter(&app.world).count() > 0);
+    }
+}
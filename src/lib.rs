@@ -0,0 +1,873 @@
+use std::collections::{LinkedList, VecDeque};
+
+use bevy::{
+    input::InputSystem,
+    math::{bounding::Aabb2d, vec2},
+    prelude::*,
+    sprite::Mesh2dHandle,
+};
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod accessibility;
+mod arena;
+mod assist;
+mod audio;
+mod bounty;
+mod capture;
+mod challenge;
+mod clip;
+#[cfg(all(feature = "cloud-sync", not(target_arch = "wasm32")))]
+mod cloud;
+mod collision;
+mod conditions;
+#[cfg(debug_assertions)]
+mod console;
+mod continues;
+mod credits;
+mod danger;
+mod darkness;
+mod debug;
+mod effects;
+mod errors;
+mod fleeing;
+mod food_chain;
+mod game_modes;
+mod gates;
+mod gravity;
+mod hardcore;
+pub mod headless;
+mod heatmap;
+mod help;
+mod high_scores;
+mod idle;
+mod input;
+mod kids;
+mod levels;
+mod main_menu;
+mod menu;
+mod mirror;
+mod missions;
+#[cfg(all(feature = "mods", not(target_arch = "wasm32")))]
+mod mods;
+mod mutator_menu;
+mod objectives;
+mod persistence;
+mod photo;
+#[cfg(feature = "physics")]
+mod physics;
+mod poison;
+mod pool;
+mod powerups;
+mod practice;
+mod profile;
+mod progression;
+mod puzzle;
+mod quota;
+mod rebind;
+mod replay;
+mod result_card;
+mod rules;
+mod run_log;
+mod sandbox;
+mod scoring;
+#[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+mod scripting;
+mod secrets;
+mod settings;
+pub mod sim;
+mod snake;
+// only the debug console drives it so far
+#[cfg_attr(not(debug_assertions), allow(dead_code))]
+mod snapshot;
+mod speedrun;
+mod sprint;
+mod stats;
+#[cfg(feature = "steam")]
+mod steam;
+mod storage;
+#[cfg(debug_assertions)]
+mod stress;
+mod switch;
+mod telemetry;
+mod theme;
+mod tips;
+mod toast;
+mod touch;
+mod ui;
+mod versus;
+mod visual_cues;
+mod world_events;
+mod zones;
+
+use accessibility::AccessibilityPlugin;
+pub use arena::ArenaPlugin;
+use arena::{
+    apple_spawn_at, apple_spawn_free, obstacle_spawn_at, wrap_edge_sprite, WallBundle,
+    WallLocation, WallThickness,
+};
+use assist::AssistPlugin;
+use audio::AudioPlugin;
+use bounty::BountyApplesPlugin;
+use capture::CapturePlugin;
+use challenge::WeeklyChallengePlugin;
+use clip::ClipPlugin;
+use collision::{
+    sync_collision_grid, ColliderKind, CollisionEvent, CollisionGrid, DetectCollisions,
+};
+use conditions::ConditionsPlugin;
+use continues::ContinuePlugin;
+use credits::CreditsPlugin;
+use danger::DangerBonusPlugin;
+use darkness::DarknessPlugin;
+use debug::DebugOverlayPlugin;
+use effects::EffectsPlugin;
+use errors::ErrorScreenPlugin;
+use fleeing::FleeingApplesPlugin;
+use food_chain::FoodChainPlugin;
+use game_modes::GameModesPlugin;
+use gates::KeysAndGatesPlugin;
+use gravity::GravityModePlugin;
+use hardcore::HardcorePlugin;
+use heatmap::DeathHeatmapPlugin;
+use help::HelpPlugin;
+use high_scores::HighScoresPlugin;
+use idle::IdlePausePlugin;
+use input::{apply_control_settings, update_gamepad_direction, GamepadDirection, KeyBindings};
+use kids::KidsModePlugin;
+use levels::LevelsPlugin;
+use main_menu::MainMenuPlugin;
+use menu::MenuFocusPlugin;
+use mirror::MirroredControlsPlugin;
+use missions::MissionsPlugin;
+use mutator_menu::MutatorMenuPlugin;
+use objectives::ObjectivesPlugin;
+use persistence::PersistencePlugin;
+use photo::PhotoModePlugin;
+use poison::PoisonTrailPlugin;
+use pool::{EntityPool, Pooled};
+use powerups::PowerUpsPlugin;
+use practice::PracticePlugin;
+use profile::ProfilePlugin;
+use progression::ProgressionPlugin;
+use puzzle::PuzzlePlugin;
+use quota::QuotaTimerPlugin;
+use rebind::RebindPlugin;
+use replay::ReplayPlugin;
+use result_card::ResultCardPlugin;
+use rules::{GameRules, RulesPlugin};
+use run_log::RunLogPlugin;
+use sandbox::SandboxPlugin;
+use scoring::score_update;
+pub use scoring::ScoringPlugin;
+use secrets::SecretsPlugin;
+use settings::{GameSettings, HitboxSettings, SettingsPlugin};
+pub use snake::SnakePlugin;
+use snake::{move_snake, snake_segment_spawn, snake_spawn};
+use speedrun::SpeedrunPlugin;
+use sprint::SprintPlugin;
+use stats::StatsPlugin;
+use switch::SingleSwitchPlugin;
+use telemetry::TelemetryPlugin;
+use theme::ThemePlugin;
+use tips::TipsPlugin;
+use toast::ToastPlugin;
+use touch::TouchControlsPlugin;
+use ui::display_final_score;
+pub use ui::UiPlugin;
+use versus::VersusPlugin;
+use visual_cues::VisualCuesPlugin;
+use world_events::WorldEventsPlugin;
+use zones::KingOfTheHillPlugin;
+
+const WALL_THICKNESS: f32 = 10.0;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const LEFT_WALL: f32 = -350.0;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const RIGHT_WALL: f32 = 350.0;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const BOTTOM_WALL: f32 = -350.0;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const TOP_WALL: f32 = 350.0;
+
+// phones are held upright, so the mobile arena is taller than it is wide
+#[cfg(any(target_os = "android", target_os = "ios"))]
+const LEFT_WALL: f32 = -250.0;
+#[cfg(any(target_os = "android", target_os = "ios"))]
+const RIGHT_WALL: f32 = 250.0;
+#[cfg(any(target_os = "android", target_os = "ios"))]
+const BOTTOM_WALL: f32 = -400.0;
+#[cfg(any(target_os = "android", target_os = "ios"))]
+const TOP_WALL: f32 = 400.0;
+
+// room left around the walls so the scoreboard never covers the arena
+const ARENA_MARGIN: f32 = 60.0;
+
+const WALL_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
+
+const MOVE_INTERVAL: f32 = 0.1;
+const SEGMENT_SIZE: Vec2 = vec2(20.0, 20.0);
+// the head and tail sprites face up
+const SNAKE_HEAD_SPRITE: &str = "sprites/snake_head.png";
+const SNAKE_BODY_SPRITE: &str = "sprites/snake_body.png";
+const SNAKE_TAIL_SPRITE: &str = "sprites/snake_tail.png";
+const APPLE_SPRITE: &str = "sprites/apple.png";
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, States)]
+enum GameState {
+    // the title screen the game boots into
+    #[default]
+    MainMenu,
+    Playing,
+    Paused,
+    PhotoMode,
+    // a death that coins can still undo
+    ContinuePrompt,
+    GameOver,
+    Credits,
+    Help,
+    // rebinding the action keys, opened from help
+    Rebind,
+    Profile,
+    // naming the run that just made the high score table
+    NameEntry,
+    Stats,
+    Mutators,
+    Error,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    fn vector(self) -> Vec2 {
+        match self {
+            Direction::Up => Vec2::Y,
+            Direction::Down => Vec2::NEG_Y,
+            Direction::Left => Vec2::NEG_X,
+            Direction::Right => Vec2::X,
+        }
+    }
+
+    fn clockwise(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+}
+
+/// Sent when the head starts heading in a new direction.
+#[derive(Event)]
+struct SnakeTurned(Direction);
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Collider;
+
+#[derive(Clone, Reflect, Serialize, Deserialize)]
+struct SnakeSegment {
+    x: f32,
+    y: f32,
+    // spawned again when a saved run is picked back up
+    #[serde(skip)]
+    entity: Option<Entity>,
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct SnakeHead;
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Apple;
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct SnakeBodySegment;
+
+/// The first player's snake is a resource; a versus round's second snake
+/// carries its own on its head, with its segments' entities in `body`.
+#[derive(Resource, Component, Reflect, Serialize, Deserialize, Clone)]
+#[reflect(Resource)]
+struct Snake {
+    // LinkedList has no Reflect impl
+    #[reflect(ignore)]
+    body: LinkedList<SnakeSegment>,
+    head: SnakeSegment,
+    /// Heading the head steps in on every move.
+    direction: Direction,
+    // turns pressed since the last move, taken one per move
+    #[reflect(ignore)]
+    turn_queue: VecDeque<Direction>,
+    // bevy's timers only serialize with its `serialize` feature, and the
+    // speed rules work the interval out from the score again anyway
+    #[serde(skip)]
+    move_cooldown: Timer,
+}
+
+// the second player's score rides on its snake's head, like its `Snake`
+#[derive(Resource, Component, Reflect, Default)]
+#[reflect(Resource)]
+struct Scoreboard {
+    score: u32,
+}
+
+/// How a run came to an end.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Ending {
+    /// The snake filled the board, met a level's goal or outlasted a mode's
+    /// clock.
+    Won,
+    /// A rule rather than a crash ended it, like time attack's clock or a
+    /// quota round running out.
+    Over,
+    Died(ColliderKind),
+}
+
+impl Ending {
+    fn death_cause(self) -> Option<ColliderKind> {
+        match self {
+            Ending::Died(kind) => Some(kind),
+            Ending::Won | Ending::Over => None,
+        }
+    }
+}
+
+/// How the current run ended, set by whatever ends it and cleared when the
+/// next one starts. Quitting a run leaves it unset.
+#[derive(Resource, Default)]
+struct RunEnd {
+    ending: Option<Ending>,
+    // game over is entered again coming back from the credits, help or name
+    // entry, and the run mustn't be counted twice
+    recorded: bool,
+}
+
+impl RunEnd {
+    /// Ends the run, unless something else already did this tick.
+    fn end(&mut self, ending: Ending) {
+        self.ending.get_or_insert(ending);
+    }
+
+    /// How the run ended, if it has and hasn't been recorded yet.
+    fn unrecorded(&self) -> Option<Ending> {
+        self.ending.filter(|_| !self.recorded)
+    }
+
+    fn death_cause(&self) -> Option<ColliderKind> {
+        self.ending.and_then(Ending::death_cause)
+    }
+}
+
+/// Whether the latest entry into `Playing` started a new run. Resuming from
+/// pause or the continue prompt enters it again with the run still on screen.
+#[derive(Resource, Default)]
+struct NewRun(bool);
+
+// decided ahead of the transition, as setup spawns the snake it goes by
+fn check_new_run(
+    next_state: Res<NextState<GameState>>,
+    snake_heads: Query<(), With<SnakeHead>>,
+    mut new_run: ResMut<NewRun>,
+) {
+    if next_state.0 == Some(GameState::Playing) {
+        new_run.0 = snake_heads.is_empty();
+    }
+}
+
+/// Run condition for the `OnEnter(GameState::Playing)` systems that start a
+/// new run, whether they're ordered before `setup` or after it.
+fn new_run(new_run: Res<NewRun>) -> bool {
+    new_run.0
+}
+
+// one quad and one material per look, shared by every spawned entity
+#[derive(Resource)]
+struct GameAssets {
+    quad: Mesh2dHandle,
+    snake_head_material: Handle<ColorMaterial>,
+    // the body segments'
+    snake_material: Handle<ColorMaterial>,
+    snake_tail_material: Handle<ColorMaterial>,
+    apple_material: Handle<ColorMaterial>,
+    // grey, so the fruit's color comes from the material it's drawn with
+    apple_texture: Handle<Image>,
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let quad = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Rectangle::from_size(SEGMENT_SIZE));
+        let asset_server = world.resource::<AssetServer>();
+        let [head, body, tail, apple_texture] = [
+            SNAKE_HEAD_SPRITE,
+            SNAKE_BODY_SPRITE,
+            SNAKE_TAIL_SPRITE,
+            APPLE_SPRITE,
+        ]
+        .map(|path| asset_server.load(path));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+
+        GameAssets {
+            quad: Mesh2dHandle(quad),
+            snake_head_material: materials.add(head),
+            snake_material: materials.add(body),
+            snake_tail_material: materials.add(tail),
+            apple_material: materials.add(ColorMaterial {
+                color: Color::RED,
+                texture: Some(apple_texture.clone()),
+            }),
+            apple_texture,
+        }
+    }
+}
+
+// every gameplay roll goes through here so a run can be replayed from its seed
+#[derive(Resource)]
+struct GameRng(StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng(StdRng::from_entropy())
+    }
+}
+
+impl GameRng {
+    fn seeded(seed: u64) -> Self {
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+// despawned together when their screen's state is left
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct OnGameScreen;
+
+#[derive(Component)]
+struct OnGameOverScreen;
+
+#[derive(Component)]
+struct ScoreboardText;
+
+#[derive(Component)]
+struct PauseOverlay;
+
+// space kept clear of notches and home indicators; bevy doesn't report the real insets yet
+#[derive(Resource)]
+struct SafeArea {
+    top: f32,
+    bottom: f32,
+}
+
+impl Default for SafeArea {
+    fn default() -> Self {
+        if cfg!(any(target_os = "android", target_os = "ios")) {
+            SafeArea {
+                top: 48.0,
+                bottom: 34.0,
+            }
+        } else {
+            SafeArea {
+                top: 0.0,
+                bottom: 0.0,
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+#[bevy_main]
+fn main() {
+    run();
+}
+
+/// The whole game as a plugin, so it can run inside another Bevy app: add it
+/// after `DefaultPlugins`. The arena, the snake, the score and the screens
+/// around a run each have a plugin of their own; this one adds them along
+/// with every mode, modifier and screen, and runs the collision checks they
+/// share. Steam, cloud saves and the inspector are left to `run`, which
+/// needs them set up before the app is built.
+pub struct SnakeGamePlugin;
+
+impl Plugin for SnakeGamePlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(not(feature = "physics"))]
+        let detect_collisions = check_for_collisions.after(sync_collision_grid);
+        #[cfg(feature = "physics")]
+        let detect_collisions = physics::forward_rapier_collisions;
+
+        app.init_resource::<GameRng>()
+            .add_plugins((
+                (
+                    SettingsPlugin,
+                    RulesPlugin,
+                    SprintPlugin,
+                    ThemePlugin,
+                    AccessibilityPlugin,
+                    SingleSwitchPlugin,
+                    AssistPlugin,
+                    IdlePausePlugin,
+                    MenuFocusPlugin,
+                    VisualCuesPlugin,
+                    EffectsPlugin,
+                    AudioPlugin,
+                ),
+                (ArenaPlugin, SnakePlugin, ScoringPlugin, UiPlugin),
+                ErrorScreenPlugin,
+                TouchControlsPlugin,
+                ToastPlugin,
+                CapturePlugin,
+                ClipPlugin,
+                PhotoModePlugin,
+                DebugOverlayPlugin,
+                // optional rule changes, each switched on in the settings
+                (
+                    PoisonTrailPlugin,
+                    FoodChainPlugin,
+                    KingOfTheHillPlugin,
+                    FleeingApplesPlugin,
+                    WorldEventsPlugin,
+                    KidsModePlugin,
+                    GravityModePlugin,
+                    MirroredControlsPlugin,
+                    DarknessPlugin,
+                    DangerBonusPlugin,
+                    BountyApplesPlugin,
+                    PowerUpsPlugin,
+                    LevelsPlugin,
+                    QuotaTimerPlugin,
+                ),
+                (MissionsPlugin, ObjectivesPlugin),
+                (WeeklyChallengePlugin, ResultCardPlugin, MutatorMenuPlugin),
+                (
+                    SpeedrunPlugin,
+                    PracticePlugin,
+                    SandboxPlugin,
+                    PuzzlePlugin,
+                    HardcorePlugin,
+                    ContinuePlugin,
+                    ConditionsPlugin,
+                    KeysAndGatesPlugin,
+                    VersusPlugin,
+                    GameModesPlugin,
+                ),
+                // kept across sessions
+                (
+                    ProgressionPlugin,
+                    ProfilePlugin,
+                    HighScoresPlugin,
+                    SecretsPlugin,
+                    TelemetryPlugin,
+                ),
+                // screens and game-over extras outside a run
+                (
+                    MainMenuPlugin,
+                    CreditsPlugin,
+                    HelpPlugin,
+                    TipsPlugin,
+                    DeathHeatmapPlugin,
+                    RunLogPlugin,
+                    ReplayPlugin,
+                    StatsPlugin,
+                    RebindPlugin,
+                    PersistencePlugin,
+                ),
+            ))
+            .init_resource::<GameAssets>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<GamepadDirection>()
+            .init_resource::<RunEnd>()
+            .init_resource::<NewRun>()
+            .init_resource::<CollisionGrid>()
+            .add_event::<CollisionEvent>()
+            .init_state::<GameState>()
+            .add_systems(
+                First,
+                apply_control_settings.run_if(resource_changed::<GameSettings>),
+            )
+            .add_systems(PreUpdate, update_gamepad_direction.after(InputSystem))
+            .add_systems(
+                StateTransition,
+                check_new_run.before(apply_state_transition::<GameState>),
+            )
+            .add_systems(OnEnter(GameState::Playing), setup.run_if(new_run))
+            // only an ended run sets it, so coming back from pause clears nothing
+            .add_systems(OnEnter(GameState::Playing), clear_run_end)
+            .add_systems(OnEnter(GameState::GameOver), teardown_game_screen)
+            .add_systems(OnExit(GameState::GameOver), mark_run_recorded)
+            .add_systems(
+                Update,
+                // collisions are resolved against the head's position from this same tick
+                (
+                    detect_collisions,
+                    rules::detect_self_collisions.run_if(rules::self_collision_lethal),
+                )
+                    .in_set(DetectCollisions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, sync_collision_grid)
+            .register_type::<Snake>()
+            .register_type::<Scoreboard>()
+            .register_type::<GameSettings>()
+            .register_type::<Collider>()
+            .register_type::<SnakeHead>()
+            .register_type::<Apple>()
+            .register_type::<SnakeBodySegment>()
+            .register_type::<OnGameScreen>()
+            .register_type::<ColliderKind>();
+
+        #[cfg(debug_assertions)]
+        app.add_plugins((console::ConsolePlugin, stress::StressTestPlugin));
+
+        #[cfg(feature = "physics")]
+        app.add_plugins(physics::PhysicsModePlugin);
+
+        #[cfg(all(feature = "mods", not(target_arch = "wasm32")))]
+        app.add_plugins(mods::ModsPlugin);
+
+        #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+        app.add_plugins(scripting::ScriptingPlugin);
+    }
+}
+
+pub fn run() {
+    // bots and scripted checks get the whole game without a window
+    if std::env::args().any(|arg| arg == headless::CLI_FLAG) {
+        headless::run();
+        return;
+    }
+
+    // remote saves are pulled before anything reads them
+    #[cfg(all(feature = "cloud-sync", not(target_arch = "wasm32")))]
+    let cloud_failures = cloud::sync(&GameSettings::load().cloud_sync);
+
+    // before loading too, so newer Steam Cloud saves are picked up
+    #[cfg(feature = "steam")]
+    let steam = steam::init();
+
+    let settings = GameSettings::load();
+    let mut window = Window {
+        title: "Snake".to_string(),
+        prevent_default_event_handling: false,
+        ..default()
+    };
+    settings.window.apply(&mut window);
+
+    let mut app = App::new();
+    app.insert_resource(settings).add_plugins((
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(window),
+            ..default()
+        }),
+        SnakeGamePlugin,
+    ));
+
+    #[cfg(all(feature = "cloud-sync", not(target_arch = "wasm32")))]
+    app.add_plugins(cloud::CloudSyncPlugin {
+        startup_failures: cloud_failures,
+    });
+
+    #[cfg(feature = "steam")]
+    match steam {
+        Ok((steam, single_client)) => {
+            app.insert_non_send_resource(single_client)
+                .add_plugins(steam);
+        }
+        Err(reason) => {
+            app.add_plugins(steam::OfflineScoresPlugin { reason });
+        }
+    }
+
+    // F1 toggles the inspector
+    #[cfg(feature = "dev")]
+    app.add_plugins(
+        bevy_inspector_egui::quick::WorldInspectorPlugin::new().run_if(
+            bevy::input::common_conditions::input_toggle_active(false, KeyCode::F1),
+        ),
+    );
+
+    app.run();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    mut rng: ResMut<GameRng>,
+    mut snake: ResMut<Snake>,
+    safe_area: Res<SafeArea>,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    rules: Res<GameRules>,
+    // whatever the level placed before the run started, like gates
+    colliders: Query<&Transform, With<Collider>>,
+) {
+    let wraps = rules::wraps_edges(&rules, &settings);
+    for location in [
+        WallLocation::Left,
+        WallLocation::Right,
+        WallLocation::Bottom,
+        WallLocation::Top,
+    ] {
+        if wraps {
+            commands.spawn((wrap_edge_sprite(location, wall_thickness.0), OnGameScreen));
+        } else {
+            commands.spawn((WallBundle::new(location, wall_thickness.0), OnGameScreen));
+        }
+    }
+    let start = if settings.modifiers.random_start {
+        let blocked: Vec<Aabb2d> = colliders
+            .iter()
+            .map(|transform| {
+                Aabb2d::new(
+                    transform.translation.truncate(),
+                    transform.scale.truncate() / 2.0,
+                )
+            })
+            .collect();
+        rules::random_start(&mut rng, rules.start.length, &blocked).unwrap_or(rules.start)
+    } else {
+        rules.start
+    };
+    // only the layout, so the move timer keeps the speed it was given
+    let laid_out = Snake::at(&start);
+    snake.head = laid_out.head;
+    snake.body = laid_out.body;
+    snake.direction = laid_out.direction;
+    snake.turn_queue = laid_out.turn_queue;
+
+    let clear = start.footprint();
+    rules::place_obstacles(&mut commands, &mut rng, rules.obstacle_density, &clear);
+    rules::place_platforms(&mut commands, &mut rng, rules.platforms, &clear);
+
+    snake_spawn(&mut commands, &game_assets, &mut segment_pool, &mut snake);
+    // the first apple stays off the snake and the room ahead of it
+    let occupied: Vec<Aabb2d> = clear
+        .iter()
+        .map(|&cell| Aabb2d::new(cell, SEGMENT_SIZE / 2.0))
+        .chain(colliders.iter().map(|transform| {
+            Aabb2d::new(
+                transform.translation.truncate(),
+                transform.scale.truncate() / 2.0,
+            )
+        }))
+        .collect();
+    apple_spawn_free(
+        &mut commands,
+        &game_assets,
+        &mut rng,
+        wall_thickness.0,
+        &occupied,
+    );
+
+    // scoreboard
+    commands.spawn((
+        TextBundle::from_section(
+            "Apples Eaten: 0",
+            TextStyle {
+                font_size: 30.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0 + safe_area.top),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ScoreboardText,
+        OnGameScreen,
+    ));
+}
+
+fn clear_run_end(mut run_end: ResMut<RunEnd>) {
+    *run_end = RunEnd::default();
+}
+
+fn mark_run_recorded(mut run_end: ResMut<RunEnd>) {
+    run_end.recorded = true;
+}
+
+#[cfg_attr(feature = "physics", allow(dead_code))]
+fn check_for_collisions(
+    collision_grid: Res<CollisionGrid>,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    snake_head_query: Query<&Transform, (With<SnakeHead>, With<Collider>)>,
+) {
+    let _span = info_span!("collision_checks").entered();
+    let hitboxes = &settings.hitboxes;
+    for snake_head_transform in &snake_head_query {
+        let head = snake_head_transform.translation.truncate();
+        // forgiving hitboxes only shrink the head against what can kill it
+        for (kind, hitbox) in [
+            (ColliderKind::Apple, hitboxes.head()),
+            (ColliderKind::Wall, hitboxes.lethal_head()),
+            (ColliderKind::Poison, hitboxes.lethal_head()),
+            (ColliderKind::Rival, hitboxes.head()),
+        ] {
+            let hitbox = hitbox * rules.head_hitbox_scale;
+            if let Some(collider) = collision_grid.first_hit(Aabb2d::new(head, hitbox / 2.0), kind)
+            {
+                collision_events.send(CollisionEvent { collider, kind });
+            }
+        }
+    }
+}
+
+// walls are scaled unit sprites, apples are meshes scaled by the theme's fruit size
+fn collider_half_size(
+    collider_transform: &Transform,
+    is_apple: bool,
+    hitboxes: &HitboxSettings,
+) -> Vec2 {
+    if is_apple {
+        hitboxes.apple_half_size() * collider_transform.scale.truncate()
+    } else {
+        collider_transform.scale.truncate() / 2.0
+    }
+}
+
+fn despawn_screen<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// body segments go back to their pool instead of being despawned
+#[allow(clippy::type_complexity)]
+fn teardown_game_screen(
+    mut commands: Commands,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    entities: Query<(Entity, Has<SnakeBodySegment>), (With<OnGameScreen>, Without<Pooled>)>,
+) {
+    for (entity, is_segment) in &entities {
+        if is_segment {
+            segment_pool.release(&mut commands, entity);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    credits::MENU_CREDITS_KEY,
+    despawn_screen,
+    game_modes::GameMode,
+    input::{key_name, KeyBindings},
+    menu::MenuItem,
+    persistence::{SavedGame, CONTINUE_KEY},
+    settings::GameSettings,
+    GameState,
+};
+
+const DIFFICULTY_KEY: KeyCode = KeyCode::KeyD;
+const WALLS_KEY: KeyCode = KeyCode::KeyW;
+const PLAYERS_KEY: KeyCode = KeyCode::KeyT;
+const MODE_KEY: KeyCode = KeyCode::KeyM;
+const QUIT_KEY: KeyCode = KeyCode::KeyQ;
+// two cells further in on each side, as the walls are centered on the arena's edges
+const HARD_WALL_GROWTH: f32 = 80.0;
+
+/// The title screen the game boots into: Start, also a tap anywhere,
+/// Continue when a run was saved, a difficulty picked with D or Left/Right,
+/// solid or wrapping walls toggled with W, one or two players toggled with
+/// T, the game mode cycled with M, the credits on R, and Quit. The difficulty scales how fast
+/// the snake moves and, on Hard, thickens the walls so the arena is smaller.
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(
+                OnExit(GameState::MainMenu),
+                despawn_screen::<MainMenuScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    main_menu_input,
+                    (despawn_screen::<MainMenuScreen>, spawn_main_menu)
+                        .chain()
+                        .run_if(
+                            resource_changed::<Difficulty>
+                                .or_else(resource_changed::<GameSettings>)
+                                .or_else(resource_changed::<GameMode>),
+                        ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// How many times faster than the rules' speed the snake moves.
+    pub fn speed_multiplier(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    /// Added to the configured wall thickness.
+    pub fn wall_growth(self) -> f32 {
+        match self {
+            Difficulty::Hard => HARD_WALL_GROWTH,
+            Difficulty::Easy | Difficulty::Normal => 0.0,
+        }
+    }
+
+    fn step(self, forward: bool) -> Self {
+        let count = Difficulty::ALL.len();
+        let index = Difficulty::ALL
+            .iter()
+            .position(|&difficulty| difficulty == self)
+            .unwrap_or_default();
+        let index = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        Difficulty::ALL[index]
+    }
+}
+
+#[derive(Component)]
+struct MainMenuScreen;
+
+#[allow(clippy::too_many_arguments)]
+fn main_menu_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    touches: Res<Touches>,
+    mut difficulty: ResMut<Difficulty>,
+    mut settings: ResMut<GameSettings>,
+    mut mode: ResMut<GameMode>,
+    mut exit: EventWriter<AppExit>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // a tap starts too, as touch screens have no keys to pick the entries with
+    if keyboard_input.just_pressed(bindings.confirm) || touches.any_just_pressed() {
+        next_state.set(GameState::Playing);
+        return;
+    }
+    if keyboard_input.just_pressed(QUIT_KEY) && can_quit() {
+        exit.send(AppExit);
+        return;
+    }
+    if keyboard_input.just_pressed(WALLS_KEY) {
+        settings.modifiers.walls = settings.modifiers.walls.toggled();
+        return;
+    }
+    if keyboard_input.just_pressed(PLAYERS_KEY) {
+        settings.modifiers.two_player = !settings.modifiers.two_player;
+        return;
+    }
+    if keyboard_input.just_pressed(MODE_KEY) {
+        *mode = mode.next();
+        return;
+    }
+
+    let forward = keyboard_input.any_just_pressed([DIFFICULTY_KEY, KeyCode::ArrowRight]);
+    if !forward && !keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        return;
+    }
+    *difficulty = difficulty.step(forward);
+}
+
+// a browser tab can't be closed from the page
+fn can_quit() -> bool {
+    !cfg!(target_arch = "wasm32")
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    bindings: Res<KeyBindings>,
+    difficulty: Res<Difficulty>,
+    settings: Res<GameSettings>,
+    mode: Res<GameMode>,
+    saved_game: Res<SavedGame>,
+) {
+    let mut entries = vec![(
+        format!("Start ({})", key_name(bindings.confirm)),
+        bindings.confirm,
+    )];
+    if saved_game.can_continue() {
+        entries.push((
+            format!("Continue ({})", key_name(CONTINUE_KEY)),
+            CONTINUE_KEY,
+        ));
+    }
+    entries.extend([
+        (
+            format!(
+                "Difficulty: < {} > ({})",
+                difficulty.label(),
+                key_name(DIFFICULTY_KEY)
+            ),
+            DIFFICULTY_KEY,
+        ),
+        (
+            format!(
+                "Walls: {} ({})",
+                settings.modifiers.walls.label(),
+                key_name(WALLS_KEY)
+            ),
+            WALLS_KEY,
+        ),
+        (
+            format!(
+                "Players: {} ({})",
+                if settings.modifiers.two_player { 2 } else { 1 },
+                key_name(PLAYERS_KEY)
+            ),
+            PLAYERS_KEY,
+        ),
+        (
+            format!("Mode: {} ({})", mode.label(), key_name(MODE_KEY)),
+            MODE_KEY,
+        ),
+        (
+            format!("Credits ({})", key_name(MENU_CREDITS_KEY)),
+            MENU_CREDITS_KEY,
+        ),
+    ]);
+    if can_quit() {
+        entries.push((format!("Quit ({})", key_name(QUIT_KEY)), QUIT_KEY));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            MainMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Snake",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+            for (label, key) in entries {
+                parent.spawn((
+                    TextBundle::from_section(
+                        label,
+                        TextStyle {
+                            font_size: 28.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    MenuItem(key),
+                ));
+            }
+        });
+}
@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*};
+
+use crate::{
+    input::{KeyBindings, STICK_THRESHOLD},
+    GameState,
+};
+
+const HINT_COLOR: Color = Color::rgb(0.5, 0.5, 1.0);
+const FOCUS_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+const FOCUS_WIDTH: f32 = 2.0;
+const FOCUS_OFFSET: f32 = 4.0;
+
+/// Keyboard and gamepad focus for every menu screen: the up and down arrows,
+/// the d-pad or the left stick step through the entries on screen, wrapping
+/// around at either end, and Enter or the south face button picks the focused
+/// one. The east face button stands in for Escape, which is left to each
+/// screen to go back with. Entries are tagged with `MenuItem` and the key
+/// their screen already answers to, so picking one just presses that key and
+/// each screen keeps handling its own input. A hint bar along the bottom
+/// shows the prompts for whichever device was used last.
+pub struct MenuFocusPlugin;
+
+impl Plugin for MenuFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .init_resource::<LastInputDevice>()
+            .add_systems(
+                PreUpdate,
+                (detect_input_device, navigate_menu, highlight_focus)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(Update, update_hint_bar);
+    }
+}
+
+/// A menu entry that can take the focus. Picking it presses the key.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MenuItem(pub KeyCode);
+
+/// The key of the focused entry. Screens respawn their entries whenever they
+/// change, so the focus follows the key rather than the entity.
+#[derive(Resource, Default, Debug)]
+struct MenuFocus(Option<KeyCode>);
+
+/// Whichever of the keyboard or a gamepad was touched last, for the prompts.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LastInputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(Component)]
+struct MenuHintBar;
+
+#[derive(Component)]
+struct MenuHintText;
+
+#[derive(SystemParam)]
+struct GamepadMenuInput<'w, 's> {
+    gamepads: Res<'w, Gamepads>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    // the stick has to come back to rest between steps
+    stick_leaning: Local<'s, bool>,
+}
+
+impl GamepadMenuInput<'_, '_> {
+    fn just_pressed(&self, button: GamepadButtonType) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.buttons
+                .just_pressed(GamepadButton::new(gamepad, button))
+        })
+    }
+
+    /// Whether the stick was just pushed up (`Some(true)`) or down.
+    fn stick_pushed(&mut self) -> Option<bool> {
+        let lean = self
+            .gamepads
+            .iter()
+            .filter_map(|gamepad| {
+                self.axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            })
+            .find(|y| y.abs() > STICK_THRESHOLD);
+        let was_leaning = std::mem::replace(&mut *self.stick_leaning, lean.is_some());
+        lean.filter(|_| !was_leaning).map(|y| y > 0.0)
+    }
+}
+
+fn detect_input_device(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut device: ResMut<LastInputDevice>,
+) {
+    let stick_moved = gamepad_axes.devices().any(|axis| {
+        gamepad_axes
+            .get(*axis)
+            .is_some_and(|value| value.abs() > STICK_THRESHOLD)
+    });
+    if gamepad_buttons.get_just_pressed().next().is_some() || stick_moved {
+        device.set_if_neq(LastInputDevice::Gamepad);
+    } else if keyboard_input.get_just_pressed().next().is_some() {
+        device.set_if_neq(LastInputDevice::Keyboard);
+    }
+}
+
+// pressed and let go at once, so it reads as a fresh press this frame
+// without being held into the next
+fn tap(keyboard_input: &mut ButtonInput<KeyCode>, key: KeyCode) {
+    keyboard_input.press(key);
+    keyboard_input.release(key);
+}
+
+fn navigate_menu(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut gamepad: GamepadMenuInput,
+    bindings: Res<KeyBindings>,
+    state: Res<State<GameState>>,
+    mut focus: ResMut<MenuFocus>,
+    items: Query<(&MenuItem, &GlobalTransform, &ViewVisibility)>,
+) {
+    let stick = gamepad.stick_pushed();
+    let down = keyboard_input.just_pressed(KeyCode::ArrowDown)
+        || gamepad.just_pressed(GamepadButtonType::DPadDown)
+        || stick == Some(false);
+    let up = keyboard_input.just_pressed(KeyCode::ArrowUp)
+        || gamepad.just_pressed(GamepadButtonType::DPadUp)
+        || stick == Some(true);
+    // the face buttons mean nothing to a run in progress
+    let in_menu = *state.get() != GameState::Playing;
+    let select = in_menu && gamepad.just_pressed(GamepadButtonType::South);
+    if in_menu && gamepad.just_pressed(GamepadButtonType::East) {
+        tap(&mut keyboard_input, KeyCode::Escape);
+    }
+
+    // top to bottom, then left to right, the way the screen reads
+    let mut entries: Vec<(KeyCode, Vec2)> = items
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(item, transform, _)| (item.0, transform.translation().truncate()))
+        .collect();
+    entries.sort_by(|(_, a), (_, b)| {
+        a.y.partial_cmp(&b.y)
+            .unwrap_or(Ordering::Equal)
+            .then(a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
+    });
+    entries.dedup_by_key(|(key, _)| *key);
+    let current = focus
+        .0
+        .and_then(|key| entries.iter().position(|(entry, _)| *entry == key));
+    // the focused entry left with its screen
+    if focus.0.is_some() && current.is_none() {
+        focus.0 = None;
+    }
+    let count = entries.len();
+    let step = if count == 0 {
+        None
+    } else if down {
+        Some(current.map_or(0, |index| (index + 1) % count))
+    } else if up {
+        Some(current.map_or(count - 1, |index| (index + count - 1) % count))
+    } else {
+        None
+    };
+    if let Some(index) = step {
+        focus.0 = Some(entries[index].0);
+        return;
+    }
+
+    match current.map(|index| entries[index].0) {
+        Some(key) => {
+            if keyboard_input.clear_just_pressed(KeyCode::Enter) || select {
+                tap(&mut keyboard_input, key);
+            }
+        }
+        // with nothing focused the south button confirms, like the confirm key
+        None if select => tap(&mut keyboard_input, bindings.confirm),
+        None => {}
+    }
+}
+
+fn highlight_focus(
+    mut commands: Commands,
+    focus: Res<MenuFocus>,
+    items: Query<(Entity, &MenuItem, Has<Outline>)>,
+) {
+    for (entity, item, outlined) in &items {
+        let focused = focus.0 == Some(item.0);
+        if focused && !outlined {
+            commands.entity(entity).insert(Outline::new(
+                Val::Px(FOCUS_WIDTH),
+                Val::Px(FOCUS_OFFSET),
+                FOCUS_COLOR,
+            ));
+        } else if !focused && outlined {
+            commands.entity(entity).remove::<Outline>();
+        }
+    }
+}
+
+fn hint_text(device: LastInputDevice) -> &'static str {
+    match device {
+        LastInputDevice::Keyboard => "Up/Down move   Enter select   Esc back",
+        LastInputDevice::Gamepad => "D-pad move   (A) select   (B) back",
+    }
+}
+
+// shown whenever there's a menu on screen
+fn update_hint_bar(
+    mut commands: Commands,
+    device: Res<LastInputDevice>,
+    items: Query<(), With<MenuItem>>,
+    bars: Query<Entity, With<MenuHintBar>>,
+    mut texts: Query<&mut Text, With<MenuHintText>>,
+) {
+    if items.is_empty() {
+        for bar in &bars {
+            commands.entity(bar).despawn_recursive();
+        }
+        return;
+    }
+    if !bars.is_empty() {
+        if device.is_changed() {
+            for mut text in &mut texts {
+                text.sections[0].value = hint_text(*device).to_string();
+            }
+        }
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(4.0),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                // above full-screen menus like the mutator picker
+                z_index: ZIndex::Global(6),
+                ..default()
+            },
+            MenuHintBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    hint_text(*device),
+                    TextStyle {
+                        font_size: 14.0,
+                        color: HINT_COLOR,
+                        ..default()
+                    },
+                ),
+                MenuHintText,
+            ));
+        });
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use crate::{
+    new_run, settings::GameSettings, toast::Toast, visual_cues::Cue, Direction, GameState,
+    OnGameScreen,
+};
+
+const BURST_INTERVAL_SECONDS: f32 = 20.0;
+const BURST_WARNING_SECONDS: f32 = 2.0;
+const BURST_SECONDS: f32 = 6.0;
+const BANNER_COLOR: Color = Color::rgb(1.0, 0.4, 0.9);
+
+/// Mirrored-controls modifier: left and right, and optionally up and down,
+/// swap places for the whole run or in bursts announced by a banner. The
+/// flip lives in `InputMirror`, which `TurnInput` applies on top of the
+/// weekly mirror mutator.
+pub struct MirroredControlsPlugin;
+
+impl Plugin for MirroredControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMirror>()
+            .init_resource::<MirrorBursts>()
+            .add_systems(OnEnter(GameState::Playing), start_mirroring.run_if(new_run))
+            .add_systems(OnEnter(GameState::GameOver), stop_mirroring)
+            .add_systems(
+                Update,
+                time_mirror_bursts
+                    .run_if(|settings: Res<GameSettings>| {
+                        settings.modifiers.mirrored && settings.modifiers.mirror_bursts
+                    })
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Which input axes are flipped right now.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct InputMirror {
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+impl InputMirror {
+    pub fn apply(self, direction: Direction) -> Direction {
+        match direction {
+            Direction::Left | Direction::Right if self.horizontal => direction.opposite(),
+            Direction::Up | Direction::Down if self.vertical => direction.opposite(),
+            _ => direction,
+        }
+    }
+
+    fn from_settings(settings: &GameSettings) -> Self {
+        InputMirror {
+            horizontal: true,
+            vertical: settings.modifiers.mirror_vertical,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct MirrorBursts {
+    phase: BurstPhase,
+    timer: Option<Timer>,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+enum BurstPhase {
+    #[default]
+    Calm,
+    Warning,
+    Mirrored,
+}
+
+#[derive(Component)]
+struct MirrorBanner;
+
+fn start_mirroring(
+    settings: Res<GameSettings>,
+    mut mirror: ResMut<InputMirror>,
+    mut bursts: ResMut<MirrorBursts>,
+) {
+    let modifiers = &settings.modifiers;
+    *mirror = if modifiers.mirrored && !modifiers.mirror_bursts {
+        InputMirror::from_settings(&settings)
+    } else {
+        InputMirror::default()
+    };
+    *bursts = MirrorBursts::default();
+}
+
+fn stop_mirroring(mut mirror: ResMut<InputMirror>) {
+    *mirror = InputMirror::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn time_mirror_bursts(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut mirror: ResMut<InputMirror>,
+    mut bursts: ResMut<MirrorBursts>,
+    mut toasts: EventWriter<Toast>,
+    banners: Query<Entity, With<MirrorBanner>>,
+    mut cues: EventWriter<Cue>,
+) {
+    let phase = bursts.phase;
+    let timer = bursts
+        .timer
+        .get_or_insert_with(|| Timer::from_seconds(BURST_INTERVAL_SECONDS, TimerMode::Once));
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for shown in &banners {
+        commands.entity(shown).despawn_recursive();
+    }
+    let (next_phase, seconds) = match phase {
+        BurstPhase::Calm => {
+            commands.spawn(banner("Controls flipping soon!"));
+            cues.send(Cue::HazardWarning);
+            (BurstPhase::Warning, BURST_WARNING_SECONDS)
+        }
+        BurstPhase::Warning => {
+            *mirror = InputMirror::from_settings(&settings);
+            commands.spawn(banner("Mirrored!"));
+            cues.send(Cue::Callout("Mirrored!".to_string()));
+            (BurstPhase::Mirrored, BURST_SECONDS)
+        }
+        BurstPhase::Mirrored => {
+            *mirror = InputMirror::default();
+            toasts.send(Toast("Controls back to normal".to_string()));
+            (BurstPhase::Calm, BURST_INTERVAL_SECONDS)
+        }
+    };
+    *bursts = MirrorBursts {
+        phase: next_phase,
+        timer: Some(Timer::from_seconds(seconds, TimerMode::Once)),
+    };
+}
+
+fn banner(message: &str) -> impl Bundle {
+    (
+        TextBundle::from_section(
+            message,
+            TextStyle {
+                font_size: 36.0,
+                color: BANNER_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(30.0),
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        MirrorBanner,
+        OnGameScreen,
+    )
+}
@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    progression::Progression,
+    toast::Toast,
+    Direction, GameRng, GameState, OnGameScreen, SafeArea, SnakeBodySegment, SnakeTurned,
+};
+
+const ACTIVE_MISSIONS: usize = 3;
+const SURVIVE_SECONDS: f32 = 60.0;
+
+/// Rotating in-run missions that pay out coins into the saved progression.
+pub struct MissionsPlugin;
+
+impl Plugin for MissionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveMissions>()
+            .init_resource::<MissionPanel>()
+            .add_systems(OnEnter(GameState::GameOver), clear_missions)
+            .add_systems(
+                Update,
+                (
+                    roll_missions,
+                    track_missions.after(DetectCollisions),
+                    spawn_mission_panel,
+                    toggle_mission_panel,
+                    update_mission_panel,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MissionGoal {
+    EatApples(u32),
+    SurviveWithout(Direction),
+    ReachLength(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Mission {
+    goal: MissionGoal,
+    progress: f32,
+}
+
+impl Mission {
+    fn random(rng: &mut GameRng) -> Self {
+        let goal = match rng.0.gen_range(0..3) {
+            0 => MissionGoal::EatApples(rng.0.gen_range(3..=8)),
+            1 => MissionGoal::SurviveWithout(
+                [
+                    Direction::Up,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Right,
+                ][rng.0.gen_range(0..4)],
+            ),
+            _ => MissionGoal::ReachLength(rng.0.gen_range(8..=15)),
+        };
+        Mission {
+            goal,
+            progress: 0.0,
+        }
+    }
+
+    fn target(&self) -> f32 {
+        match self.goal {
+            MissionGoal::EatApples(apples) => apples as f32,
+            MissionGoal::SurviveWithout(_) => SURVIVE_SECONDS,
+            MissionGoal::ReachLength(length) => length as f32,
+        }
+    }
+
+    fn reward(&self) -> u32 {
+        match self.goal {
+            MissionGoal::EatApples(apples) => apples * 5,
+            MissionGoal::SurviveWithout(_) => 40,
+            MissionGoal::ReachLength(length) => length as u32 * 3,
+        }
+    }
+
+    fn complete(&self) -> bool {
+        self.progress >= self.target()
+    }
+
+    fn description(&self) -> String {
+        match self.goal {
+            MissionGoal::EatApples(apples) => format!("Eat {apples} apples"),
+            MissionGoal::SurviveWithout(direction) => {
+                let direction = format!("{direction:?}").to_lowercase();
+                format!("Survive {SURVIVE_SECONDS:.0} s without turning {direction}")
+            }
+            MissionGoal::ReachLength(length) => format!("Grow to length {length}"),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct ActiveMissions(Vec<Mission>);
+
+#[derive(Resource, Default)]
+struct MissionPanel {
+    collapsed: bool,
+}
+
+#[derive(Component)]
+struct MissionPanelRoot;
+
+#[derive(Component)]
+struct MissionList;
+
+fn clear_missions(mut missions: ResMut<ActiveMissions>) {
+    missions.0.clear();
+}
+
+fn roll_missions(mut missions: ResMut<ActiveMissions>, mut rng: ResMut<GameRng>) {
+    while missions.0.len() < ACTIVE_MISSIONS {
+        missions.0.push(Mission::random(&mut rng));
+    }
+}
+
+fn track_missions(
+    time: Res<Time>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut turns: EventReader<SnakeTurned>,
+    mut missions: ResMut<ActiveMissions>,
+    mut progression: ResMut<Progression>,
+    mut toasts: EventWriter<Toast>,
+    segments: Query<(), With<SnakeBodySegment>>,
+) {
+    let apples = collisions
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .count();
+    let turned: Vec<Direction> = turns
+        .read()
+        .map(|SnakeTurned(direction)| *direction)
+        .collect();
+    let length = segments.iter().count() + 1;
+
+    for mission in &mut missions.0 {
+        match mission.goal {
+            MissionGoal::EatApples(_) => mission.progress += apples as f32,
+            MissionGoal::SurviveWithout(direction) => {
+                if turned.contains(&direction) {
+                    mission.progress = 0.0;
+                } else {
+                    mission.progress += time.delta_seconds();
+                }
+            }
+            MissionGoal::ReachLength(_) => mission.progress = length as f32,
+        }
+    }
+
+    let (done, remaining): (Vec<Mission>, Vec<Mission>) =
+        missions.0.drain(..).partition(Mission::complete);
+    missions.0 = remaining;
+    if done.is_empty() {
+        return;
+    }
+
+    for mission in &done {
+        progression.coins += mission.reward();
+        progression.missions_completed += 1;
+        toasts.send(Toast(format!(
+            "Mission complete: {} (+{} coins)",
+            mission.description(),
+            mission.reward()
+        )));
+    }
+    progression.save();
+}
+
+fn spawn_mission_panel(
+    mut commands: Commands,
+    safe_area: Res<SafeArea>,
+    panels: Query<(), With<MissionPanelRoot>>,
+) {
+    if !panels.is_empty() {
+        return;
+    }
+
+    let style = TextStyle {
+        font_size: 18.0,
+        color: Color::rgb(0.8, 0.8, 1.0),
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(80.0 + safe_area.top),
+                    left: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+                ..default()
+            },
+            MissionPanelRoot,
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Missions (Tab)", style.clone()));
+            parent.spawn((TextBundle::from_section("", style), MissionList));
+        });
+}
+
+fn toggle_mission_panel(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<MissionPanel>,
+    mut lists: Query<&mut Style, With<MissionList>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        panel.collapsed = !panel.collapsed;
+    }
+    let display = if panel.collapsed {
+        Display::None
+    } else {
+        Display::Flex
+    };
+    for mut style in &mut lists {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+fn update_mission_panel(
+    missions: Res<ActiveMissions>,
+    progression: Res<Progression>,
+    mut lists: Query<&mut Text, With<MissionList>>,
+) {
+    let mut lines: Vec<String> = missions
+        .0
+        .iter()
+        .map(|mission| {
+            format!(
+                "{} ({:.0}/{:.0})",
+                mission.description(),
+                mission.progress.min(mission.target()).floor(),
+                mission.target()
+            )
+        })
+        .collect();
+    lines.push(format!("Coins: {}", progression.coins));
+    let contents = lines.join("\n");
+
+    for mut text in &mut lists {
+        if text.sections[0].value != contents {
+            text.sections[0].value.clone_from(&contents);
+        }
+    }
+}
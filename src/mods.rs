@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+use wasmi::{Caller, Config, Engine, Extern, Linker, Module, Store, TypedFunc};
+
+use crate::{
+    apple_spawn_at,
+    collision::{ColliderKind, CollisionEvent},
+    score_update, storage, Ending, GameAssets, GameState, RunEnd, Scoreboard, BOTTOM_WALL,
+    LEFT_WALL, RIGHT_WALL, SEGMENT_SIZE, TOP_WALL,
+};
+
+const MODS_FOLDER: &str = "mods";
+// wasm instructions a mod may run per call before it's cut off
+const FUEL_PER_CALL: u64 = 1_000_000;
+
+const EVENT_APPLE_EATEN: i32 = 0;
+const EVENT_TICK: i32 = 1;
+const EVENT_DEATH: i32 = 2;
+
+/// Loads every `.wasm` file in the `mods` data folder. Mods get no WASI, only
+/// the host functions in the `snake` import module, and a fuel budget per call:
+///
+/// - `subscribe(event: i32)`: 0 apple eaten, 1 tick, 2 death
+/// - `spawn_pickup(x: f32, y: f32)`: places an apple in the arena
+/// - `add_score(points: i32)`
+/// - `log(ptr: i32, len: i32)`: prints a UTF-8 string from the mod's memory
+///
+/// A mod can export `init()`, called once after loading, and
+/// `on_event(event: i32, value: i32)` for the events it subscribed to; the
+/// value is the score, or the milliseconds since the last tick. A mod that
+/// traps or runs out of fuel is switched off.
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_non_send_resource(Mods::load())
+            .add_systems(
+                Update,
+                run_gameplay_events
+                    .after(score_update)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), run_death_event);
+    }
+}
+
+#[derive(Default)]
+struct HostState {
+    // one bit per event id
+    subscriptions: u32,
+    pickups: Vec<Vec2>,
+    score_delta: i32,
+}
+
+struct LoadedMod {
+    name: String,
+    store: Store<HostState>,
+    on_event: Option<TypedFunc<(i32, i32), ()>>,
+}
+
+impl LoadedMod {
+    fn load(
+        engine: &Engine,
+        linker: &Linker<HostState>,
+        name: String,
+        bytes: &[u8],
+    ) -> Result<Self, String> {
+        let module = Module::new(engine, bytes).map_err(|err| err.to_string())?;
+        let mut store = Store::new(engine, HostState::default());
+        store
+            .add_fuel(FUEL_PER_CALL)
+            .map_err(|err| err.to_string())?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|err| err.to_string())?;
+
+        if let Ok(init) = instance.get_typed_func::<(), ()>(&store, "init") {
+            init.call(&mut store, ()).map_err(|err| err.to_string())?;
+        }
+        let on_event = instance
+            .get_typed_func::<(i32, i32), ()>(&store, "on_event")
+            .ok();
+        Ok(LoadedMod {
+            name,
+            store,
+            on_event,
+        })
+    }
+
+    fn subscribed(&self, event: i32) -> bool {
+        self.on_event.is_some() && self.store.data().subscriptions & (1 << event) != 0
+    }
+
+    fn send(&mut self, event: i32, value: i32) -> Result<(), String> {
+        let Some(on_event) = self.on_event else {
+            return Ok(());
+        };
+        // topped back up to the budget, so a quiet mod can't save fuel up
+        let remaining = self.store.consume_fuel(0).map_err(|err| err.to_string())?;
+        self.store
+            .add_fuel(FUEL_PER_CALL.saturating_sub(remaining))
+            .map_err(|err| err.to_string())?;
+        on_event
+            .call(&mut self.store, (event, value))
+            .map_err(|err| err.to_string())
+    }
+}
+
+struct Mods(Vec<LoadedMod>);
+
+impl Mods {
+    fn load() -> Self {
+        let paths = storage::data_files(MODS_FOLDER, "wasm");
+        if paths.is_empty() {
+            return Mods(Vec::new());
+        }
+
+        let engine = Engine::new(Config::default().consume_fuel(true));
+        let linker = match host_api(&engine) {
+            Ok(linker) => linker,
+            Err(err) => {
+                error!("mod host API unavailable: {err}");
+                return Mods(Vec::new());
+            }
+        };
+
+        let mods = paths
+            .into_iter()
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().into_owned();
+                let loaded = std::fs::read(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|bytes| LoadedMod::load(&engine, &linker, name.clone(), &bytes));
+                match loaded {
+                    Ok(loaded) => {
+                        info!("loaded mod {name}");
+                        Some(loaded)
+                    }
+                    Err(err) => {
+                        warn!("mod {name} failed to load: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Mods(mods)
+    }
+
+    fn send(&mut self, event: i32, value: i32) {
+        self.0.retain_mut(|loaded| {
+            if !loaded.subscribed(event) {
+                return true;
+            }
+            match loaded.send(event, value) {
+                Ok(()) => true,
+                Err(err) => {
+                    warn!("mod {} switched off: {err}", loaded.name);
+                    false
+                }
+            }
+        });
+    }
+
+    fn take_requests(&mut self) -> (Vec<Vec2>, i32) {
+        let mut pickups = Vec::new();
+        let mut score_delta = 0;
+        for loaded in &mut self.0 {
+            let state = loaded.store.data_mut();
+            pickups.append(&mut state.pickups);
+            score_delta = std::mem::take(&mut state.score_delta).saturating_add(score_delta);
+        }
+        (pickups, score_delta)
+    }
+}
+
+fn host_api(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::new(engine);
+    linker
+        .func_wrap(
+            "snake",
+            "subscribe",
+            |mut caller: Caller<'_, HostState>, event: i32| {
+                if (0..32).contains(&event) {
+                    caller.data_mut().subscriptions |= 1 << event;
+                }
+            },
+        )?
+        .func_wrap(
+            "snake",
+            "spawn_pickup",
+            |mut caller: Caller<'_, HostState>, x: f32, y: f32| {
+                caller.data_mut().pickups.push(Vec2::new(x, y));
+            },
+        )?
+        .func_wrap(
+            "snake",
+            "add_score",
+            |mut caller: Caller<'_, HostState>, points: i32| {
+                let state = caller.data_mut();
+                state.score_delta = state.score_delta.saturating_add(points);
+            },
+        )?
+        .func_wrap(
+            "snake",
+            "log",
+            |caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+                    return;
+                };
+                let start = ptr as usize;
+                let bytes = memory
+                    .data(&caller)
+                    .get(start..start.saturating_add(len as usize));
+                if let Some(bytes) = bytes {
+                    info!("mod: {}", String::from_utf8_lossy(bytes));
+                }
+            },
+        )?;
+    Ok(linker)
+}
+
+fn run_gameplay_events(
+    mut commands: Commands,
+    mut mods: NonSendMut<Mods>,
+    mut collisions: EventReader<CollisionEvent>,
+    time: Res<Time>,
+    game_assets: Res<GameAssets>,
+    mut scoreboard: ResMut<Scoreboard>,
+) {
+    if mods.0.is_empty() {
+        return;
+    }
+    let apples = collisions
+        .read()
+        .filter(|collision| collision.kind == ColliderKind::Apple)
+        .count();
+    for _ in 0..apples {
+        mods.send(EVENT_APPLE_EATEN, scoreboard.score as i32);
+    }
+    mods.send(EVENT_TICK, time.delta().as_millis() as i32);
+
+    let (pickups, score_delta) = mods.take_requests();
+    // pickups land on the grid the snake moves on, inside the walls
+    let half = SEGMENT_SIZE / 2.0;
+    for position in pickups {
+        let position = ((position / SEGMENT_SIZE).round() * SEGMENT_SIZE).clamp(
+            Vec2::new(LEFT_WALL, BOTTOM_WALL) + half,
+            Vec2::new(RIGHT_WALL, TOP_WALL) - half,
+        );
+        apple_spawn_at(&mut commands, &game_assets, position.extend(-2.0));
+    }
+    if score_delta != 0 {
+        scoreboard.score = scoreboard.score.saturating_add_signed(score_delta);
+    }
+}
+
+fn run_death_event(mut mods: NonSendMut<Mods>, run_end: Res<RunEnd>, scoreboard: Res<Scoreboard>) {
+    // the credits and help screens come back through game over, and wins aren't deaths
+    if run_end.unrecorded().and_then(Ending::death_cause).is_none() {
+        return;
+    }
+    mods.send(EVENT_DEATH, scoreboard.score as i32);
+    // the run is over, so whatever the mods asked for on death is dropped
+    mods.take_requests();
+}
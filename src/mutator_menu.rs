@@ -0,0 +1,397 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    challenge::{ActiveChallenge, Mutators},
+    despawn_screen, display_final_score,
+    gravity::apply_gravity_rules,
+    hardcore::HardcoreMode,
+    input::{key_name, KeyBindings},
+    kids::apply_kids_presets,
+    menu::MenuItem,
+    new_run,
+    rules::GameRules,
+    settings::GameSettings,
+    setup,
+    theme::Theme,
+    GameState, OnGameOverScreen, OnGameScreen, SafeArea,
+};
+
+type Toggle = (KeyCode, &'static str, fn(&mut Mutators) -> &mut bool);
+
+const TOGGLES: [Toggle; 5] = [
+    (KeyCode::Digit1, "Double speed", |mutators| {
+        &mut mutators.double_speed
+    }),
+    (KeyCode::Digit2, "Mirror controls", |mutators| {
+        &mut mutators.mirror_controls
+    }),
+    (KeyCode::Digit3, "Fog", |mutators| &mut mutators.fog),
+    (KeyCode::Digit4, "No walls", |mutators| {
+        &mut mutators.no_walls
+    }),
+    (KeyCode::Digit5, "Tiny hitbox", |mutators| {
+        &mut mutators.tiny_hitbox
+    }),
+];
+const BADGE_COLOR: Color = Color::rgba(1.0, 0.8, 0.3, 0.85);
+const RANDOMIZER_KEY: KeyCode = KeyCode::KeyR;
+const RANDOMIZER_BANNER_SECONDS: f32 = 3.0;
+
+/// A screen opened with O from the game-over screen where the next run's
+/// mutators are picked with the number keys before starting it, or left to
+/// the randomizer, which rolls mutators and a theme for every run. Whatever
+/// mutators a run has, from here or the weekly challenge, show as badges
+/// in the HUD.
+pub struct MutatorMenuPlugin;
+
+impl Plugin for MutatorMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MutatorChoice>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_mutators_hint.after(display_final_score),
+            )
+            .add_systems(
+                Update,
+                open_mutator_menu.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnEnter(GameState::Mutators), spawn_mutator_menu)
+            .add_systems(
+                OnExit(GameState::Mutators),
+                despawn_screen::<MutatorMenuScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    pick_mutators,
+                    (despawn_screen::<MutatorMenuScreen>, spawn_mutator_menu)
+                        .chain()
+                        .run_if(resource_changed::<MutatorChoice>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Mutators)),
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                roll_random_mutators
+                    .after(apply_kids_presets)
+                    .before(apply_gravity_rules)
+                    .before(setup)
+                    .run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (spawn_mutator_badges, count_down_randomizer_banner)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// The mutators picked on the screen, kept between runs so the same set is
+/// one key away.
+#[derive(Resource, Default)]
+struct MutatorChoice {
+    mutators: Mutators,
+    // rolls new mutators and a theme as every run starts instead
+    randomize: bool,
+}
+
+#[derive(Component)]
+struct MutatorMenuScreen;
+
+#[derive(Component)]
+struct MutatorBadges;
+
+#[derive(Component)]
+struct RandomizerBanner {
+    rolled: String,
+    countdown: Timer,
+}
+
+fn display_mutators_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Press O to pick mutators",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(220.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyO),
+        OnGameOverScreen,
+    ));
+}
+
+fn open_mutator_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyO) {
+        next_state.set(GameState::Mutators);
+    }
+}
+
+fn pick_mutators(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut choice: ResMut<MutatorChoice>,
+    mut challenge: ResMut<ActiveChallenge>,
+    mut mutators: ResMut<Mutators>,
+    mut rules: ResMut<GameRules>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::GameOver);
+        return;
+    }
+    if keyboard_input.just_pressed(bindings.confirm) {
+        challenge.end();
+        *mutators = choice.mutators;
+        *rules = mutators.rules();
+        next_state.set(GameState::Playing);
+        return;
+    }
+    for (key, _, toggle) in TOGGLES {
+        if keyboard_input.just_pressed(key) {
+            let enabled = toggle(&mut choice.mutators);
+            *enabled = !*enabled;
+        }
+    }
+    if keyboard_input.just_pressed(RANDOMIZER_KEY) {
+        choice.randomize = !choice.randomize;
+    }
+}
+
+fn spawn_mutator_menu(
+    mut commands: Commands,
+    bindings: Res<KeyBindings>,
+    choice: Res<MutatorChoice>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            MutatorMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Mutators",
+                TextStyle {
+                    font_size: 36.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+            for (key, name, toggle) in TOGGLES {
+                // toggles hand out `&mut`, so they read from a copy
+                let mut mutators = choice.mutators;
+                let enabled = *toggle(&mut mutators);
+                parent.spawn((
+                    TextBundle::from_section(
+                        format!(
+                            "{}  {name}: {}",
+                            key_name(key),
+                            if enabled { "on" } else { "off" }
+                        ),
+                        TextStyle {
+                            font_size: 20.0,
+                            color: if enabled { Color::WHITE } else { Color::GRAY },
+                            ..default()
+                        },
+                    ),
+                    MenuItem(key),
+                ));
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    format!(
+                        "{}  Randomize every run: {}",
+                        key_name(RANDOMIZER_KEY),
+                        if choice.randomize { "on" } else { "off" }
+                    ),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: if choice.randomize {
+                            Color::WHITE
+                        } else {
+                            Color::GRAY
+                        },
+                        ..default()
+                    },
+                ),
+                MenuItem(RANDOMIZER_KEY),
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    format!(
+                        "Number keys toggle; {} starts the run, Escape goes back",
+                        key_name(bindings.confirm)
+                    ),
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::rgb(0.5, 0.5, 1.0),
+                        ..default()
+                    },
+                ),
+                MenuItem(bindings.confirm),
+            ));
+        });
+}
+
+fn spawn_mutator_badges(
+    mut commands: Commands,
+    mutators: Res<Mutators>,
+    safe_area: Res<SafeArea>,
+    badges: Query<(), With<MutatorBadges>>,
+) {
+    let names = mutators.names();
+    if names.is_empty() || !badges.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(58.0 + safe_area.top),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MutatorBadges,
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            for name in names {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: BADGE_COLOR.into(),
+                        ..default()
+                    })
+                    .with_children(|badge| {
+                        badge.spawn(TextBundle::from_section(
+                            name,
+                            TextStyle {
+                                font_size: 14.0,
+                                color: Color::BLACK,
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+// rolled with the thread rng, so seeded runs like replays stay seeded
+#[allow(clippy::too_many_arguments)]
+fn roll_random_mutators(
+    mut commands: Commands,
+    choice: Res<MutatorChoice>,
+    settings: Res<GameSettings>,
+    challenge: Res<ActiveChallenge>,
+    hardcore: Res<HardcoreMode>,
+    mut mutators: ResMut<Mutators>,
+    mut rules: ResMut<GameRules>,
+    mut theme: ResMut<Theme>,
+    // whether the last run was rolled, so turning it off restores the picked set
+    mut rolled_last_run: Local<bool>,
+) {
+    // challenges, hardcore and kids mode bring their own rules
+    let kids_mode = settings.modifiers.kids_mode;
+    if !choice.randomize || challenge.is_active() || hardcore.is_active() || kids_mode {
+        if *rolled_last_run && !challenge.is_active() && !hardcore.is_active() {
+            *mutators = choice.mutators;
+            if !kids_mode {
+                *rules = mutators.rules();
+                *theme = Theme::classic();
+            }
+        }
+        *rolled_last_run = false;
+        return;
+    }
+
+    let mut rng = thread_rng();
+    let mut rolled = Mutators::default();
+    // at least one mutator, or the roll would be a plain run
+    while rolled == Mutators::default() {
+        for (_, _, toggle) in TOGGLES {
+            *toggle(&mut rolled) = rng.gen_bool(0.35);
+        }
+    }
+    *mutators = rolled;
+    *rules = rolled.rules();
+    let (theme_name, rolled_preset) = if rng.gen_bool(0.5) {
+        ("Classic", Theme::classic())
+    } else {
+        ("Kids", Theme::kids())
+    };
+    *theme = rolled_preset;
+    *rolled_last_run = true;
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 28.0,
+                color: BADGE_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(35.0),
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        RandomizerBanner {
+            rolled: format!("{} + {theme_name} theme", rolled.names().join(" + ")),
+            countdown: Timer::from_seconds(RANDOMIZER_BANNER_SECONDS, TimerMode::Once),
+        },
+        OnGameScreen,
+    ));
+}
+
+fn count_down_randomizer_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut RandomizerBanner, &mut Text)>,
+) {
+    for (entity, mut banner, mut text) in &mut banners {
+        if banner.countdown.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let seconds = banner.countdown.remaining_secs().ceil();
+        text.sections[0].value = format!("Randomizer: {}\n{seconds:.0}", banner.rolled);
+    }
+}
@@ -0,0 +1,270 @@
+use bevy::{
+    math::bounding::{Aabb2d, IntersectsVolume},
+    prelude::*,
+};
+use rand::seq::SliceRandom;
+
+use crate::{
+    arena::WallThickness, collision::DetectCollisions, move_snake, settings::GameSettings, sim,
+    toast::Toast, GameRng, GameState, OnGameScreen, SafeArea, Scoreboard, SnakeBodySegment,
+    SnakeHead,
+};
+
+const CHAIN_COLORS: [Color; 3] = [
+    Color::rgb(0.2, 0.5, 1.0),
+    Color::rgb(1.0, 0.85, 0.1),
+    Color::rgb(0.8, 0.3, 1.0),
+];
+const CHAIN_BONUS: u32 = 10;
+// seconds from the start of a run, or the last finished chain, to the next one
+const CHAIN_SECONDS: f32 = 20.0;
+const CHAIN_PLACEMENT_ATTEMPTS: usize = 64;
+const HUD_ICON_SIZE: f32 = 16.0;
+
+/// Optional objectives that come and go during a run, on top of eating
+/// apples. Apple chains, switched on in the settings, drop three colored
+/// apples that pay a big bonus when eaten in the order shown in the HUD.
+pub struct ObjectivesPlugin;
+
+impl Plugin for ObjectivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveObjective>()
+            .init_resource::<NextObjective>()
+            .add_systems(OnExit(GameState::GameOver), reset_objective)
+            .add_systems(
+                Update,
+                (
+                    start_objectives
+                        .run_if(|settings: Res<GameSettings>| settings.modifiers.apple_chains),
+                    eat_chain_apples.after(move_snake).before(DetectCollisions),
+                    update_chain_hud.run_if(resource_changed::<ActiveObjective>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Objective {
+    // indices into `CHAIN_COLORS`, in the order they must be eaten
+    AppleChain { order: Vec<usize>, eaten: usize },
+}
+
+#[derive(Resource, Default)]
+struct ActiveObjective(Option<Objective>);
+
+// kept apart from the objective so ticking it doesn't redraw the HUD
+#[derive(Resource)]
+struct NextObjective(Timer);
+
+impl Default for NextObjective {
+    fn default() -> Self {
+        NextObjective(Timer::from_seconds(CHAIN_SECONDS, TimerMode::Once))
+    }
+}
+
+#[derive(Component)]
+struct ChainApple(usize);
+
+#[derive(Component)]
+struct ChainHud;
+
+fn reset_objective(mut active: ResMut<ActiveObjective>, mut next: ResMut<NextObjective>) {
+    *active = ActiveObjective::default();
+    *next = NextObjective::default();
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn start_objectives(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    safe_area: Res<SafeArea>,
+    mut rng: ResMut<GameRng>,
+    mut active: ResMut<ActiveObjective>,
+    mut next: ResMut<NextObjective>,
+    snake: Query<&Transform, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+) {
+    if active.0.is_some() || !next.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..CHAIN_COLORS.len()).collect();
+    order.shuffle(&mut rng.0);
+    let occupied: Vec<Vec2> = snake
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    spawn_chain_apples(
+        &mut commands,
+        &mut rng,
+        &settings,
+        wall_thickness.0,
+        occupied,
+    );
+    active.0 = Some(Objective::AppleChain { order, eaten: 0 });
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(34.0 + safe_area.top),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        },
+        ChainHud,
+        OnGameScreen,
+    ));
+}
+
+fn spawn_chain_apples(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    settings: &GameSettings,
+    wall_thickness: f32,
+    mut occupied: Vec<Vec2>,
+) {
+    let hitboxes = &settings.hitboxes;
+    for (color_index, color) in CHAIN_COLORS.into_iter().enumerate() {
+        let Some(position) = sim::free_apple_position(
+            &mut rng.0,
+            &occupied,
+            hitboxes.apple_half_size(),
+            wall_thickness,
+            CHAIN_PLACEMENT_ATTEMPTS,
+        ) else {
+            continue;
+        };
+        occupied.push(position);
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: position.extend(-2.0),
+                    scale: (hitboxes.apple_half_size() * 2.0).extend(1.0),
+                    ..default()
+                },
+                sprite: Sprite { color, ..default() },
+                ..default()
+            },
+            ChainApple(color_index),
+            OnGameScreen,
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn eat_chain_apples(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    mut rng: ResMut<GameRng>,
+    mut active: ResMut<ActiveObjective>,
+    mut next: ResMut<NextObjective>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut toasts: EventWriter<Toast>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    body: Query<&Transform, With<SnakeBodySegment>>,
+    apples: Query<(Entity, &Transform, &ChainApple)>,
+    huds: Query<Entity, With<ChainHud>>,
+) {
+    let Some(Objective::AppleChain { order, eaten }) = active.0.clone() else {
+        return;
+    };
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let head_box = Aabb2d::new(head.translation.truncate(), settings.hitboxes.head() / 2.0);
+    let Some((entity, &ChainApple(color))) = apples
+        .iter()
+        .find(|(_, transform, _)| {
+            head_box.intersects(&Aabb2d::new(
+                transform.translation.truncate(),
+                settings.hitboxes.apple_half_size(),
+            ))
+        })
+        .map(|(entity, _, chain_apple)| (entity, chain_apple))
+    else {
+        return;
+    };
+
+    if order[eaten] != color {
+        // the wrong color puts every chain apple back somewhere new
+        for (entity, _, _) in &apples {
+            commands.entity(entity).despawn();
+        }
+        let occupied = body
+            .iter()
+            .chain([head])
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        spawn_chain_apples(
+            &mut commands,
+            &mut rng,
+            &settings,
+            wall_thickness.0,
+            occupied,
+        );
+        toasts.send(Toast("Wrong color, the chain starts over".to_string()));
+        active.0 = Some(Objective::AppleChain { order, eaten: 0 });
+        return;
+    }
+
+    commands.entity(entity).despawn();
+    let eaten = eaten + 1;
+    if eaten < order.len() {
+        active.0 = Some(Objective::AppleChain { order, eaten });
+        return;
+    }
+
+    scoreboard.score += CHAIN_BONUS;
+    toasts.send(Toast(format!("Chain complete! +{CHAIN_BONUS}")));
+    for hud in &huds {
+        commands.entity(hud).despawn_recursive();
+    }
+    active.0 = None;
+    next.0.reset();
+}
+
+fn update_chain_hud(
+    mut commands: Commands,
+    active: Res<ActiveObjective>,
+    huds: Query<Entity, With<ChainHud>>,
+) {
+    let Some(Objective::AppleChain { order, eaten }) = &active.0 else {
+        return;
+    };
+    for hud in &huds {
+        commands
+            .entity(hud)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for (step, &color) in order.iter().enumerate() {
+                    // eaten colors fade, and the one to eat next is outlined
+                    let color = if step < *eaten {
+                        CHAIN_COLORS[color].with_a(0.25)
+                    } else {
+                        CHAIN_COLORS[color]
+                    };
+                    let outline = if step == *eaten { 2.0 } else { 0.0 };
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(HUD_ICON_SIZE),
+                            height: Val::Px(HUD_ICON_SIZE),
+                            border: UiRect::all(Val::Px(outline)),
+                            ..default()
+                        },
+                        background_color: color.into(),
+                        border_color: Color::WHITE.into(),
+                        ..default()
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,325 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    apple_spawn_at,
+    arena::GridPosition,
+    collision::ColliderKind,
+    game_modes::{start_mode_run, GameMode, ModeRun},
+    obstacle_spawn_at,
+    pool::{EntityPool, Pooled},
+    setup, snake_spawn, storage, teardown_game_screen, Apple, GameAssets, GameRng, GameState,
+    OnGameScreen, Scoreboard, Snake, SnakeBodySegment, SnakeHead, SnakeSegment, SEGMENT_SIZE,
+};
+
+const SAVE_KEY: &str = "saved_run";
+// bumped whenever `SavedRun` changes shape; saves from other versions are dropped
+const SAVE_VERSION: u32 = 1;
+pub const SAVE_AND_QUIT_KEY: KeyCode = KeyCode::KeyQ;
+pub const CONTINUE_KEY: KeyCode = KeyCode::KeyC;
+
+/// Save and resume: Q on the pause screen writes the run to a `saved_run`
+/// file next to the other saves and goes back to the main menu, where a
+/// Continue entry, C, picks it up where it was left. The snake, its
+/// heading and queued turns, the score, the apples and obstacles, the game
+/// mode and how far into it the clock was are kept. The rolls carry on from
+/// a seed drawn when saving, as the generator's own state can't be written
+/// out. A saved run is only continued once; the state the modifiers keep for
+/// themselves, like levels or quota rounds, starts over.
+pub struct PersistencePlugin;
+
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedGame::load())
+            .add_systems(Update, save_and_quit.run_if(in_state(GameState::Paused)))
+            .add_systems(
+                OnTransition {
+                    from: GameState::Paused,
+                    to: GameState::MainMenu,
+                },
+                teardown_game_screen,
+            )
+            .add_systems(
+                Update,
+                continue_saved_run.run_if(in_state(GameState::MainMenu)),
+            )
+            // only set from the main menu, so never when resuming from pause;
+            // setup has already spawned a snake by the time this runs
+            .add_systems(
+                OnEnter(GameState::Playing),
+                restore_saved_run
+                    .after(setup)
+                    .after(start_mode_run)
+                    .run_if(|saved_game: Res<SavedGame>| saved_game.resuming),
+            );
+    }
+}
+
+// read on its own first, as it parses whatever else the file holds
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+/// A run written out to pick back up later. Positions are arena
+/// coordinates.
+#[derive(Serialize, Deserialize)]
+struct SavedRun {
+    version: u32,
+    snake: Snake,
+    score: u32,
+    apples: Vec<(f32, f32)>,
+    obstacles: Vec<(f32, f32)>,
+    mode: GameMode,
+    elapsed_seconds: f32,
+    seed: u64,
+}
+
+/// The saved run, if there is one, and whether the run being started is
+/// picking it up.
+#[derive(Resource, Default)]
+pub struct SavedGame {
+    run: Option<SavedRun>,
+    resuming: bool,
+}
+
+impl SavedGame {
+    fn load() -> Self {
+        SavedGame::from_save(storage::load_raw(SAVE_KEY).as_deref())
+    }
+
+    fn from_save(contents: Option<&str>) -> Self {
+        let version = contents
+            .and_then(|contents| storage::parse_ron::<SaveVersion>(SAVE_KEY, contents))
+            .map(|save| save.version);
+        let run = match version {
+            Some(SAVE_VERSION) => {
+                contents.and_then(|contents| storage::parse_ron(SAVE_KEY, contents))
+            }
+            Some(version) => {
+                warn!("dropping a saved run from save version {version}");
+                storage::remove(SAVE_KEY);
+                None
+            }
+            None => None,
+        };
+        SavedGame {
+            run,
+            resuming: false,
+        }
+    }
+
+    pub fn can_continue(&self) -> bool {
+        self.run.is_some()
+    }
+}
+
+// walls around the arena are long sprites, obstacles fill one cell
+fn is_obstacle(kind: ColliderKind, transform: &Transform) -> bool {
+    kind == ColliderKind::Wall && transform.scale.truncate() == SEGMENT_SIZE
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_and_quit(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    snake: Res<Snake>,
+    snake_head: Query<&GridPosition, With<SnakeHead>>,
+    snake_segments: Query<&GridPosition, With<SnakeBodySegment>>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mode: Res<GameMode>,
+    mode_run: Res<ModeRun>,
+    mut rng: ResMut<GameRng>,
+    mut saved_game: ResMut<SavedGame>,
+    colliders: Query<(&Transform, &ColliderKind, Has<Apple>), With<OnGameScreen>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.just_pressed(SAVE_AND_QUIT_KEY) {
+        return;
+    }
+    let Ok(head) = snake_head.get_single() else {
+        return;
+    };
+
+    // the resource keeps the layout the run started from; the cells have
+    // moved on since, and the segments follow the head in query order
+    let segment = |cell: &GridPosition| {
+        let position = cell.translation();
+        SnakeSegment {
+            x: position.x,
+            y: position.y,
+            entity: None,
+        }
+    };
+    let position = |transform: &Transform| (transform.translation.x, transform.translation.y);
+    let run = SavedRun {
+        version: SAVE_VERSION,
+        snake: Snake {
+            head: segment(head),
+            body: snake_segments.iter().map(segment).collect(),
+            ..snake.clone()
+        },
+        score: scoreboard.score,
+        apples: colliders
+            .iter()
+            .filter(|(_, _, is_apple)| *is_apple)
+            .map(|(transform, _, _)| position(transform))
+            .collect(),
+        obstacles: colliders
+            .iter()
+            .filter(|(transform, kind, _)| is_obstacle(**kind, transform))
+            .map(|(transform, _, _)| position(transform))
+            .collect(),
+        mode: *mode,
+        elapsed_seconds: mode_run.elapsed().as_secs_f32(),
+        seed: rng.0.gen(),
+    };
+    storage::save_ron(SAVE_KEY, &run);
+    saved_game.run = Some(run);
+    // the run is over here, as far as the next one is concerned
+    scoreboard.score = 0;
+    next_state.set(GameState::MainMenu);
+}
+
+fn continue_saved_run(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut saved_game: ResMut<SavedGame>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.just_pressed(CONTINUE_KEY) {
+        return;
+    }
+    let Some(saved_mode) = saved_game.run.as_ref().map(|run| run.mode) else {
+        return;
+    };
+    *mode = saved_mode;
+    saved_game.resuming = true;
+    next_state.set(GameState::Playing);
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn restore_saved_run(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    mut saved_game: ResMut<SavedGame>,
+    mut snake: ResMut<Snake>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut rng: ResMut<GameRng>,
+    mut mode_run: ResMut<ModeRun>,
+    laid_out: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&ColliderKind>,
+            Has<SnakeHead>,
+            Has<SnakeBodySegment>,
+        ),
+        (With<OnGameScreen>, Without<Pooled>),
+    >,
+) {
+    saved_game.resuming = false;
+    let Some(run) = saved_game.run.take() else {
+        return;
+    };
+    // only continued once, like a suspended run
+    storage::remove(SAVE_KEY);
+
+    // what setup laid out for a new run makes way for the saved one
+    for (entity, transform, kind, is_head, is_segment) in &laid_out {
+        let replaced =
+            kind.is_some_and(|&kind| kind == ColliderKind::Apple || is_obstacle(kind, transform));
+        if is_segment {
+            segment_pool.release(&mut commands, entity);
+        } else if is_head || replaced {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    // only the layout, so the move timer keeps the speed it was given
+    snake.head = run.snake.head;
+    snake.body = run.snake.body;
+    snake.direction = run.snake.direction;
+    snake.turn_queue = run.snake.turn_queue;
+    snake_spawn(&mut commands, &game_assets, &mut segment_pool, &mut snake);
+    for (x, y) in run.apples {
+        apple_spawn_at(&mut commands, &game_assets, Vec3::new(x, y, -2.0));
+    }
+    for (x, y) in run.obstacles {
+        obstacle_spawn_at(&mut commands, Vec2::new(x, y));
+    }
+
+    scoreboard.score = run.score;
+    *rng = GameRng::seeded(run.seed);
+    mode_run.resume_at(Duration::from_secs_f32(run.elapsed_seconds), run.score);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        keyboard::{Key, KeyboardInput, NativeKey},
+        ButtonState,
+    };
+
+    use super::*;
+    use crate::headless::{greedy_command, GameStateSnapshot, HeadlessPlugin};
+
+    fn press(app: &mut App, key_code: KeyCode) {
+        app.world.send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state: ButtonState::Pressed,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+
+    #[test]
+    fn continued_run_picks_up_the_moved_and_grown_snake() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HeadlessPlugin { seed: 7 }));
+        app.update();
+        while app.world.resource::<GameStateSnapshot>().score < 3 {
+            let snapshot = app.world.resource::<GameStateSnapshot>();
+            if let Some(command) = greedy_command(snapshot) {
+                app.world.send_event(command);
+            }
+            app.update();
+        }
+        let saved = app.world.resource::<GameStateSnapshot>().clone();
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Paused);
+        app.update();
+        press(&mut app, SAVE_AND_QUIT_KEY);
+        app.update();
+        assert!(app.world.resource::<SavedGame>().can_continue());
+        assert_eq!(
+            *app.world.resource::<State<GameState>>(),
+            GameState::MainMenu
+        );
+        press(&mut app, CONTINUE_KEY);
+        app.update();
+
+        // and has taken its next step since
+        let resumed = app.world.resource::<GameStateSnapshot>();
+        assert_eq!(resumed.score, saved.score);
+        assert_eq!(resumed.snake.len(), saved.snake.len());
+        assert_eq!(resumed.snake[1..], saved.snake[..saved.snake.len() - 1]);
+    }
+
+    #[test]
+    fn missing_or_corrupt_save_offers_no_continue() {
+        assert!(!SavedGame::from_save(None).can_continue());
+        assert!(!SavedGame::from_save(Some("")).can_continue());
+        assert!(!SavedGame::from_save(Some("not a saved run")).can_continue());
+        // the version reads fine, the rest doesn't
+        let corrupt = format!("(version: {SAVE_VERSION}, score: \"seven\")");
+        assert!(!SavedGame::from_save(Some(&corrupt)).can_continue());
+    }
+}
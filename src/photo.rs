@@ -0,0 +1,329 @@
+use std::path::PathBuf;
+
+use bevy::{
+    input::mouse::MouseWheel, prelude::*, render::view::screenshot::ScreenshotManager,
+    utils::HashMap, window::PrimaryWindow,
+};
+
+use crate::{capture::capture_path, toast::Toast, GameState};
+
+const PAN_SPEED: f32 = 400.0;
+const ZOOM_SPEED: f32 = 1.5;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 4.0;
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhotoSession>()
+            .add_systems(Update, enter_photo_mode.run_if(in_state(GameState::Paused)))
+            .add_systems(OnEnter(GameState::PhotoMode), start_photo_session)
+            .add_systems(OnExit(GameState::PhotoMode), end_photo_session)
+            .add_systems(
+                Update,
+                (
+                    pan_zoom_camera,
+                    cycle_filter,
+                    toggle_frame,
+                    save_photo,
+                    exit_photo_mode,
+                )
+                    .run_if(in_state(GameState::PhotoMode)),
+            );
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+enum PhotoFilter {
+    #[default]
+    None,
+    Grayscale,
+    Sepia,
+    Inverted,
+}
+
+impl PhotoFilter {
+    fn next(self) -> Self {
+        match self {
+            PhotoFilter::None => PhotoFilter::Grayscale,
+            PhotoFilter::Grayscale => PhotoFilter::Sepia,
+            PhotoFilter::Sepia => PhotoFilter::Inverted,
+            PhotoFilter::Inverted => PhotoFilter::None,
+        }
+    }
+
+    fn apply(self, color: Color) -> Color {
+        let [r, g, b, a] = color.as_rgba_f32();
+        match self {
+            PhotoFilter::None => color,
+            PhotoFilter::Grayscale => {
+                let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+                Color::rgba(luma, luma, luma, a)
+            }
+            PhotoFilter::Sepia => Color::rgba(
+                (0.393 * r + 0.769 * g + 0.189 * b).min(1.0),
+                (0.349 * r + 0.686 * g + 0.168 * b).min(1.0),
+                (0.272 * r + 0.534 * g + 0.131 * b).min(1.0),
+                a,
+            ),
+            PhotoFilter::Inverted => Color::rgba(1.0 - r, 1.0 - g, 1.0 - b, a),
+        }
+    }
+}
+
+/// Everything photo mode changed, so leaving it puts the paused game back as it was.
+#[derive(Resource, Default)]
+struct PhotoSession {
+    camera: Option<(Transform, f32)>,
+    hidden_ui: Vec<(Entity, Visibility)>,
+    filter: PhotoFilter,
+    material_colors: HashMap<AssetId<ColorMaterial>, Color>,
+    sprite_colors: HashMap<Entity, Color>,
+}
+
+#[derive(Component)]
+struct PhotoHint;
+
+#[derive(Component)]
+struct PhotoFrame;
+
+fn enter_photo_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        next_state.set(GameState::PhotoMode);
+    }
+}
+
+fn exit_photo_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Paused);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn start_photo_session(
+    mut commands: Commands,
+    mut session: ResMut<PhotoSession>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    mut ui_roots: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>)>,
+) {
+    *session = PhotoSession {
+        camera: cameras
+            .get_single()
+            .ok()
+            .map(|(transform, projection)| (*transform, projection.scale)),
+        ..default()
+    };
+
+    for (entity, mut visibility) in &mut ui_roots {
+        if *visibility != Visibility::Hidden {
+            session.hidden_ui.push((entity, *visibility));
+            *visibility = Visibility::Hidden;
+        }
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "Photo mode - arrows: pan, wheel or +/-: zoom, F: filter, B: frame, Enter: save, Esc: back",
+            TextStyle {
+                font_size: 16.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        PhotoHint,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn end_photo_session(
+    mut commands: Commands,
+    mut session: ResMut<PhotoSession>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mut visibilities: Query<&mut Visibility>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprites: Query<&mut Sprite>,
+    photo_ui: Query<Entity, Or<(With<PhotoHint>, With<PhotoFrame>)>>,
+) {
+    if let (Some((transform, scale)), Ok((mut camera_transform, mut projection))) =
+        (session.camera, cameras.get_single_mut())
+    {
+        *camera_transform = transform;
+        projection.scale = scale;
+    }
+
+    for (entity, visibility) in session.hidden_ui.drain(..) {
+        if let Ok(mut current) = visibilities.get_mut(entity) {
+            *current = visibility;
+        }
+    }
+
+    for (id, color) in session.material_colors.drain() {
+        if let Some(material) = materials.get_mut(id) {
+            material.color = color;
+        }
+    }
+    for (entity, color) in session.sprite_colors.drain() {
+        if let Ok(mut sprite) = sprites.get_mut(entity) {
+            sprite.color = color;
+        }
+    }
+
+    for entity in &photo_ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn pan_zoom_camera(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+
+    let mut pan = Vec2::ZERO;
+    if keyboard_input.pressed(KeyCode::ArrowLeft) {
+        pan.x -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowRight) {
+        pan.x += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowDown) {
+        pan.y -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowUp) {
+        pan.y += 1.0;
+    }
+    transform.translation +=
+        (pan * PAN_SPEED * projection.scale * time.delta_seconds()).extend(0.0);
+
+    let mut zoom = -wheel_events.read().map(|event| event.y).sum::<f32>() * 0.1;
+    if keyboard_input.pressed(KeyCode::Equal) {
+        zoom -= ZOOM_SPEED * time.delta_seconds();
+    }
+    if keyboard_input.pressed(KeyCode::Minus) {
+        zoom += ZOOM_SPEED * time.delta_seconds();
+    }
+    if zoom != 0.0 {
+        projection.scale = (projection.scale * (1.0 + zoom)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+fn cycle_filter(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<PhotoSession>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprites: Query<(Entity, &mut Sprite)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+
+    let session = &mut *session;
+    session.filter = session.filter.next();
+
+    for (id, material) in materials.iter_mut() {
+        let original = *session.material_colors.entry(id).or_insert(material.color);
+        material.color = session.filter.apply(original);
+    }
+    for (entity, mut sprite) in &mut sprites {
+        let original = *session.sprite_colors.entry(entity).or_insert(sprite.color);
+        sprite.color = session.filter.apply(original);
+    }
+}
+
+fn toggle_frame(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    frames: Query<Entity, With<PhotoFrame>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyB) {
+        return;
+    }
+
+    if !frames.is_empty() {
+        for entity in &frames {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    border: UiRect::all(Val::Px(16.0)),
+                    justify_content: JustifyContent::FlexEnd,
+                    align_items: AlignItems::FlexEnd,
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                border_color: Color::WHITE.into(),
+                ..default()
+            },
+            PhotoFrame,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "snake-bevy",
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn save_photo(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut hints: Query<&mut Visibility, With<PhotoHint>>,
+    mut toasts: EventWriter<Toast>,
+    mut saved_to: Local<Option<PathBuf>>,
+) {
+    if let Some(path) = saved_to.take() {
+        for mut visibility in &mut hints {
+            *visibility = Visibility::Inherited;
+        }
+        toasts.send(Toast(format!("Photo saved to {}", path.display())));
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let Ok(window) = main_window.get_single() else {
+        return;
+    };
+
+    let path = capture_path("photos", "photo", "png");
+    match screenshot_manager.save_screenshot_to_disk(window, &path) {
+        Ok(()) => {
+            // hidden for the captured frame only
+            for mut visibility in &mut hints {
+                *visibility = Visibility::Hidden;
+            }
+            *saved_to = Some(path);
+        }
+        Err(err) => warn!("photo capture failed: {err}"),
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude as rapier;
+
+use crate::{
+    collider_half_size,
+    collision::{ColliderKind, CollisionEvent},
+    move_snake,
+    rules::GameRules,
+    settings::GameSettings,
+    Apple, Collider, SnakeBodySegment, SnakeHead,
+};
+
+/// Detects collisions with rapier sensors instead of the collision grid, then
+/// feeds them into the same `CollisionEvent` pipeline.
+pub struct PhysicsModePlugin;
+
+impl Plugin for PhysicsModePlugin {
+    fn build(&self, app: &mut App) {
+        // stepped right after movement so sensor events land in the same tick
+        app.add_plugins(
+            rapier::RapierPhysicsPlugin::<rapier::NoUserData>::default().in_schedule(Update),
+        )
+        .configure_sets(
+            Update,
+            (
+                rapier::PhysicsSet::SyncBackend,
+                rapier::PhysicsSet::StepSimulation,
+                rapier::PhysicsSet::Writeback,
+            )
+                .after(move_snake)
+                .before(forward_rapier_collisions),
+        )
+        .add_systems(
+            Update,
+            attach_sensors.before(rapier::PhysicsSet::SyncBackend),
+        );
+    }
+}
+
+fn attach_sensors(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    added: Query<
+        (Entity, &Transform, Has<Apple>, Has<SnakeHead>),
+        Or<(Added<Collider>, Added<SnakeBodySegment>)>,
+    >,
+) {
+    for (entity, transform, is_apple, is_head) in &added {
+        let mut entity = commands.entity(entity);
+
+        if is_head {
+            let half_size = settings.hitboxes.head() * rules.head_hitbox_scale / 2.0;
+            entity.insert((
+                rapier::RigidBody::KinematicPositionBased,
+                rapier::Collider::cuboid(half_size.x, half_size.y),
+                rapier::ActiveEvents::COLLISION_EVENTS,
+                rapier::ActiveCollisionTypes::default()
+                    | rapier::ActiveCollisionTypes::KINEMATIC_STATIC,
+            ));
+        } else {
+            // rapier scales colliders by the transform, and walls are scaled unit sprites
+            let half_size = collider_half_size(transform, is_apple, &settings.hitboxes)
+                / transform.scale.truncate();
+            entity.insert(rapier::Collider::cuboid(half_size.x, half_size.y));
+        }
+        entity.insert(rapier::Sensor);
+    }
+}
+
+pub fn forward_rapier_collisions(
+    mut rapier_events: EventReader<rapier::CollisionEvent>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    heads: Query<(), With<SnakeHead>>,
+    colliders: Query<&ColliderKind, Without<SnakeHead>>,
+) {
+    for event in rapier_events.read() {
+        let rapier::CollisionEvent::Started(first, second, _) = *event else {
+            continue;
+        };
+        let collider = if heads.contains(first) {
+            second
+        } else if heads.contains(second) {
+            first
+        } else {
+            continue;
+        };
+
+        // body segments carry sensors too, but nothing reacts to touching them yet
+        let Ok(&kind) = colliders.get(collider) else {
+            continue;
+        };
+        collision_events.send(CollisionEvent { collider, kind });
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    collision::ColliderKind, settings::GameSettings, Collider, GameState, OnGameScreen,
+    SnakeBodySegment, SEGMENT_SIZE,
+};
+
+const POISON_COLOR: Color = Color::rgb(0.6, 0.1, 0.8);
+
+/// Poison trail modifier: the tail leaves a lethal cell behind every few
+/// seconds, which wears off after a while.
+pub struct PoisonTrailPlugin;
+
+impl Plugin for PoisonTrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PoisonDropTimer>()
+            .register_type::<Poison>()
+            .add_systems(OnExit(GameState::GameOver), reset_drop_timer)
+            .add_systems(
+                Update,
+                (
+                    drop_poison
+                        .run_if(|settings: Res<GameSettings>| settings.modifiers.poison_trail),
+                    age_poison,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Poison(Timer);
+
+#[derive(Resource)]
+struct PoisonDropTimer(Timer);
+
+impl Default for PoisonDropTimer {
+    fn default() -> Self {
+        PoisonDropTimer(Timer::from_seconds(5.0, TimerMode::Repeating))
+    }
+}
+
+fn reset_drop_timer(mut drop_timer: ResMut<PoisonDropTimer>) {
+    drop_timer.0.reset();
+}
+
+fn drop_poison(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut drop_timer: ResMut<PoisonDropTimer>,
+    segments: Query<&Transform, With<SnakeBodySegment>>,
+) {
+    let modifiers = &settings.modifiers;
+    let interval = Duration::from_secs_f32(modifiers.poison_drop_seconds);
+    if drop_timer.0.duration() != interval {
+        drop_timer.0.set_duration(interval);
+    }
+    if !drop_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // `move_snake` walks the body in query order, so the last segment is the tail
+    let Some(tail) = segments.iter().last() else {
+        return;
+    };
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform {
+                translation: tail.translation.truncate().extend(-1.0),
+                scale: (SEGMENT_SIZE * 0.8).extend(1.0),
+                ..default()
+            },
+            sprite: Sprite {
+                color: POISON_COLOR,
+                ..default()
+            },
+            ..default()
+        },
+        Poison(Timer::from_seconds(
+            modifiers.poison_lifetime_seconds,
+            TimerMode::Once,
+        )),
+        Collider,
+        ColliderKind::Poison,
+        OnGameScreen,
+    ));
+}
+
+fn age_poison(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut poisons: Query<(Entity, &mut Poison, &mut Sprite)>,
+) {
+    for (entity, mut poison, mut sprite) in &mut poisons {
+        if poison.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // fades as it wears off, but stays visible until it's gone
+        sprite.color.set_a(1.0 - 0.7 * poison.0.fraction());
+    }
+}
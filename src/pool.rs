@@ -0,0 +1,55 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+// a very long run shouldn't keep thousands of hidden entities alive forever
+const MAX_PARKED: usize = 4096;
+
+/// Entities parked by an [`EntityPool`], hidden and stripped of their kind marker.
+#[derive(Component)]
+pub struct Pooled;
+
+/// Free list of parked entities for a kind of frequently spawned thing
+/// (body segments, score popups, particles), keyed by its marker component `T`.
+#[derive(Resource)]
+pub struct EntityPool<T: Component> {
+    parked: Vec<Entity>,
+    _kind: PhantomData<T>,
+}
+
+impl<T: Component> Default for EntityPool<T> {
+    fn default() -> Self {
+        EntityPool {
+            parked: Vec::new(),
+            _kind: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> EntityPool<T> {
+    /// Reuses a parked entity when there is one, otherwise spawns a new one.
+    /// `bundle` must contain `T` and a visible `Visibility` so a reused entity
+    /// shows up in queries and on screen again.
+    pub fn acquire(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        match self.parked.pop() {
+            Some(entity) => {
+                commands.entity(entity).remove::<Pooled>().insert(bundle);
+                entity
+            }
+            None => commands.spawn(bundle).id(),
+        }
+    }
+
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if self.parked.len() >= MAX_PARKED {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<T>()
+            .insert((Pooled, Visibility::Hidden));
+        self.parked.push(entity);
+    }
+}
@@ -0,0 +1,365 @@
+use std::time::Duration;
+
+use bevy::{
+    math::bounding::{Aabb2d, IntersectsVolume},
+    prelude::*,
+};
+use rand::{distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom};
+
+use crate::{
+    arena::{occupied_boxes, GridPosition, WallThickness},
+    collision::{ColliderKind, DetectCollisions},
+    move_snake, new_run,
+    pool::EntityPool,
+    settings::GameSettings,
+    sim,
+    toast::Toast,
+    versus::{mirrored_pairs, Versus},
+    visual_cues::Cue,
+    GameRng, GameState, OnGameScreen, SafeArea, Scoreboard, Snake, SnakeBodySegment, SnakeHead,
+};
+
+const SPAWN_INTERVAL_SECONDS: f32 = 12.0;
+// how long an item waits to be taken, and how long a speed effect lasts
+const ITEM_LIFETIME_SECONDS: f32 = 8.0;
+const EFFECT_SECONDS: f32 = 6.0;
+const GOLDEN_POINTS: u32 = 5;
+const SHRINK_SEGMENTS: usize = 2;
+const SPEED_BOOST: f32 = 1.5;
+const SLOW_DOWN: f32 = 0.6;
+// golden apples turn up most, shrinks least
+const WEIGHTS: [(FoodKind, u32); 4] = [
+    (FoodKind::Golden, 4),
+    (FoodKind::SpeedBoost, 3),
+    (FoodKind::SlowDown, 3),
+    (FoodKind::Shrink, 2),
+];
+
+/// Power-ups, a modifier from the settings file: now and then an item lands
+/// next to the apples, picked at random by weight, and vanishes if it isn't
+/// taken in time. A golden apple is worth 5, a speed boost or slow-down
+/// changes the snake's pace for a few seconds, shown in the HUD, and a
+/// shrink takes two segments off the tail. In a versus round the second
+/// snake takes them too, for its own score, pace and tail, and items land in
+/// pairs mirrored across the center.
+pub struct PowerUpsPlugin;
+
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUpSpawner>()
+            .init_resource::<ActiveEffect>()
+            .init_resource::<EffectSpeed>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_power_ups, spawn_effect_text).run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_power_ups
+                        .run_if(|settings: Res<GameSettings>| settings.modifiers.power_ups),
+                    collect_power_ups.after(move_snake).before(DetectCollisions),
+                    expire_power_ups,
+                    (tick_effect, update_effect_text).chain(),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// The kinds of power-up item.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoodKind {
+    Golden,
+    SpeedBoost,
+    SlowDown,
+    Shrink,
+}
+
+impl FoodKind {
+    fn label(self) -> &'static str {
+        match self {
+            FoodKind::Golden => "Golden apple",
+            FoodKind::SpeedBoost => "Speed boost",
+            FoodKind::SlowDown => "Slow-down",
+            FoodKind::Shrink => "Shrink",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            FoodKind::Golden => Color::rgb(1.0, 0.84, 0.0),
+            FoodKind::SpeedBoost => Color::rgb(0.3, 0.7, 1.0),
+            FoodKind::SlowDown => Color::rgb(0.6, 0.4, 1.0),
+            FoodKind::Shrink => Color::rgb(1.0, 0.45, 0.8),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct PowerUpSpawner(Timer);
+
+impl Default for PowerUpSpawner {
+    fn default() -> Self {
+        PowerUpSpawner(Timer::from_seconds(
+            SPAWN_INTERVAL_SECONDS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+/// The speed effect a power-up left running and the time it has left. The
+/// second player's, like its `EffectSpeed`, rides on its snake's head.
+#[derive(Resource, Component, Default, Debug)]
+pub struct ActiveEffect(Option<(FoodKind, Timer)>);
+
+/// How many times faster the active effect makes the snake. Kept apart from
+/// `ActiveEffect`, which changes every frame, so the speed is only
+/// recomputed when an effect starts or ends.
+#[derive(Resource, Component, Debug, PartialEq)]
+pub struct EffectSpeed(pub f32);
+
+impl Default for EffectSpeed {
+    fn default() -> Self {
+        EffectSpeed(1.0)
+    }
+}
+
+#[derive(Component)]
+struct PowerUpItem(Timer);
+
+#[derive(Component)]
+struct EffectText;
+
+fn reset_power_ups(
+    mut spawner: ResMut<PowerUpSpawner>,
+    mut effect: ResMut<ActiveEffect>,
+    mut speed: ResMut<EffectSpeed>,
+) {
+    *spawner = PowerUpSpawner::default();
+    *effect = ActiveEffect::default();
+    speed.set_if_neq(EffectSpeed::default());
+}
+
+fn spawn_effect_text(mut commands: Commands, safe_area: Res<SafeArea>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            // above the stamina bar
+            bottom: Val::Px(24.0 + safe_area.bottom),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        EffectText,
+        OnGameScreen,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn spawn_power_ups(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    mut rng: ResMut<GameRng>,
+    mut spawner: ResMut<PowerUpSpawner>,
+    items: Query<(), With<PowerUpItem>>,
+    snake: Query<&GridPosition, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    colliders: Query<(&Transform, &ColliderKind)>,
+    versus: Res<Versus>,
+    mut cues: EventWriter<Cue>,
+) {
+    if !spawner.0.tick(time.delta()).just_finished() || !items.is_empty() {
+        return;
+    }
+    let Ok(weights) = WeightedIndex::new(WEIGHTS.iter().map(|(_, weight)| weight)) else {
+        return;
+    };
+    let kind = WEIGHTS[weights.sample(&mut rng.0)].0;
+    // only one drop is out at a time, so the snake and colliders are all it
+    // has to keep off
+    let occupied = occupied_boxes(&snake, &colliders, &settings.hitboxes);
+    let free = sim::free_cells(&occupied, wall_thickness.0);
+    // a versus round gets one on each side of the center
+    let cells = if versus.is_active() {
+        mirrored_pairs(&free)
+            .choose(&mut rng.0)
+            .map(|&cell| vec![cell, -cell])
+    } else {
+        free.choose(&mut rng.0)
+            .map(|&position| vec![GridPosition::containing(position).0])
+    };
+
+    for cell in cells.into_iter().flatten() {
+        let position = GridPosition(cell).translation();
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(position.extend(-2.0)),
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(settings.hitboxes.apple_half_size() * 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            kind,
+            PowerUpItem(Timer::from_seconds(ITEM_LIFETIME_SECONDS, TimerMode::Once)),
+            OnGameScreen,
+        ));
+        cues.send(Cue::BonusSpawned(position));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn collect_power_ups(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut effect: ResMut<ActiveEffect>,
+    mut speed: ResMut<EffectSpeed>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    mut toasts: EventWriter<Toast>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    mut player_two: Query<(
+        &Transform,
+        &mut Snake,
+        &mut Scoreboard,
+        &mut ActiveEffect,
+        &mut EffectSpeed,
+    )>,
+    items: Query<(Entity, &Transform, &FoodKind), With<PowerUpItem>>,
+    segments: Query<Entity, With<SnakeBodySegment>>,
+) {
+    let touches = |head: &Transform, item: &Transform| {
+        let head = Aabb2d::new(head.translation.truncate(), settings.hitboxes.head() / 2.0);
+        let item = Aabb2d::new(
+            item.translation.truncate(),
+            settings.hitboxes.apple_half_size(),
+        );
+        head.intersects(&item)
+    };
+    for (entity, transform, &kind) in &items {
+        // the first player takes an item both heads reach at once
+        if heads.iter().any(|head| touches(head, transform)) {
+            commands.entity(entity).despawn_recursive();
+            match kind {
+                FoodKind::Golden => {
+                    scoreboard.score += GOLDEN_POINTS;
+                    toasts.send(Toast(format!("Golden apple! +{GOLDEN_POINTS}")));
+                }
+                FoodKind::SpeedBoost | FoodKind::SlowDown => {
+                    start_effect(kind, &mut effect, speed.reborrow());
+                    toasts.send(Toast(format!("{}!", kind.label())));
+                }
+                FoodKind::Shrink => {
+                    // segments follow the head in query order, so the tail comes last;
+                    // one segment always stays
+                    let tail: Vec<Entity> = segments.iter().collect();
+                    let removed = SHRINK_SEGMENTS.min(tail.len().saturating_sub(1));
+                    for &segment in tail.iter().rev().take(removed) {
+                        segment_pool.release(&mut commands, segment);
+                    }
+                    toasts.send(Toast("Shrink!".to_string()));
+                }
+            }
+            continue;
+        }
+
+        let Some((_, mut snake, mut scoreboard, mut effect, speed)) = player_two
+            .iter_mut()
+            .find(|(head, ..)| touches(head, transform))
+        else {
+            continue;
+        };
+        commands.entity(entity).despawn_recursive();
+        match kind {
+            FoodKind::Golden => scoreboard.score += GOLDEN_POINTS,
+            FoodKind::SpeedBoost | FoodKind::SlowDown => start_effect(kind, &mut effect, speed),
+            FoodKind::Shrink => {
+                // the second snake's segments aren't pooled
+                let removed = SHRINK_SEGMENTS.min(snake.body.len().saturating_sub(1));
+                for _ in 0..removed {
+                    if let Some(entity) = snake.body.pop_back().and_then(|segment| segment.entity) {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+        }
+        toasts.send(Toast(format!("Player 2: {}!", kind.label())));
+    }
+}
+
+// a new effect replaces whichever was running
+fn start_effect(kind: FoodKind, effect: &mut ActiveEffect, mut speed: Mut<EffectSpeed>) {
+    effect.0 = Some((kind, Timer::from_seconds(EFFECT_SECONDS, TimerMode::Once)));
+    let multiplier = if kind == FoodKind::SpeedBoost {
+        SPEED_BOOST
+    } else {
+        SLOW_DOWN
+    };
+    speed.set_if_neq(EffectSpeed(multiplier));
+}
+
+fn expire_power_ups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut items: Query<(Entity, &mut PowerUpItem)>,
+) {
+    for (entity, mut item) in &mut items {
+        if item.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn tick_effect(
+    time: Res<Time>,
+    mut effect: ResMut<ActiveEffect>,
+    mut speed: ResMut<EffectSpeed>,
+    mut player_two: Query<(&mut ActiveEffect, &mut EffectSpeed)>,
+) {
+    effect.tick(time.delta(), speed.reborrow());
+    for (mut effect, speed) in &mut player_two {
+        effect.tick(time.delta(), speed);
+    }
+}
+
+impl ActiveEffect {
+    fn tick(&mut self, delta: Duration, mut speed: Mut<EffectSpeed>) {
+        let Some((_, timer)) = &mut self.0 else {
+            return;
+        };
+        if timer.tick(delta).finished() {
+            self.0 = None;
+            speed.set_if_neq(EffectSpeed::default());
+        }
+    }
+}
+
+fn update_effect_text(
+    effect: Res<ActiveEffect>,
+    mut texts: Query<(&mut Text, &mut Visibility), With<EffectText>>,
+) {
+    if !effect.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in &mut texts {
+        let Some((kind, timer)) = &effect.0 else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        text.sections[0].value = format!("{} {:.1}s", kind.label(), timer.remaining_secs());
+        text.sections[0].style.color = kind.color();
+    }
+}
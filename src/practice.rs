@@ -0,0 +1,277 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    apple_spawn_at, arena::GridPosition, display_final_score, menu::MenuItem, rules::GameRules,
+    sandbox::SandboxMode, GameAssets, GameState, OnGameOverScreen, OnGameScreen, RunEnd, SafeArea,
+    Snake, SnakeBodySegment, SnakeHead, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, SEGMENT_SIZE, TOP_WALL,
+};
+
+const SPEED_STEP: f32 = 1.25;
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+/// Practice runs, started with T from the game-over screen. V toggles
+/// invincibility, clicking places an apple, +/- change the speed, R puts the
+/// snake back at the start and Escape ends the run. Nothing from a practice
+/// run counts towards bests, leaderboards or progression. The sandbox is a
+/// practice run with its own tools in place of click-to-place apples.
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PracticeMode>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_practice_hint.after(display_final_score),
+            )
+            .add_systems(
+                Update,
+                request_practice.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnExit(GameState::GameOver), start_or_end_practice)
+            .add_systems(
+                Update,
+                (
+                    toggle_invincibility,
+                    place_apple_on_click.run_if(|sandbox: Res<SandboxMode>| !sandbox.is_active()),
+                    adjust_speed,
+                    reset_position,
+                    end_practice_run,
+                    update_practice_banner,
+                )
+                    .run_if(in_state(GameState::Playing).and_then(practicing)),
+            )
+            // after every system that could have ended the run this frame
+            .add_systems(
+                PostUpdate,
+                cancel_deaths.run_if(in_state(GameState::Playing).and_then(practicing)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct PracticeMode {
+    active: bool,
+    requested: bool,
+    invincible: bool,
+    speed: f32,
+}
+
+impl Default for PracticeMode {
+    fn default() -> Self {
+        PracticeMode {
+            active: false,
+            requested: false,
+            invincible: false,
+            speed: 1.0,
+        }
+    }
+}
+
+impl PracticeMode {
+    /// Whether the current (or just finished) run is a practice run.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Makes the run started from the game-over screen a practice run.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+}
+
+#[derive(Component)]
+struct PracticeBanner;
+
+pub fn practicing(practice: Res<PracticeMode>) -> bool {
+    practice.active
+}
+
+fn display_practice_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Press T to practice",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(95.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyT),
+        OnGameOverScreen,
+    ));
+}
+
+fn request_practice(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut practice: ResMut<PracticeMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyT) {
+        practice.request();
+        next_state.set(GameState::Playing);
+    }
+}
+
+// any other way out of the game-over screen leaves practice behind
+fn start_or_end_practice(mut practice: ResMut<PracticeMode>, mut rules: ResMut<GameRules>) {
+    let requested = practice.requested;
+    *practice = PracticeMode {
+        active: requested,
+        ..default()
+    };
+    if requested {
+        *rules = GameRules::classic();
+    }
+}
+
+fn toggle_invincibility(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut practice: ResMut<PracticeMode>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyV) {
+        practice.invincible = !practice.invincible;
+    }
+}
+
+/// The grid cell under the mouse, kept inside the walls.
+pub fn cursor_cell(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position)?;
+    let position = cameras
+        .iter()
+        .find_map(|(camera, transform)| camera.viewport_to_world_2d(transform, cursor))?;
+
+    // snapped to the grid the snake moves on, and kept inside the walls
+    let half = SEGMENT_SIZE / 2.0;
+    Some(((position / SEGMENT_SIZE).round() * SEGMENT_SIZE).clamp(
+        Vec2::new(LEFT_WALL, BOTTOM_WALL) + half,
+        Vec2::new(RIGHT_WALL, TOP_WALL) - half,
+    ))
+}
+
+fn place_apple_on_click(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    game_assets: Res<GameAssets>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(position) = cursor_cell(&windows, &cameras) else {
+        return;
+    };
+    apple_spawn_at(&mut commands, &game_assets, position.extend(-2.0));
+}
+
+fn adjust_speed(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut practice: ResMut<PracticeMode>,
+    mut rules: ResMut<GameRules>,
+) {
+    let speed = if keyboard_input.just_pressed(KeyCode::Equal) {
+        practice.speed * SPEED_STEP
+    } else if keyboard_input.just_pressed(KeyCode::Minus) {
+        practice.speed / SPEED_STEP
+    } else {
+        return;
+    };
+    practice.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    rules.speed = GameRules::classic().speed.faster(practice.speed);
+}
+
+fn reset_position(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    snake: Res<Snake>,
+    mut snake_heads: Query<&mut GridPosition, With<SnakeHead>>,
+    mut segments: Query<&mut GridPosition, (With<SnakeBodySegment>, Without<SnakeHead>)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyR) {
+        return;
+    }
+    // the body gathers under the head and trails out again as it moves
+    let start = GridPosition::containing(Vec2::new(snake.head.x, snake.head.y));
+    for mut cell in snake_heads.iter_mut().chain(segments.iter_mut()) {
+        *cell = start;
+    }
+}
+
+fn end_practice_run(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut practice: ResMut<PracticeMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        // Escape ends the run even while invincible
+        practice.invincible = false;
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn update_practice_banner(
+    mut commands: Commands,
+    practice: Res<PracticeMode>,
+    sandbox: Res<SandboxMode>,
+    safe_area: Res<SafeArea>,
+    mut banners: Query<&mut Text, With<PracticeBanner>>,
+) {
+    let message = format!(
+        "Practice: V invincible ({}), {}+/- speed ({:.2}x), R reset, Esc end",
+        if practice.invincible { "on" } else { "off" },
+        if sandbox.is_active() {
+            ""
+        } else {
+            "click to place apples, "
+        },
+        practice.speed
+    );
+
+    if let Ok(mut text) = banners.get_single_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message;
+        }
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            message,
+            TextStyle {
+                font_size: 16.0,
+                color: Color::rgb(1.0, 0.8, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0 + safe_area.bottom),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        PracticeBanner,
+        OnGameScreen,
+    ));
+}
+
+fn cancel_deaths(
+    practice: Res<PracticeMode>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if practice.invincible && next_state.0 == Some(GameState::GameOver) {
+        next_state.0 = None;
+        *run_end = RunEnd::default();
+    }
+}
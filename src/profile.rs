@@ -0,0 +1,400 @@
+use bevy::{prelude::*, window::ReceivedCharacter};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display_final_score, menu::MenuItem, progression::Progression, GameState, OnGameOverScreen,
+    PauseOverlay,
+};
+
+const MAX_NAME_LEN: usize = 16;
+const EDITOR_PIXEL: f32 = 12.0;
+const BADGE_PIXEL: f32 = 4.0;
+const COLORS: [Color; 6] = [
+    Color::GREEN,
+    Color::rgb(0.2, 0.7, 1.0),
+    Color::rgb(1.0, 0.6, 0.1),
+    Color::rgb(0.6, 0.3, 1.0),
+    Color::rgb(1.0, 0.45, 0.8),
+    Color::rgb(0.9, 0.9, 0.9),
+];
+const FIELDS: [&str; 5] = ["Name", "Color", "Eyes", "Mouth", "Hat"];
+
+/// The player's display name and snake-head avatar, edited on a screen
+/// opened with U from the game-over screen and saved with the progression.
+/// Shown on the pause screen and next to local high scores.
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProfileEditor>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_profile_hint.after(display_final_score),
+            )
+            .add_systems(Update, open_profile.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnEnter(GameState::Paused), display_pause_badge)
+            .add_systems(OnEnter(GameState::Profile), spawn_profile_editor)
+            .add_systems(
+                OnExit(GameState::Profile),
+                (despawn_profile_editor, save_profile),
+            )
+            .add_systems(
+                Update,
+                (
+                    edit_profile,
+                    (despawn_profile_editor, spawn_profile_editor)
+                        .chain()
+                        .run_if(resource_changed::<ProfileEditor>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Profile)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Eyes {
+    #[default]
+    Dots,
+    Wide,
+    Sleepy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mouth {
+    #[default]
+    Smile,
+    Fangs,
+    Tongue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Hat {
+    #[default]
+    Bare,
+    Crown,
+    Cap,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PlayerProfile {
+    pub name: String,
+    // index into the avatar colors
+    pub color: usize,
+    pub eyes: Eyes,
+    pub mouth: Mouth,
+    pub hat: Hat,
+}
+
+impl Default for PlayerProfile {
+    fn default() -> Self {
+        PlayerProfile {
+            name: "Player".to_string(),
+            color: 0,
+            eyes: Eyes::default(),
+            mouth: Mouth::default(),
+            hat: Hat::default(),
+        }
+    }
+}
+
+impl PlayerProfile {
+    /// The avatar's pixels, two rows of hat above eight of head, with
+    /// `Color::NONE` where nothing is drawn.
+    fn avatar_rows(&self) -> Vec<Vec<Color>> {
+        let hat: [&str; 2] = match self.hat {
+            Hat::Bare => ["        ", "        "],
+            Hat::Crown => ["y  yy  y", "yyyyyyyy"],
+            Hat::Cap => ["  bbbb  ", " bbbbbbb"],
+        };
+        let eyes: [&str; 3] = match self.eyes {
+            Eyes::Dots => ["        ", " k    k ", "        "],
+            Eyes::Wide => ["        ", " wk  kw ", " ww  ww "],
+            Eyes::Sleepy => ["        ", "        ", " kk  kk "],
+        };
+        let mouth: [&str; 3] = match self.mouth {
+            Mouth::Smile => [" k    k ", "  kkkk  ", "        "],
+            Mouth::Fangs => ["  kkkk  ", "  w  w  ", "        "],
+            Mouth::Tongue => ["  kkkk  ", "   rr   ", "   rr   "],
+        };
+        let head = COLORS[self.color % COLORS.len()];
+
+        hat.iter()
+            .map(|row| (row, Color::NONE))
+            .chain(eyes.iter().map(|row| (row, head)))
+            .chain(std::iter::once((&"        ", head)))
+            .chain(mouth.iter().map(|row| (row, head)))
+            .chain(std::iter::once((&"        ", head)))
+            .map(|(row, background)| {
+                row.chars()
+                    .map(|pixel| match pixel {
+                        'k' => Color::BLACK,
+                        'w' => Color::WHITE,
+                        'r' => Color::RED,
+                        'y' => Color::GOLD,
+                        'b' => Color::rgb(0.2, 0.3, 0.9),
+                        _ => background,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Pixel-grid avatar in the style of the hardcore skull.
+pub fn spawn_avatar(parent: &mut ChildBuilder, profile: &PlayerProfile, pixel: f32) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Grid,
+                grid_template_columns: RepeatedGridTrack::px(8, pixel),
+                grid_template_rows: RepeatedGridTrack::px(10, pixel),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|avatar| {
+            for color in profile.avatar_rows().into_iter().flatten() {
+                avatar.spawn(NodeBundle {
+                    background_color: color.into(),
+                    ..default()
+                });
+            }
+        });
+}
+
+/// Which field of the profile screen is selected.
+#[derive(Resource, Default)]
+struct ProfileEditor {
+    field: usize,
+    // set after the first frame, which still carries the key that opened the screen
+    typing: bool,
+}
+
+#[derive(Component)]
+struct ProfileScreen;
+
+fn display_profile_hint(mut commands: Commands, progression: Res<Progression>) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Playing as {}, press U to edit", progression.profile.name),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(170.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyU),
+        OnGameOverScreen,
+    ));
+}
+
+fn open_profile(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut editor: ResMut<ProfileEditor>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyU) {
+        *editor = ProfileEditor::default();
+        next_state.set(GameState::Profile);
+    }
+}
+
+fn display_pause_badge(mut commands: Commands, progression: Res<Progression>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            PauseOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                progression.profile.name.clone(),
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            spawn_avatar(parent, &progression.profile, BADGE_PIXEL);
+        });
+}
+
+fn field_value(profile: &PlayerProfile, field: usize) -> String {
+    match field {
+        0 => profile.name.clone(),
+        1 => format!("{}/{}", profile.color % COLORS.len() + 1, COLORS.len()),
+        2 => format!("{:?}", profile.eyes),
+        3 => format!("{:?}", profile.mouth),
+        _ => format!("{:?}", profile.hat),
+    }
+}
+
+fn spawn_profile_editor(
+    mut commands: Commands,
+    editor: Res<ProfileEditor>,
+    progression: Res<Progression>,
+) {
+    let profile = &progression.profile;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            ProfileScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Profile",
+                TextStyle {
+                    font_size: 36.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+            spawn_avatar(parent, profile, EDITOR_PIXEL);
+            for (index, field) in FIELDS.iter().enumerate() {
+                let marker = if index == editor.field { "> " } else { "  " };
+                parent.spawn(TextBundle::from_section(
+                    format!("{marker}{field}: {}", field_value(profile, index)),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: if index == editor.field {
+                            Color::WHITE
+                        } else {
+                            Color::GRAY
+                        },
+                        ..default()
+                    },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                "Up/Down pick, Left/Right change, type a name; Enter or Escape to go back",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0) as isize;
+    options[(index + step).rem_euclid(options.len() as isize) as usize]
+}
+
+fn edit_profile(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut editor: ResMut<ProfileEditor>,
+    mut progression: ResMut<Progression>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !editor.typing {
+        characters.clear();
+        editor.typing = true;
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::Enter]) {
+        next_state.set(GameState::GameOver);
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        editor.field = (editor.field + FIELDS.len() - 1) % FIELDS.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        editor.field = (editor.field + 1) % FIELDS.len();
+    }
+
+    let step = if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        -1
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        1
+    } else {
+        0
+    };
+    // typing only goes into the name, so the arrows and other keys stay free elsewhere
+    let typed: Vec<char> = characters
+        .read()
+        .flat_map(|event| event.char.chars())
+        .filter(|character| !character.is_control())
+        .collect();
+    let backspace = keyboard_input.just_pressed(KeyCode::Backspace);
+    if step == 0 && (editor.field != 0 || (typed.is_empty() && !backspace)) {
+        return;
+    }
+
+    // edits mark the editor changed too, which redraws the screen
+    editor.set_changed();
+    let profile = &mut progression.profile;
+    match editor.field {
+        0 => {
+            if backspace {
+                profile.name.pop();
+            }
+            for character in typed {
+                if profile.name.chars().count() < MAX_NAME_LEN {
+                    profile.name.push(character);
+                }
+            }
+        }
+        1 => {
+            let color = profile.color as isize + step;
+            profile.color = color.rem_euclid(COLORS.len() as isize) as usize;
+        }
+        2 => profile.eyes = cycle(&[Eyes::Dots, Eyes::Wide, Eyes::Sleepy], profile.eyes, step),
+        3 => {
+            profile.mouth = cycle(
+                &[Mouth::Smile, Mouth::Fangs, Mouth::Tongue],
+                profile.mouth,
+                step,
+            )
+        }
+        _ => profile.hat = cycle(&[Hat::Bare, Hat::Crown, Hat::Cap], profile.hat, step),
+    }
+}
+
+fn save_profile(mut progression: ResMut<Progression>) {
+    if progression.profile.name.trim().is_empty() {
+        progression.profile.name = PlayerProfile::default().name;
+    }
+    progression.save();
+}
+
+fn despawn_profile_editor(mut commands: Commands, screens: Query<Entity, With<ProfileScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
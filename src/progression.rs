@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display_final_score, menu::MenuItem, practice::PracticeMode, profile::PlayerProfile, storage,
+    toast::Toast, GameState, OnGameOverScreen, Scoreboard,
+};
+
+pub const PROGRESSION_KEY: &str = "progress";
+const PRESTIGE_THRESHOLD: u32 = 500;
+const PRESTIGE_BONUS: f32 = 0.1;
+
+/// Loads the local profile's progression and offers prestige on the game-over
+/// screen once enough lifetime score has been banked.
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Progression::load())
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    bank_run_score.before(display_final_score),
+                    display_prestige.after(display_final_score),
+                ),
+            )
+            .add_systems(Update, prestige_input.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// Lifetime progress kept between runs.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Progression {
+    pub coins: u32,
+    pub missions_completed: u32,
+    pub lifetime_score: u32,
+    // kept through prestige resets
+    pub prestige: u32,
+    pub profile: PlayerProfile,
+}
+
+impl Progression {
+    pub fn load() -> Self {
+        storage::load_ron(PROGRESSION_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(PROGRESSION_KEY, self);
+    }
+
+    pub fn can_prestige(&self) -> bool {
+        self.lifetime_score >= PRESTIGE_THRESHOLD
+    }
+
+    /// Points earned per point scored; each prestige adds a small bonus.
+    pub fn score_multiplier(&self) -> f32 {
+        1.0 + PRESTIGE_BONUS * self.prestige as f32
+    }
+
+    /// Badge shown next to the player's score, empty before the first prestige.
+    pub fn badge(&self) -> String {
+        if self.prestige == 0 {
+            String::new()
+        } else {
+            // the default font is ASCII-only, so no star glyph
+            format!("[P{}] ", self.prestige)
+        }
+    }
+
+    fn prestige(&mut self) {
+        *self = Progression {
+            prestige: self.prestige + 1,
+            profile: std::mem::take(&mut self.profile),
+            ..default()
+        };
+    }
+}
+
+fn bank_run_score(
+    scoreboard: Res<Scoreboard>,
+    practice: Res<PracticeMode>,
+    mut progression: ResMut<Progression>,
+) {
+    if practice.is_active() {
+        return;
+    }
+    progression.lifetime_score += scoreboard.score;
+    progression.save();
+}
+
+fn prestige_message(progression: &Progression) -> String {
+    if progression.can_prestige() {
+        format!(
+            "Press P to prestige: reset coins and progress for badge P{} and +{:.0}% score",
+            progression.prestige + 1,
+            PRESTIGE_BONUS * 100.0
+        )
+    } else {
+        format!(
+            "Lifetime score {}/{PRESTIGE_THRESHOLD} until prestige",
+            progression.lifetime_score
+        )
+    }
+}
+
+#[derive(Component)]
+struct PrestigeText;
+
+fn display_prestige(mut commands: Commands, progression: Res<Progression>) {
+    commands.spawn((
+        TextBundle::from_section(
+            prestige_message(&progression),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.9, 0.7, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(45.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        PrestigeText,
+        MenuItem(KeyCode::KeyP),
+        OnGameOverScreen,
+    ));
+}
+
+fn prestige_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut progression: ResMut<Progression>,
+    mut toasts: EventWriter<Toast>,
+    mut texts: Query<&mut Text, With<PrestigeText>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyP) || !progression.can_prestige() {
+        return;
+    }
+
+    progression.prestige();
+    progression.save();
+    toasts.send(Toast(format!("Prestige {} reached!", progression.prestige)));
+    for mut text in &mut texts {
+        text.sections[0].value = prestige_message(&progression);
+    }
+}
@@ -0,0 +1,437 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::{GridPosition, WallThickness},
+    display_final_score,
+    gates::apply_level_start,
+    menu::MenuItem,
+    move_snake, new_run,
+    pool::EntityPool,
+    rules::{GameRules, SnakeStart},
+    settings::GameSettings,
+    setup,
+    sim::{self, PuzzleBoard, PuzzleStep},
+    snake_segment_spawn, storage,
+    toast::Toast,
+    Apple, Direction, GameAssets, GameState, KeyBindings, OnGameOverScreen, OnGameScreen, SafeArea,
+    SnakeBodySegment, SnakeHead, SEGMENT_SIZE, WALL_COLOR,
+};
+
+const PUZZLES_KEY: &str = "puzzles";
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+const PUZZLE_APPLE_COLOR: Color = Color::RED;
+
+/// Puzzle runs, started with J from the game-over screen: hand-made levels
+/// with every apple already placed, played one cell per key press. All the
+/// apples have to be eaten within the level's move limit; Backspace takes
+/// the last move back, R starts the level over and Escape gives up. Moves
+/// are played on the deterministic `sim::PuzzleBoard`, which also checks
+/// the solutions puzzles come with.
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PuzzleMode>()
+            .init_resource::<PuzzleRun>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_puzzle_hint.after(display_final_score),
+            )
+            .add_systems(Update, request_puzzle.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnExit(GameState::GameOver), start_or_end_puzzles)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    apply_puzzle_rules.after(apply_level_start).before(setup),
+                    clear_arena_apples.after(setup),
+                )
+                    .run_if(solving_puzzle)
+                    .run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    play_puzzle_moves,
+                    (sync_snake_to_board, sync_board_pieces, update_puzzle_hud)
+                        .run_if(resource_changed::<PuzzleRun>),
+                )
+                    .chain()
+                    .before(move_snake)
+                    .run_if(in_state(GameState::Playing).and_then(solving_puzzle)),
+            );
+    }
+}
+
+/// A hand-made puzzle level, as written in the puzzles file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Puzzle {
+    pub name: String,
+    pub start: SnakeStart,
+    pub apples: Vec<(i32, i32)>,
+    #[serde(default)]
+    pub walls: Vec<(i32, i32)>,
+    pub max_moves: u32,
+    /// A known way through, checked when the puzzles are loaded.
+    #[serde(default)]
+    pub solution: Vec<Direction>,
+}
+
+impl Puzzle {
+    fn board(&self, wall_thickness: f32) -> PuzzleBoard {
+        let cell = |(x, y): (i32, i32)| IVec2::new(x, y);
+        PuzzleBoard {
+            snake: sim::puzzle_snake(
+                cell(self.start.cell),
+                self.start.length,
+                self.start.facing.vector().as_ivec2(),
+            ),
+            apples: self.apples.iter().copied().map(cell).collect(),
+            walls: self.walls.iter().copied().map(cell).collect(),
+            moves: 0,
+            bounds: sim::grid_bounds(wall_thickness),
+        }
+    }
+}
+
+fn built_in_puzzles() -> Vec<Puzzle> {
+    let start = SnakeStart {
+        cell: (0, 0),
+        length: 2,
+        facing: Direction::Right,
+    };
+    vec![
+        Puzzle {
+            name: "First bite".to_string(),
+            start,
+            apples: vec![(2, 0), (2, 2)],
+            walls: Vec::new(),
+            max_moves: 6,
+            solution: vec![
+                Direction::Right,
+                Direction::Right,
+                Direction::Up,
+                Direction::Up,
+            ],
+        },
+        Puzzle {
+            name: "Around the wall".to_string(),
+            start,
+            apples: vec![(4, 0)],
+            walls: vec![(2, -1), (2, 0), (2, 1)],
+            max_moves: 8,
+            solution: vec![
+                Direction::Up,
+                Direction::Up,
+                Direction::Right,
+                Direction::Right,
+                Direction::Right,
+                Direction::Down,
+                Direction::Down,
+                Direction::Right,
+            ],
+        },
+    ]
+}
+
+// the player's own puzzles replace the built-in ones
+fn load_puzzles(wall_thickness: f32) -> Vec<Puzzle> {
+    let puzzles = storage::load_ron::<Vec<Puzzle>>(PUZZLES_KEY)
+        .filter(|puzzles| !puzzles.is_empty())
+        .unwrap_or_else(built_in_puzzles);
+    for puzzle in &puzzles {
+        if puzzle.solution.is_empty() {
+            continue;
+        }
+        let moves: Vec<IVec2> = puzzle
+            .solution
+            .iter()
+            .map(|direction| direction.vector().as_ivec2())
+            .collect();
+        if !puzzle
+            .board(wall_thickness)
+            .solved_by(&moves, puzzle.max_moves)
+        {
+            warn!(
+                "puzzle {} isn't solved by its solution in {} moves",
+                puzzle.name, puzzle.max_moves
+            );
+        }
+    }
+    puzzles
+}
+
+#[derive(Resource, Default)]
+pub struct PuzzleMode {
+    active: bool,
+    requested: bool,
+}
+
+/// The level being solved and the boards before each move, for undo.
+#[derive(Resource, Default)]
+struct PuzzleRun {
+    puzzles: Vec<Puzzle>,
+    current: usize,
+    board: PuzzleBoard,
+    history: Vec<PuzzleBoard>,
+}
+
+impl PuzzleRun {
+    fn restart(&mut self, wall_thickness: f32) {
+        self.board = self.puzzles[self.current].board(wall_thickness);
+        self.history.clear();
+    }
+}
+
+#[derive(Component)]
+struct PuzzlePiece;
+
+#[derive(Component)]
+struct PuzzleHud;
+
+pub fn solving_puzzle(puzzle: Res<PuzzleMode>) -> bool {
+    puzzle.active
+}
+
+fn display_puzzle_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Press J for puzzles",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(245.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyJ),
+        OnGameOverScreen,
+    ));
+}
+
+fn request_puzzle(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut puzzle: ResMut<PuzzleMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyJ) {
+        puzzle.requested = true;
+        next_state.set(GameState::Playing);
+    }
+}
+
+// any other way out of the game-over screen leaves the puzzles behind
+fn start_or_end_puzzles(
+    mut puzzle: ResMut<PuzzleMode>,
+    mut run: ResMut<PuzzleRun>,
+    wall_thickness: Res<WallThickness>,
+) {
+    *puzzle = PuzzleMode {
+        active: puzzle.requested,
+        ..default()
+    };
+    if puzzle.active {
+        let wall_thickness = wall_thickness.0;
+        run.puzzles = load_puzzles(wall_thickness);
+        run.current = 0;
+        run.restart(wall_thickness);
+    }
+}
+
+fn apply_puzzle_rules(run: Res<PuzzleRun>, mut rules: ResMut<GameRules>) {
+    *rules = GameRules {
+        start: run.puzzles[run.current].start,
+        ..GameRules::classic()
+    };
+}
+
+// the puzzle's own apples are all there is to eat
+fn clear_arena_apples(mut commands: Commands, apples: Query<Entity, With<Apple>>) {
+    for apple in &apples {
+        commands.entity(apple).despawn_recursive();
+    }
+}
+
+fn play_puzzle_moves(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    wall_thickness: Res<WallThickness>,
+    mut run: ResMut<PuzzleRun>,
+    mut toasts: EventWriter<Toast>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let wall_thickness = wall_thickness.0;
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::GameOver);
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        run.restart(wall_thickness);
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        if let Some(previous) = run.history.pop() {
+            run.board = previous;
+        }
+        return;
+    }
+    let Some(direction) = DIRECTIONS
+        .into_iter()
+        .find(|direction| keyboard_input.just_pressed(bindings.turn(*direction)))
+    else {
+        return;
+    };
+
+    let max_moves = run.puzzles[run.current].max_moves;
+    if run.board.moves >= max_moves {
+        toasts.send(Toast(
+            "Out of moves: Backspace to undo, R to restart".to_string(),
+        ));
+        return;
+    }
+    let mut board = run.board.clone();
+    match board.step(direction.vector().as_ivec2()) {
+        PuzzleStep::Blocked => return,
+        PuzzleStep::Moved | PuzzleStep::Ate => {}
+        PuzzleStep::Solved => {
+            toasts.send(Toast(format!(
+                "Solved {} in {} moves",
+                run.puzzles[run.current].name, board.moves
+            )));
+            if run.current + 1 == run.puzzles.len() {
+                next_state.set(GameState::GameOver);
+            } else {
+                run.current += 1;
+                run.restart(wall_thickness);
+            }
+            return;
+        }
+    }
+    let previous = std::mem::replace(&mut run.board, board);
+    run.history.push(previous);
+}
+
+#[allow(clippy::type_complexity)]
+fn sync_snake_to_board(
+    mut commands: Commands,
+    run: Res<PuzzleRun>,
+    game_assets: Res<GameAssets>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    mut heads: Query<&mut GridPosition, With<SnakeHead>>,
+    mut segments: Query<(Entity, &mut GridPosition), (With<SnakeBodySegment>, Without<SnakeHead>)>,
+) {
+    let Some((&head, body)) = run.board.snake.split_first() else {
+        return;
+    };
+    for mut cell in &mut heads {
+        *cell = GridPosition(head);
+    }
+
+    // body segments come in the order they follow the head
+    let mut cells = body.iter();
+    for (entity, mut cell) in &mut segments {
+        match cells.next() {
+            Some(&next) => *cell = GridPosition(next),
+            None => segment_pool.release(&mut commands, entity),
+        }
+    }
+    for &cell in cells {
+        let position = GridPosition(cell).translation();
+        snake_segment_spawn(
+            &mut commands,
+            &game_assets,
+            &mut segment_pool,
+            position.x,
+            position.y,
+        );
+    }
+}
+
+// the board decides what blocks and what gets eaten, so these are just sprites
+fn sync_board_pieces(
+    mut commands: Commands,
+    run: Res<PuzzleRun>,
+    settings: Res<GameSettings>,
+    pieces: Query<Entity, With<PuzzlePiece>>,
+) {
+    for piece in &pieces {
+        commands.entity(piece).despawn_recursive();
+    }
+    let apple_size = settings.hitboxes.apple_half_size() * 2.0;
+    let apples = run
+        .board
+        .apples
+        .iter()
+        .map(|&cell| (cell, PUZZLE_APPLE_COLOR, apple_size, -2.0));
+    let walls = run
+        .board
+        .walls
+        .iter()
+        .map(|&cell| (cell, WALL_COLOR, SEGMENT_SIZE, 0.0));
+    for (cell, color, size, z) in apples.chain(walls) {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(GridPosition(cell).translation().extend(z)),
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                ..default()
+            },
+            PuzzlePiece,
+            OnGameScreen,
+        ));
+    }
+}
+
+fn update_puzzle_hud(
+    mut commands: Commands,
+    run: Res<PuzzleRun>,
+    safe_area: Res<SafeArea>,
+    mut huds: Query<&mut Text, With<PuzzleHud>>,
+) {
+    let puzzle = &run.puzzles[run.current];
+    let message = format!(
+        "Puzzle {}/{} {}: moves {}/{}, {} apples left. Backspace undo, R restart, Esc quit",
+        run.current + 1,
+        run.puzzles.len(),
+        puzzle.name,
+        run.board.moves,
+        puzzle.max_moves,
+        run.board.apples.len()
+    );
+
+    if let Ok(mut text) = huds.get_single_mut() {
+        text.sections[0].value = message;
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            message,
+            TextStyle {
+                font_size: 16.0,
+                color: Color::rgb(1.0, 0.8, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0 + safe_area.bottom),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        PuzzleHud,
+        OnGameScreen,
+    ));
+}
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    new_run,
+    settings::GameSettings,
+    toast::Toast,
+    visual_cues::Cue,
+    Ending, GameState, OnGameScreen, RunEnd, SafeArea,
+};
+
+const FIRST_QUOTA: u32 = 5;
+const QUOTA_STEP: u32 = 2;
+// each round adds this to whatever time was left over
+const ROUND_SECONDS: f32 = 30.0;
+const BANNER_SECONDS: f32 = 2.0;
+// the timer turns red once it gets this low
+const HURRY_SECONDS: f32 = 5.0;
+const TIMER_COLOR: Color = Color::WHITE;
+const HURRY_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const BANNER_COLOR: Color = Color::rgb(0.5, 1.0, 0.5);
+
+/// Quota-timer mode, toggled with Q from the game-over screen: each round
+/// sets a number of apples to eat before the timer runs out. Meeting it
+/// starts the next round with a bigger quota and more time on the clock;
+/// running out of time ends the run.
+pub struct QuotaTimerPlugin;
+
+impl Plugin for QuotaTimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuotaRound>()
+            .add_systems(
+                Update,
+                toggle_quota_timer.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_first_round
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.quota_timer)
+                    .run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    count_quota_apples.after(DetectCollisions),
+                    run_down_timer,
+                    update_quota_timer,
+                    fade_round_banner,
+                )
+                    .chain()
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.quota_timer)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Debug)]
+struct QuotaRound {
+    round: u32,
+    quota: u32,
+    eaten: u32,
+    timer: Timer,
+}
+
+impl Default for QuotaRound {
+    fn default() -> Self {
+        QuotaRound {
+            round: 1,
+            quota: FIRST_QUOTA,
+            eaten: 0,
+            timer: Timer::from_seconds(ROUND_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component)]
+struct QuotaTimerText;
+
+#[derive(Component)]
+struct RoundBanner(Timer);
+
+fn toggle_quota_timer(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        settings.modifiers.quota_timer = !settings.modifiers.quota_timer;
+        toasts.send(Toast(if settings.modifiers.quota_timer {
+            "Quota timer on from the next run".to_string()
+        } else {
+            "Quota timer off from the next run".to_string()
+        }));
+    }
+}
+
+fn start_first_round(
+    mut commands: Commands,
+    safe_area: Res<SafeArea>,
+    mut quota: ResMut<QuotaRound>,
+) {
+    *quota = QuotaRound::default();
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 40.0,
+                color: TIMER_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(82.0 + safe_area.top),
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        QuotaTimerText,
+        OnGameScreen,
+    ));
+    commands.spawn(round_banner(&quota));
+}
+
+fn round_banner(quota: &QuotaRound) -> impl Bundle {
+    (
+        TextBundle::from_section(
+            format!("Round {}: eat {} apples", quota.round, quota.quota),
+            TextStyle {
+                font_size: 36.0,
+                color: BANNER_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(30.0),
+            width: Val::Percent(100.0),
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        RoundBanner(Timer::from_seconds(BANNER_SECONDS, TimerMode::Once)),
+        OnGameScreen,
+    )
+}
+
+fn count_quota_apples(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut quota: ResMut<QuotaRound>,
+    banners: Query<Entity, With<RoundBanner>>,
+    mut cues: EventWriter<Cue>,
+) {
+    let apples = collision_events
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .count() as u32;
+    if apples == 0 {
+        return;
+    }
+    quota.eaten += apples;
+    if quota.eaten < quota.quota {
+        return;
+    }
+
+    // time left over carries into the next round
+    let remaining = quota.timer.remaining_secs();
+    *quota = QuotaRound {
+        round: quota.round + 1,
+        quota: quota.quota + QUOTA_STEP,
+        eaten: 0,
+        timer: Timer::from_seconds(remaining + ROUND_SECONDS, TimerMode::Once),
+    };
+    for banner in &banners {
+        commands.entity(banner).despawn_recursive();
+    }
+    commands.spawn(round_banner(&quota));
+    cues.send(Cue::Callout(format!("Round {}", quota.round)));
+}
+
+fn run_down_timer(
+    time: Res<Time>,
+    mut quota: ResMut<QuotaRound>,
+    mut run_end: ResMut<RunEnd>,
+    mut toasts: EventWriter<Toast>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if quota.timer.tick(time.delta()).just_finished() {
+        toasts.send(Toast(format!("Out of time in round {}", quota.round)));
+        run_end.end(Ending::Over);
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn update_quota_timer(quota: Res<QuotaRound>, mut texts: Query<&mut Text, With<QuotaTimerText>>) {
+    let remaining = quota.timer.remaining_secs();
+    let message = format!(
+        "Round {}  {}/{}  {:.0}:{:02.0}",
+        quota.round,
+        quota.eaten,
+        quota.quota,
+        (remaining / 60.0).floor(),
+        (remaining % 60.0).floor()
+    );
+    for mut text in &mut texts {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+            text.sections[0].style.color = if remaining <= HURRY_SECONDS {
+                HURRY_COLOR
+            } else {
+                TIMER_COLOR
+            };
+        }
+    }
+}
+
+fn fade_round_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut RoundBanner, &mut Text)>,
+) {
+    for (entity, mut banner, mut text) in &mut banners {
+        if banner.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = banner.0.fraction_remaining();
+        text.sections[0].style.color = BANNER_COLOR.with_a(alpha);
+    }
+}
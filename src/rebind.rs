@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{key_name, rebind, GameAction, KeyBindings},
+    settings::GameSettings,
+    toast::Toast,
+    GameState,
+};
+
+const ACTION_KEYS: [KeyCode; 10] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Digit0,
+];
+const WAITING_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+
+/// Rebind screen, opened with R from the help screen: 1-9 and 0 pick an
+/// action and the next key pressed becomes its key, swapping with any action
+/// already on it. Escape cancels a pick or goes back, Backspace returns every
+/// action to the layout preset's keys. The keys are saved with the control
+/// settings.
+pub struct RebindPlugin;
+
+impl Plugin for RebindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(Update, open_rebind.run_if(in_state(GameState::Help)))
+            .add_systems(OnEnter(GameState::Rebind), spawn_rebind)
+            .add_systems(OnExit(GameState::Rebind), despawn_rebind)
+            .add_systems(
+                Update,
+                (
+                    rebind_keys,
+                    (despawn_rebind, spawn_rebind).chain().run_if(
+                        resource_changed::<KeyBindings>.or_else(resource_changed::<Rebinding>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Rebind)),
+            );
+    }
+}
+
+/// The action waiting for its new key.
+#[derive(Resource, Default)]
+struct Rebinding(Option<GameAction>);
+
+#[derive(Component)]
+struct RebindScreen;
+
+fn open_rebind(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        rebinding.0 = None;
+        next_state.set(GameState::Rebind);
+    }
+}
+
+fn rebind_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(action) = rebinding.0 else {
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            next_state.set(GameState::Help);
+        } else if keyboard_input.just_pressed(KeyCode::Backspace)
+            && !settings.controls.custom_keys.is_empty()
+        {
+            settings.controls.custom_keys.clear();
+        } else if let Some(action) = ACTION_KEYS
+            .into_iter()
+            .zip(GameAction::ALL)
+            .find_map(|(key, action)| keyboard_input.just_pressed(key).then_some(action))
+        {
+            rebinding.0 = Some(action);
+        }
+        return;
+    };
+
+    let Some(&key) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+    rebinding.0 = None;
+    // Escape already backs out of every screen, and help has to stay reachable
+    if key == KeyCode::Escape {
+        return;
+    }
+    if key == bindings.help {
+        toasts.send(Toast(format!("{} is the help key", key_name(key))));
+        return;
+    }
+    if key != bindings.key(action) {
+        rebind(&mut settings.controls, action, key);
+    }
+}
+
+fn spawn_rebind(mut commands: Commands, bindings: Res<KeyBindings>, rebinding: Res<Rebinding>) {
+    let line = |text: String, color: Color| {
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size: 20.0,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            RebindScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Rebind keys",
+                    TextStyle {
+                        font_size: 36.0,
+                        color: Color::rgb(0.5, 0.5, 1.0),
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::vertical(Val::Px(16.0)),
+                    ..default()
+                }),
+            );
+            for (key, action) in ACTION_KEYS.into_iter().zip(GameAction::ALL) {
+                let entry = if rebinding.0 == Some(action) {
+                    line(
+                        format!("{} {}: press a key", key_name(key), action.label()),
+                        WAITING_COLOR,
+                    )
+                } else {
+                    line(
+                        format!(
+                            "{} {}: {}",
+                            key_name(key),
+                            action.label(),
+                            key_name(bindings.key(action))
+                        ),
+                        Color::WHITE,
+                    )
+                };
+                parent.spawn(entry);
+            }
+            parent.spawn(
+                line(
+                    "Backspace: back to the layout's keys, Escape: go back".to_string(),
+                    Color::WHITE,
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(24.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn despawn_rebind(mut commands: Commands, screens: Query<Entity, With<RebindScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,331 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    capture::{capture_path, write_capture},
+    display_final_score,
+    menu::MenuItem,
+    new_run,
+    rules::GameRules,
+    setup,
+    toast::Toast,
+    DetectCollisions, Direction, GameRng, GameState, OnGameOverScreen, SnakeHead, SnakeTurned,
+};
+
+const MAGIC: &[u8; 4] = b"SNKR";
+// bumped when older builds can no longer read the file at all
+const FORMAT_MAJOR: u16 = 1;
+// bumped when fields are appended to the header, which older builds skip
+const FORMAT_MINOR: u16 = 0;
+// the header fields this build writes: rules hash and seed
+const HEADER_LEN: u16 = 16;
+const REPLAY_FOLDER: &str = "replays";
+const REPLAY_EXTENSION: &str = "snkr";
+
+/// Records every run as a replay: the seed it was played with and the turns
+/// the snake made. Y on the game-over screen saves it.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_recording.before(setup).run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                record_inputs
+                    .after(DetectCollisions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_save_hint.after(display_final_score),
+            )
+            .add_systems(Update, save_replay.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// A turn, and how many moves the snake made since the previous one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputDelta {
+    pub moves: u32,
+    pub direction: Direction,
+}
+
+/// A recorded run. On disk, all integers little-endian:
+///
+/// - `SNKR`, then the format major and minor version as `u16`s
+/// - the game version as a `u8` length and UTF-8 bytes
+/// - the header length as a `u16`, then the header: the rules fingerprint
+///   and the seed as `u64`s, followed by any fields newer minor versions add
+/// - the input count as a `u32`, then per input the moves since the previous
+///   one as a `u32` and the direction as a `u8`
+///
+/// Everything up to the game version stays put across major versions, so
+/// even a build that can't read the rest can say where the file came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub game_version: String,
+    pub rules_fingerprint: u64,
+    pub seed: u64,
+    pub inputs: Vec<InputDelta>,
+}
+
+impl Replay {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let version = self.game_version.as_bytes();
+        let version = &version[..version.len().min(u8::MAX as usize)];
+
+        let mut bytes = Vec::with_capacity(32 + version.len() + self.inputs.len() * 5);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_MAJOR.to_le_bytes());
+        bytes.extend_from_slice(&FORMAT_MINOR.to_le_bytes());
+        bytes.push(version.len() as u8);
+        bytes.extend_from_slice(version);
+        bytes.extend_from_slice(&HEADER_LEN.to_le_bytes());
+        bytes.extend_from_slice(&self.rules_fingerprint.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.inputs.len() as u32).to_le_bytes());
+        for input in &self.inputs {
+            bytes.extend_from_slice(&input.moves.to_le_bytes());
+            bytes.push(direction_byte(input.direction));
+        }
+        bytes
+    }
+
+    // read by the console until replays can be watched in game
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err("not a replay file".to_string());
+        }
+        let major = reader.u16()?;
+        let _minor = reader.u16()?;
+        let version_len = reader.u8()? as usize;
+        let game_version = String::from_utf8_lossy(reader.take(version_len)?).into_owned();
+        if major > FORMAT_MAJOR {
+            return Err(format!(
+                "this replay is from a newer game version ({game_version}); update to watch it"
+            ));
+        }
+
+        // newer minor versions append header fields, which are skipped
+        let header_len = reader.u16()? as usize;
+        let mut header = ByteReader(reader.take(header_len)?);
+        let rules_fingerprint = header.u64()?;
+        let seed = header.u64()?;
+
+        let count = reader.u32()?;
+        let inputs = (0..count)
+            .map(|_| {
+                let moves = reader.u32()?;
+                let direction = direction_from_byte(reader.u8()?)?;
+                Ok(InputDelta { moves, direction })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Replay {
+            game_version,
+            rules_fingerprint,
+            seed,
+            inputs,
+        })
+    }
+
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Replay::from_bytes(&bytes).map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("the replay file is cut short".to_string());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+// part of the file format, so not tied to the enum's declaration order
+fn direction_byte(direction: Direction) -> u8 {
+    match direction {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+    }
+}
+
+fn direction_from_byte(byte: u8) -> Result<Direction, String> {
+    match byte {
+        0 => Ok(Direction::Up),
+        1 => Ok(Direction::Down),
+        2 => Ok(Direction::Left),
+        3 => Ok(Direction::Right),
+        _ => Err(format!("unknown direction {byte} in the replay")),
+    }
+}
+
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    seed: u64,
+    // taken on the first tick, once every mode has set up its rules
+    rules_fingerprint: Option<u64>,
+    inputs: Vec<InputDelta>,
+    moves_since_input: u32,
+}
+
+// rerolled from the current rng, so seeded runs like the weekly challenge stay seeded
+fn start_recording(mut rng: ResMut<GameRng>, mut recorder: ResMut<ReplayRecorder>) {
+    let seed = rng.0.gen();
+    *rng = GameRng::seeded(seed);
+    *recorder = ReplayRecorder { seed, ..default() };
+}
+
+fn record_inputs(
+    rules: Res<GameRules>,
+    mut turns: EventReader<SnakeTurned>,
+    heads: Query<Ref<Transform>, With<SnakeHead>>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    recorder
+        .rules_fingerprint
+        .get_or_insert_with(|| rules.fingerprint());
+    for &SnakeTurned(direction) in turns.read() {
+        let moves = recorder.moves_since_input;
+        recorder.inputs.push(InputDelta { moves, direction });
+        recorder.moves_since_input = 0;
+    }
+    if heads.get_single().is_ok_and(|head| head.is_changed()) {
+        recorder.moves_since_input += 1;
+    }
+}
+
+fn display_save_hint(mut commands: Commands, recorder: Res<ReplayRecorder>) {
+    if recorder.rules_fingerprint.is_none() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            "Press Y to save the replay",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(120.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyY),
+        OnGameOverScreen,
+    ));
+}
+
+fn save_replay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    recorder: Res<ReplayRecorder>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyY) {
+        return;
+    }
+    let Some(rules_fingerprint) = recorder.rules_fingerprint else {
+        return;
+    };
+
+    let replay = Replay {
+        game_version: env!("CARGO_PKG_VERSION").to_string(),
+        rules_fingerprint,
+        seed: recorder.seed,
+        inputs: recorder.inputs.clone(),
+    };
+    let path = capture_path(REPLAY_FOLDER, "replay", REPLAY_EXTENSION);
+    match write_capture(&path, replay.to_bytes()) {
+        Ok(()) => toasts.send(Toast(format!("Replay saved to {}", path.display()))),
+        Err(err) => toasts.send(Toast(format!("Replay save failed: {err}"))),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay() -> Replay {
+        Replay {
+            game_version: "0.1.0".to_string(),
+            rules_fingerprint: 0x0123_4567_89ab_cdef,
+            seed: 42,
+            inputs: vec![
+                InputDelta {
+                    moves: 3,
+                    direction: Direction::Up,
+                },
+                InputDelta {
+                    moves: 0,
+                    direction: Direction::Left,
+                },
+                InputDelta {
+                    moves: 17,
+                    direction: Direction::Down,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let replay = replay();
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()), Ok(replay));
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupt_files() {
+        let bytes = replay().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Replay::from_bytes(&bytes[..len]).is_err(), "cut at {len}");
+        }
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Replay::from_bytes(&bad_magic).is_err());
+
+        let mut bad_direction = bytes.clone();
+        *bad_direction.last_mut().unwrap() = 9;
+        assert!(Replay::from_bytes(&bad_direction).is_err());
+
+        let mut newer = bytes;
+        newer[4..6].copy_from_slice(&(FORMAT_MAJOR + 1).to_le_bytes());
+        assert!(Replay::from_bytes(&newer).is_err());
+    }
+}
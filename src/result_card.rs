@@ -0,0 +1,296 @@
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+use crate::{
+    capture::capture_path, challenge::ActiveChallenge, collision::ColliderKind,
+    display_final_score, practice::PracticeMode, sim, teardown_game_screen, toast::Toast, Apple,
+    GameState, OnGameOverScreen, Scoreboard, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+// columns of the shared board; the rows follow the arena's shape
+const CARD_COLUMNS: usize = 10;
+const CARD_CELL_SIZE: f32 = 18.0;
+// the card needs a frame or two on screen before it can be captured
+const CAPTURE_DELAY_FRAMES: u32 = 2;
+const CARD_BACKGROUND: Color = Color::rgb(0.08, 0.08, 0.12);
+
+/// Result cards for weekly challenge runs: as the run ends, its score, seed
+/// and a coarse picture of the final board are copied to the clipboard as
+/// text with emoji squares, ready to post, and shown briefly as a card
+/// that's saved as a picture in the `cards` data folder.
+pub struct ResultCardPlugin;
+
+impl Plugin for ResultCardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            (
+                build_result_card.before(teardown_game_screen),
+                spawn_result_card
+                    .after(display_final_score)
+                    .run_if(resource_exists::<ResultCard>),
+            ),
+        )
+        .add_systems(
+            Update,
+            capture_result_card
+                .run_if(in_state(GameState::GameOver).and_then(resource_exists::<ResultCard>)),
+        )
+        .add_systems(OnExit(GameState::GameOver), discard_result_card);
+    }
+}
+
+// later kinds win when several share a card cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CardCell {
+    Empty,
+    Obstacle,
+    Apple,
+    Body,
+    Head,
+}
+
+impl CardCell {
+    fn emoji(self) -> char {
+        match self {
+            CardCell::Empty => '⬜',
+            CardCell::Obstacle => '⬛',
+            CardCell::Apple => '🟥',
+            CardCell::Body => '🟩',
+            CardCell::Head => '🟨',
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            CardCell::Empty => Color::rgb(0.2, 0.2, 0.25),
+            CardCell::Obstacle => Color::rgb(1.0, 0.5, 0.5),
+            CardCell::Apple => Color::RED,
+            CardCell::Body => Color::GREEN,
+            CardCell::Head => Color::YELLOW,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ResultCard {
+    week: String,
+    score: u32,
+    seed: u64,
+    // top row first
+    board: Vec<Vec<CardCell>>,
+    copied: bool,
+    frames_shown: u32,
+}
+
+impl ResultCard {
+    fn text(&self) -> String {
+        let board: Vec<String> = self
+            .board
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.emoji()).collect())
+            .collect();
+        format!(
+            "Snake weekly challenge {}: {} apples\n{}\nSeed {}",
+            self.week,
+            self.score,
+            board.join("\n"),
+            self.seed
+        )
+    }
+}
+
+#[derive(Component)]
+struct ResultCardScreen;
+
+/// Each position marks the card cell it falls in.
+fn card_board(pieces: impl IntoIterator<Item = (Vec2, CardCell)>) -> Vec<Vec<CardCell>> {
+    let arena = sim::arena();
+    let size = arena.max - arena.min;
+    let rows = ((CARD_COLUMNS as f32 * size.y / size.x).round() as usize).max(1);
+    let mut board = vec![vec![CardCell::Empty; CARD_COLUMNS]; rows];
+    for (position, cell) in pieces {
+        let fraction = ((position - arena.min) / size).clamp(Vec2::ZERO, Vec2::splat(0.999));
+        let column = (fraction.x * CARD_COLUMNS as f32) as usize;
+        // rows are written top first
+        let row = rows - 1 - (fraction.y * rows as f32) as usize;
+        let slot = &mut board[row][column];
+        *slot = (*slot).max(cell);
+    }
+    board
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn build_result_card(
+    mut commands: Commands,
+    challenge: Res<ActiveChallenge>,
+    practice: Res<PracticeMode>,
+    scoreboard: Res<Scoreboard>,
+    heads: Query<&Transform, With<SnakeHead>>,
+    body: Query<&Transform, With<SnakeBodySegment>>,
+    apples: Query<&Transform, With<Apple>>,
+    colliders: Query<(&Transform, &ColliderKind), (Without<SnakeHead>, Without<Apple>)>,
+) {
+    let (Some(week), Some(seed)) = (challenge.week_label(), challenge.seed()) else {
+        return;
+    };
+    if practice.is_active() {
+        return;
+    }
+
+    let position = |transform: &Transform| transform.translation.truncate();
+    // the outer walls are long slabs, obstacles a cell each
+    let obstacles = colliders
+        .iter()
+        .filter(|(transform, kind)| {
+            **kind == ColliderKind::Wall
+                && transform.scale.truncate().max_element() <= SEGMENT_SIZE.max_element()
+        })
+        .map(|(transform, _)| (position(transform), CardCell::Obstacle));
+    let pieces = obstacles
+        .chain(apples.iter().map(|t| (position(t), CardCell::Apple)))
+        .chain(body.iter().map(|t| (position(t), CardCell::Body)))
+        .chain(heads.iter().map(|t| (position(t), CardCell::Head)));
+
+    let mut card = ResultCard {
+        week,
+        score: scoreboard.score,
+        seed,
+        board: card_board(pieces),
+        copied: false,
+        frames_shown: 0,
+    };
+    card.copied = copy_to_clipboard(&card.text());
+    commands.insert_resource(card);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn copy_to_clipboard(text: &str) -> bool {
+    let copied = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+    if let Err(err) = &copied {
+        warn!("failed to copy the result card: {err}");
+    }
+    copied.is_ok()
+}
+
+// browsers only allow clipboard writes from a user gesture
+#[cfg(target_arch = "wasm32")]
+fn copy_to_clipboard(_text: &str) -> bool {
+    false
+}
+
+fn spawn_result_card(mut commands: Commands, card: Res<ResultCard>) {
+    let label = |text: String, font_size: f32| {
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: CARD_BACKGROUND.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ResultCardScreen,
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(label(format!("Weekly challenge {}", card.week), 30.0));
+            parent.spawn(label(format!("{} apples", card.score), 48.0));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|board| {
+                    for row in &card.board {
+                        board
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    column_gap: Val::Px(2.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|row_node| {
+                                for cell in row {
+                                    row_node.spawn(NodeBundle {
+                                        style: Style {
+                                            width: Val::Px(CARD_CELL_SIZE),
+                                            height: Val::Px(CARD_CELL_SIZE),
+                                            ..default()
+                                        },
+                                        background_color: cell.color().into(),
+                                        ..default()
+                                    });
+                                }
+                            });
+                    }
+                });
+            parent.spawn(label(format!("Seed {}", card.seed), 20.0));
+        });
+}
+
+fn capture_result_card(
+    mut commands: Commands,
+    mut card: ResMut<ResultCard>,
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    screens: Query<Entity, With<ResultCardScreen>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    card.frames_shown += 1;
+    if card.frames_shown < CAPTURE_DELAY_FRAMES {
+        return;
+    }
+    // taken as this frame renders, so the card goes away on the next one
+    if card.frames_shown == CAPTURE_DELAY_FRAMES {
+        let Ok(window) = main_window.get_single() else {
+            return;
+        };
+        let path = capture_path("cards", "challenge", "png");
+        let saved = match screenshot_manager.save_screenshot_to_disk(window, &path) {
+            Ok(()) => Some(format!("card saved to {}", path.display())),
+            Err(err) => {
+                warn!("result card capture failed: {err}");
+                None
+            }
+        };
+        let copied = card.copied.then(|| "copied to the clipboard".to_string());
+        let done: Vec<String> = copied.into_iter().chain(saved).collect();
+        if !done.is_empty() {
+            toasts.send(Toast(format!("Result {}", done.join(", "))));
+        }
+        return;
+    }
+
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+    commands.remove_resource::<ResultCard>();
+}
+
+fn discard_result_card(mut commands: Commands) {
+    commands.remove_resource::<ResultCard>();
+}
@@ -0,0 +1,402 @@
+use std::time::Duration;
+
+use bevy::{
+    math::{
+        bounding::{Aabb2d, IntersectsVolume},
+        vec2,
+    },
+    prelude::*,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::{GridPosition, WallBehavior},
+    collision::{ColliderKind, CollisionEvent},
+    main_menu::Difficulty,
+    move_snake, obstacle_spawn_at,
+    powerups::EffectSpeed,
+    settings::{GameSettings, SpeedRamp},
+    sim,
+    sprint::{Sprinting, SPRINT_MULTIPLIER},
+    Direction, GameRng, Scoreboard, Snake, SnakeBodySegment, SnakeHead, MOVE_INTERVAL,
+    SEGMENT_SIZE, WALL_THICKNESS,
+};
+
+const HARDCORE_SPEEDUP: f32 = 1.5;
+const KIDS_SLOWDOWN: f32 = 1.6;
+const OBSTACLE_ATTEMPTS: usize = 64;
+// ledge length in cells
+const PLATFORM_WIDTHS: std::ops::RangeInclusive<i32> = 3..=7;
+// ledges never appear right next to where the snake starts
+const PLATFORM_CLEARANCE: f32 = 100.0;
+// open cells kept ahead of the snake when a run starts
+const START_CLEARANCE: i32 = 5;
+// FNV-1a
+const FINGERPRINT_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FINGERPRINT_PRIME: u64 = 0x0100_0000_01b3;
+
+/// The rules a run is played by. Gameplay systems read them instead of their
+/// own constants, and modes and difficulties are presets of them, set from
+/// the game-over screen as the next run is picked.
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRules>()
+            .register_type::<GameRules>()
+            .add_systems(Update, apply_speed_curve.before(move_snake));
+    }
+}
+
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct GameRules {
+    /// Leaving the arena comes back in on the other side; there are no walls.
+    pub wrap_edges: bool,
+    pub self_collision_lethal: bool,
+    /// Body segments gained per apple.
+    pub growth_per_apple: usize,
+    /// Share of the arena's cells walled off when a run starts.
+    pub obstacle_density: f32,
+    /// Whether anything hands out free apples, like apple rain.
+    pub power_ups: bool,
+    pub speed: SpeedCurve,
+    /// The snake drops a cell every move it isn't heading up, and apples
+    /// fall until they rest on a wall or ledge.
+    pub gravity: bool,
+    /// Ledges laid across the arena when a run starts.
+    pub platforms: usize,
+    /// The head hits things with a box this many times its usual size.
+    pub head_hitbox_scale: f32,
+    pub start: SnakeStart,
+}
+
+impl GameRules {
+    pub fn classic() -> Self {
+        GameRules {
+            wrap_edges: false,
+            self_collision_lethal: true,
+            growth_per_apple: 1,
+            obstacle_density: 0.0,
+            power_ups: true,
+            speed: SpeedCurve::constant(MOVE_INTERVAL),
+            gravity: false,
+            platforms: 0,
+            head_hitbox_scale: 1.0,
+            start: SnakeStart::default(),
+        }
+    }
+
+    pub fn hardcore() -> Self {
+        GameRules {
+            power_ups: false,
+            speed: SpeedCurve::constant(MOVE_INTERVAL).faster(HARDCORE_SPEEDUP),
+            ..GameRules::classic()
+        }
+    }
+
+    pub fn kids() -> Self {
+        GameRules {
+            wrap_edges: true,
+            self_collision_lethal: false,
+            speed: SpeedCurve::constant(MOVE_INTERVAL * KIDS_SLOWDOWN),
+            ..GameRules::classic()
+        }
+    }
+
+    /// Hash of every rule. Unlike `Hash` it is the same across builds and
+    /// platforms, so it can be stored in files.
+    pub fn fingerprint(&self) -> u64 {
+        let fields = [
+            self.wrap_edges as u64,
+            self.self_collision_lethal as u64,
+            self.growth_per_apple as u64,
+            self.obstacle_density.to_bits() as u64,
+            self.power_ups as u64,
+            self.speed.base_interval.to_bits() as u64,
+            self.speed.speedup_per_apple.to_bits() as u64,
+            self.speed.min_interval.to_bits() as u64,
+            self.gravity as u64,
+            self.platforms as u64,
+            self.head_hitbox_scale.to_bits() as u64,
+            self.start.cell.0 as u64,
+            self.start.cell.1 as u64,
+            self.start.length as u64,
+            self.start.facing as u64,
+        ];
+        fields
+            .iter()
+            .flat_map(|field| field.to_le_bytes())
+            .fold(FINGERPRINT_OFFSET, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FINGERPRINT_PRIME)
+            })
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules::classic()
+    }
+}
+
+/// Where the snake starts a run: the cell its head is on, counted from the
+/// arena center in segment-sized steps, how many body segments trail behind
+/// it and which way it faces.
+#[derive(Reflect, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SnakeStart {
+    pub cell: (i32, i32),
+    pub length: usize,
+    pub facing: Direction,
+}
+
+impl SnakeStart {
+    pub fn position(&self) -> Vec2 {
+        vec2(self.cell.0 as f32, self.cell.1 as f32) * SEGMENT_SIZE
+    }
+
+    /// Cells kept clear for the start: the snake's own and a few ahead of it.
+    pub fn footprint(&self) -> Vec<Vec2> {
+        let ahead = self.facing.vector() * SEGMENT_SIZE;
+        (-(self.length as i32)..=START_CLEARANCE)
+            .map(|step| self.position() + ahead * step as f32)
+            .collect()
+    }
+}
+
+impl Default for SnakeStart {
+    fn default() -> Self {
+        SnakeStart {
+            cell: (1, 1),
+            length: 3,
+            facing: Direction::Down,
+        }
+    }
+}
+
+/// How the time between moves shrinks as the snake eats.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub struct SpeedCurve {
+    /// Seconds between moves at the start of a run.
+    pub base_interval: f32,
+    /// Fraction of the interval each apple shaves off.
+    pub speedup_per_apple: f32,
+    pub min_interval: f32,
+}
+
+impl SpeedCurve {
+    pub fn constant(interval: f32) -> Self {
+        SpeedCurve {
+            base_interval: interval,
+            speedup_per_apple: 0.0,
+            min_interval: interval,
+        }
+    }
+
+    pub fn faster(self, multiplier: f32) -> Self {
+        SpeedCurve {
+            base_interval: self.base_interval / multiplier,
+            min_interval: self.min_interval / multiplier,
+            ..self
+        }
+    }
+
+    /// This curve, or when it never speeds up, one from the same start that
+    /// speeds up the way `ramp` says.
+    pub fn ramped(self, ramp: SpeedRamp) -> Self {
+        if self.speedup_per_apple > 0.0 {
+            return self;
+        }
+        SpeedCurve {
+            speedup_per_apple: ramp.speedup_per_apple,
+            min_interval: self.base_interval / ramp.max_speedup.max(1.0),
+            ..self
+        }
+    }
+
+    pub fn interval(&self, apples: u32) -> Duration {
+        let curved = self.base_interval * (1.0 - self.speedup_per_apple).powi(apples as i32);
+        Duration::from_secs_f32(curved.max(self.min_interval))
+    }
+}
+
+pub fn self_collision_lethal(rules: Res<GameRules>) -> bool {
+    rules.self_collision_lethal
+}
+
+/// Whether the snake wraps through the arena's edges, by the rules or by the
+/// walls picked on the main menu.
+pub fn wraps_edges(rules: &GameRules, settings: &GameSettings) -> bool {
+    rules.wrap_edges || settings.modifiers.walls == WallBehavior::Wrap
+}
+
+pub fn has_gravity(rules: Res<GameRules>) -> bool {
+    rules.gravity
+}
+
+// the difficulty, sprinting and power-ups scale whatever interval the run is
+// on; each snake's is only worked out again when something it depends on changes
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn apply_speed_curve(
+    rules: Res<GameRules>,
+    settings: Res<GameSettings>,
+    scoreboard: Res<Scoreboard>,
+    sprinting: Res<Sprinting>,
+    difficulty: Res<Difficulty>,
+    effect_speed: Res<EffectSpeed>,
+    mut snake: ResMut<Snake>,
+    mut player_two: Query<(
+        &mut Snake,
+        Ref<Scoreboard>,
+        Ref<Sprinting>,
+        Ref<EffectSpeed>,
+    )>,
+) {
+    let ramp = match *difficulty {
+        Difficulty::Easy => settings.speed.easy,
+        Difficulty::Normal => settings.speed.normal,
+        Difficulty::Hard => settings.speed.hard,
+    };
+    let curve = rules.speed.ramped(ramp);
+    let interval = |score: u32, sprinting: &Sprinting, effect_speed: &EffectSpeed| {
+        let interval = curve
+            .interval(score)
+            .div_f32(difficulty.speed_multiplier())
+            .div_f32(effect_speed.0);
+        if sprinting.0 {
+            interval.div_f32(SPRINT_MULTIPLIER)
+        } else {
+            interval
+        }
+    };
+
+    let rules_changed = rules.is_changed() || settings.is_changed() || difficulty.is_changed();
+    if rules_changed
+        || scoreboard.is_changed()
+        || sprinting.is_changed()
+        || effect_speed.is_changed()
+    {
+        let interval = interval(scoreboard.score, &sprinting, &effect_speed);
+        snake.move_cooldown.set_duration(interval);
+    }
+    for (mut snake, scoreboard, sprinting, effect_speed) in &mut player_two {
+        if rules_changed
+            || scoreboard.is_changed()
+            || sprinting.is_changed()
+            || effect_speed.is_changed()
+        {
+            let interval = interval(scoreboard.score, &sprinting, &effect_speed);
+            snake.move_cooldown.set_duration(interval);
+        }
+    }
+}
+
+/// Checks the cell the head just moved into, so segments grown under the
+/// head don't count before they have trailed out behind it.
+pub fn detect_self_collisions(
+    mut collision_events: EventWriter<CollisionEvent>,
+    snake_heads: Query<&GridPosition, (With<SnakeHead>, Changed<GridPosition>)>,
+    segments: Query<(Entity, &GridPosition), With<SnakeBodySegment>>,
+) {
+    let Ok(head) = snake_heads.get_single() else {
+        return;
+    };
+    let (entities, body): (Vec<Entity>, Vec<IVec2>) = segments
+        .iter()
+        .map(|(entity, cell)| (entity, cell.0))
+        .unzip();
+    if let Some(index) = sim::hits_own_body(head.0, &body) {
+        collision_events.send(CollisionEvent {
+            collider: entities[index],
+            kind: ColliderKind::Body,
+        });
+    }
+}
+
+/// Walls off `density` of the arena's cells, away from the `clear` cells
+/// where the snake starts.
+pub fn place_obstacles(commands: &mut Commands, rng: &mut GameRng, density: f32, clear: &[Vec2]) {
+    let arena = sim::arena();
+    let cells = ((arena.max - arena.min) / SEGMENT_SIZE).floor();
+    let count = (cells.x * cells.y * density.clamp(0.0, 1.0)).round() as usize;
+    if count == 0 {
+        return;
+    }
+
+    // the cells next to the start, diagonals included, stay open as well
+    let kept_clear: Vec<Aabb2d> = clear
+        .iter()
+        .map(|&start| Aabb2d::new(start, SEGMENT_SIZE))
+        .collect();
+    let free = sim::free_cells(&kept_clear, WALL_THICKNESS);
+    for &cell in free.choose_multiple(&mut rng.0, count) {
+        obstacle_spawn_at(commands, cell);
+    }
+}
+
+/// Lays `count` ledges across the arena, each a short row of obstacles on the
+/// cell grid, away from the `clear` cells where the snake starts.
+pub fn place_platforms(commands: &mut Commands, rng: &mut GameRng, count: usize, clear: &[Vec2]) {
+    let arena = sim::arena();
+    let origin = arena.min + WALL_THICKNESS / 2.0;
+    let cells = ((arena.max - arena.min - WALL_THICKNESS) / SEGMENT_SIZE)
+        .floor()
+        .as_ivec2();
+    for _ in 0..count {
+        // the bottom rows stay open so fallen apples can be reached
+        let ledge = (0..OBSTACLE_ATTEMPTS).find_map(|_| {
+            let width = rng.0.gen_range(PLATFORM_WIDTHS).min(cells.x);
+            let column = rng.0.gen_range(0..=cells.x - width);
+            let row = rng.0.gen_range(3..cells.y.max(4));
+            let ledge: Vec<Vec2> = (column..column + width)
+                .map(|x| origin + (vec2(x as f32, row as f32) + 0.5) * SEGMENT_SIZE)
+                .collect();
+            let away = ledge.iter().all(|cell| {
+                clear
+                    .iter()
+                    .all(|start| cell.distance(*start) > PLATFORM_CLEARANCE)
+            });
+            away.then_some(ledge)
+        });
+        for cell in ledge.into_iter().flatten() {
+            obstacle_spawn_at(commands, cell);
+        }
+    }
+}
+
+/// A start at a random cell and facing whose footprint stays inside the walls
+/// and off the `blocked` boxes, or `None` if none turned up.
+pub fn random_start(rng: &mut GameRng, length: usize, blocked: &[Aabb2d]) -> Option<SnakeStart> {
+    let arena = sim::arena();
+    let min = arena.min + WALL_THICKNESS + SEGMENT_SIZE / 2.0;
+    let max = arena.max - WALL_THICKNESS - SEGMENT_SIZE / 2.0;
+    let (first, last) = (
+        (min / SEGMENT_SIZE).ceil().as_ivec2(),
+        (max / SEGMENT_SIZE).floor().as_ivec2(),
+    );
+    let facings = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    (0..OBSTACLE_ATTEMPTS).find_map(|_| {
+        let start = SnakeStart {
+            cell: (
+                rng.0.gen_range(first.x..=last.x),
+                rng.0.gen_range(first.y..=last.y),
+            ),
+            length,
+            facing: *facings.choose(&mut rng.0)?,
+        };
+        let safe = start.footprint().iter().all(|&cell| {
+            let tile = Aabb2d::new(cell, SEGMENT_SIZE / 2.0);
+            cell.cmpge(min).all()
+                && cell.cmple(max).all()
+                && !blocked.iter().any(|obstacle| tile.intersects(obstacle))
+        });
+        safe.then_some(start)
+    })
+}
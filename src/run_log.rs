@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    capture::{capture_path, write_capture},
+    collision::CollisionEvent,
+    display_final_score,
+    menu::MenuItem,
+    toast::Toast,
+    DetectCollisions, GameState, OnGameOverScreen, RunEnd, Scoreboard, SnakeBodySegment, SnakeHead,
+    SnakeTurned,
+};
+
+// about half an hour of movement; later ticks are dropped
+const MAX_TICKS: usize = 20_000;
+
+/// Records the run tick by tick: head position, length, score and what
+/// happened. D on the game-over screen exports it as both JSON and CSV.
+pub struct RunLogPlugin;
+
+impl Plugin for RunLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunLog>()
+            .add_systems(
+                Update,
+                record_tick
+                    .after(DetectCollisions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (record_death, display_export_hint.after(display_final_score)),
+            )
+            .add_systems(Update, export_run.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnExit(GameState::GameOver), reset_run_log);
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct RunTick {
+    seconds: f32,
+    x: f32,
+    y: f32,
+    length: usize,
+    score: u32,
+    events: Vec<String>,
+}
+
+#[derive(Resource, Default)]
+struct RunLog {
+    started: Option<f32>,
+    ticks: Vec<RunTick>,
+    finished: bool,
+}
+
+impl RunLog {
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("seconds,x,y,length,score,events\n");
+        for tick in &self.ticks {
+            csv.push_str(&format!(
+                "{:.3},{:.1},{:.1},{},{},{}\n",
+                tick.seconds,
+                tick.x,
+                tick.y,
+                tick.length,
+                tick.score,
+                tick.events.join(";")
+            ));
+        }
+        csv
+    }
+}
+
+// a tick is any frame the head moved or something happened
+fn record_tick(
+    time: Res<Time>,
+    scoreboard: Res<Scoreboard>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut turns: EventReader<SnakeTurned>,
+    heads: Query<Ref<Transform>, With<SnakeHead>>,
+    segments: Query<(), With<SnakeBodySegment>>,
+    mut log: ResMut<RunLog>,
+) {
+    let mut events: Vec<String> = turns
+        .read()
+        .map(|SnakeTurned(direction)| format!("turn {direction:?}"))
+        .collect();
+    events.extend(
+        collisions
+            .read()
+            .map(|collision| format!("hit {:?}", collision.kind)),
+    );
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    if (!head.is_changed() && events.is_empty()) || log.ticks.len() >= MAX_TICKS {
+        return;
+    }
+
+    let now = time.elapsed_seconds();
+    let started = *log.started.get_or_insert(now);
+    log.ticks.push(RunTick {
+        seconds: now - started,
+        x: head.translation.x,
+        y: head.translation.y,
+        length: segments.iter().count() + 1,
+        score: scoreboard.score,
+        events,
+    });
+}
+
+fn record_death(run_end: Res<RunEnd>, mut log: ResMut<RunLog>) {
+    // the credits and help screens come back through game over too
+    if log.finished {
+        return;
+    }
+    log.finished = true;
+    let Some(cause) = run_end.death_cause() else {
+        return;
+    };
+    if let Some(last) = log.ticks.last_mut() {
+        last.events.push(format!("died {cause:?}"));
+    }
+}
+
+fn display_export_hint(mut commands: Commands, log: Res<RunLog>) {
+    if log.ticks.is_empty() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            "Press D to export run data",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(95.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyD),
+        OnGameOverScreen,
+    ));
+}
+
+fn export_run(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    log: Res<RunLog>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyD) || log.ticks.is_empty() {
+        return;
+    }
+
+    let json_path = capture_path("runs", "run", "json");
+    let csv_path = json_path.with_extension("csv");
+    let written = serde_json::to_string_pretty(&log.ticks)
+        .map_err(|err| err.to_string())
+        .and_then(|json| write_capture(&json_path, json))
+        .and_then(|()| write_capture(&csv_path, log.to_csv()));
+    match written {
+        Ok(()) => toasts.send(Toast(format!(
+            "Run data saved to {} and .csv",
+            json_path.display()
+        ))),
+        Err(err) => toasts.send(Toast(format!("Run data export failed: {err}"))),
+    };
+}
+
+fn reset_run_log(mut log: ResMut<RunLog>) {
+    *log = RunLog::default();
+}
@@ -0,0 +1,300 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    apple_spawn_at, collider_half_size,
+    collision::ColliderKind,
+    display_final_score,
+    menu::MenuItem,
+    move_snake, obstacle_spawn_at,
+    pool::EntityPool,
+    practice::{cursor_cell, PracticeMode},
+    settings::GameSettings,
+    snake_segment_spawn,
+    world_events::{WorldEvent, WorldEventStarted},
+    GameAssets, GameState, OnGameOverScreen, OnGameScreen, SafeArea, Snake, SnakeBodySegment,
+    SnakeHead,
+};
+
+const TOOL_KEYS: [(KeyCode, SandboxTool); 4] = [
+    (KeyCode::Digit1, SandboxTool::Apple),
+    (KeyCode::Digit2, SandboxTool::Obstacle),
+    (KeyCode::Digit3, SandboxTool::PowerUp),
+    (KeyCode::Digit4, SandboxTool::Segment),
+];
+
+/// Sandbox runs, started with S from the game-over screen: a practice run
+/// where 1-4 pick a tool that left-click uses and right-click undoes, P
+/// pauses the simulation and . steps it one move at a time. Being a practice
+/// run, nothing from it is scored or recorded.
+pub struct SandboxPlugin;
+
+impl Plugin for SandboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SandboxMode>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_sandbox_hint.after(display_final_score),
+            )
+            .add_systems(
+                Update,
+                request_sandbox.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnExit(GameState::GameOver), start_or_end_sandbox)
+            // the pause menu and game over need time running again
+            .add_systems(OnExit(GameState::Playing), resume_simulation)
+            .add_systems(
+                Update,
+                (
+                    pick_tool,
+                    use_tool,
+                    (pause_simulation, step_simulation)
+                        .chain()
+                        .after(move_snake),
+                    update_sandbox_banner,
+                )
+                    .run_if(in_state(GameState::Playing).and_then(sandboxing)),
+            );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SandboxTool {
+    #[default]
+    Apple,
+    Obstacle,
+    // the only power-up in the arena is apple rain
+    PowerUp,
+    Segment,
+}
+
+impl SandboxTool {
+    fn name(self) -> &'static str {
+        match self {
+            SandboxTool::Apple => "apple",
+            SandboxTool::Obstacle => "obstacle",
+            SandboxTool::PowerUp => "apple rain",
+            SandboxTool::Segment => "segment",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SandboxMode {
+    active: bool,
+    requested: bool,
+    tool: SandboxTool,
+    paused: bool,
+    // a single move was let through and time pauses again once it's made
+    stepping: bool,
+}
+
+impl SandboxMode {
+    /// Whether the current (or just finished) run is a sandbox run.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+#[derive(Component)]
+struct SandboxBanner;
+
+pub fn sandboxing(sandbox: Res<SandboxMode>) -> bool {
+    sandbox.active
+}
+
+fn display_sandbox_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Press S for the sandbox",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(145.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyS),
+        OnGameOverScreen,
+    ));
+}
+
+fn request_sandbox(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut sandbox: ResMut<SandboxMode>,
+    mut practice: ResMut<PracticeMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyS) {
+        sandbox.requested = true;
+        practice.request();
+        next_state.set(GameState::Playing);
+    }
+}
+
+// any other way out of the game-over screen leaves the sandbox behind
+fn start_or_end_sandbox(mut sandbox: ResMut<SandboxMode>) {
+    *sandbox = SandboxMode {
+        active: sandbox.requested,
+        ..default()
+    };
+}
+
+fn resume_simulation(mut sandbox: ResMut<SandboxMode>, mut time: ResMut<Time<Virtual>>) {
+    sandbox.paused = false;
+    sandbox.stepping = false;
+    time.unpause();
+}
+
+fn pick_tool(keyboard_input: Res<ButtonInput<KeyCode>>, mut sandbox: ResMut<SandboxMode>) {
+    for (key, tool) in TOOL_KEYS {
+        if keyboard_input.just_pressed(key) {
+            sandbox.tool = tool;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn use_tool(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    sandbox: Res<SandboxMode>,
+    settings: Res<GameSettings>,
+    game_assets: Res<GameAssets>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    mut world_events: EventWriter<WorldEventStarted>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    colliders: Query<(Entity, &Transform, &ColliderKind), Without<SnakeHead>>,
+    segments: Query<(Entity, &Transform), With<SnakeBodySegment>>,
+) {
+    let place = mouse_input.just_pressed(MouseButton::Left);
+    let delete = mouse_input.just_pressed(MouseButton::Right);
+    if !place && !delete {
+        return;
+    }
+    let Some(position) = cursor_cell(&windows, &cameras) else {
+        return;
+    };
+
+    match (sandbox.tool, place) {
+        (SandboxTool::Apple, true) => {
+            apple_spawn_at(&mut commands, &game_assets, position.extend(-2.0));
+        }
+        (SandboxTool::Obstacle, true) => {
+            obstacle_spawn_at(&mut commands, position);
+        }
+        (SandboxTool::PowerUp, true) => {
+            world_events.send(WorldEventStarted(WorldEvent::AppleRain));
+        }
+        // new segments join at the tail, wherever the cursor is
+        (SandboxTool::Segment, true) => {
+            if let Some((_, tail)) = segments.iter().last() {
+                snake_segment_spawn(
+                    &mut commands,
+                    &game_assets,
+                    &mut segment_pool,
+                    tail.translation.x,
+                    tail.translation.y,
+                );
+            }
+        }
+        (SandboxTool::Segment, false) => {
+            if let Some((tail, _)) = segments.iter().last() {
+                segment_pool.release(&mut commands, tail);
+            }
+        }
+        // right-click takes away whatever is under the cursor
+        (_, false) => {
+            for (entity, transform, kind) in &colliders {
+                let half_size =
+                    collider_half_size(transform, *kind == ColliderKind::Apple, &settings.hitboxes);
+                let offset = (position - transform.translation.truncate()).abs();
+                if offset.cmple(half_size).all() {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+        }
+    }
+}
+
+fn pause_simulation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut sandbox: ResMut<SandboxMode>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    sandbox.paused = !sandbox.paused;
+    sandbox.stepping = false;
+    if sandbox.paused {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+}
+
+// runs after `move_snake`, so the move let through by a step has been made
+fn step_simulation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut sandbox: ResMut<SandboxMode>,
+    mut snake: ResMut<Snake>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if sandbox.stepping {
+        sandbox.stepping = false;
+        time.pause();
+    }
+    if !sandbox.paused || !keyboard_input.just_pressed(KeyCode::Period) {
+        return;
+    }
+    // the move is due as soon as time runs again next frame
+    let interval = snake.move_cooldown.duration();
+    snake.move_cooldown.set_elapsed(interval);
+    sandbox.stepping = true;
+    time.unpause();
+}
+
+fn update_sandbox_banner(
+    mut commands: Commands,
+    sandbox: Res<SandboxMode>,
+    safe_area: Res<SafeArea>,
+    mut banners: Query<&mut Text, With<SandboxBanner>>,
+) {
+    let message = format!(
+        "Sandbox: 1-4 tool ({}), click to place, right-click to remove, P {}, . step",
+        sandbox.tool.name(),
+        if sandbox.paused { "resume" } else { "pause" }
+    );
+
+    if let Ok(mut text) = banners.get_single_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message;
+        }
+        return;
+    }
+    // just above the practice banner
+    commands.spawn((
+        TextBundle::from_section(
+            message,
+            TextStyle {
+                font_size: 16.0,
+                color: Color::rgb(1.0, 0.8, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(30.0 + safe_area.bottom),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        SandboxBanner,
+        OnGameScreen,
+    ));
+}
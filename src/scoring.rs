@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    progression::Progression,
+    GameState, Scoreboard, ScoreboardText, Snake, MOVE_INTERVAL,
+};
+
+/// The score: a point for every apple, scaled by the prestige multiplier,
+/// kept on the scoreboard in the corner of the arena next to how fast the
+/// snake is moving.
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scoreboard>().add_systems(
+            Update,
+            (score_apple_collisions, score_update)
+                .chain()
+                .after(DetectCollisions)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn score_apple_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut scoreboard: ResMut<Scoreboard>,
+    progression: Res<Progression>,
+    // prestige bonuses are fractional, so the remainder carries to the next apple
+    mut carry: Local<f32>,
+) {
+    for event in collision_events.read() {
+        if event.kind == ColliderKind::Apple {
+            let earned = *carry + progression.score_multiplier();
+            scoreboard.score += earned.floor() as u32;
+            *carry = earned.fract();
+        }
+    }
+}
+
+pub fn score_update(
+    scoreboard: ResMut<Scoreboard>,
+    progression: Res<Progression>,
+    snake: Res<Snake>,
+    mut query: Query<&mut Text, With<ScoreboardText>>,
+) {
+    // against the standard pace, so it also shows the difficulty and boosts
+    let speed = MOVE_INTERVAL / snake.move_cooldown.duration().as_secs_f32();
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!(
+            "{}Apples Eaten: {}  Speed x{speed:.1}",
+            progression.badge(),
+            scoreboard.score
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{math::bounding::Aabb2d, prelude::*};
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST};
+
+use crate::{
+    apple_spawn_free,
+    arena::{occupied_boxes, GridPosition},
+    collision::{ColliderKind, CollisionEvent},
+    obstacle_spawn_at, score_update,
+    settings::GameSettings,
+    sim, storage, Ending, GameAssets, GameRng, GameState, RunEnd, Scoreboard, SnakeBodySegment,
+    SnakeHead, SEGMENT_SIZE, WALL_THICKNESS,
+};
+
+const SCRIPTS_FOLDER: &str = "scripts";
+// keeps a runaway loop in a script from freezing the game
+const MAX_OPERATIONS: u64 = 100_000;
+const OBSTACLE_ATTEMPTS: usize = 64;
+
+/// Game-rule scripts: every `.rhai` file in the `scripts` data folder may
+/// define `on_apple_eaten(score)`, `on_tick(seconds)` and `on_death(score)`.
+/// Hooks can call `spawn_apple()`, `spawn_obstacle()` and `add_score(points)`;
+/// `print` goes to the log. For example, an obstacle every fifth apple:
+///
+/// ```rhai
+/// fn on_apple_eaten(score) {
+///     if score % 5 == 0 { spawn_obstacle(); }
+/// }
+/// ```
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameScripts::load())
+            .add_systems(
+                Update,
+                (run_gameplay_hooks, apply_script_requests)
+                    .chain()
+                    .after(score_update)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), run_death_hook);
+    }
+}
+
+/// What the hooks asked for, applied once they have all run.
+#[derive(Default)]
+struct ScriptRequests {
+    apples: usize,
+    obstacles: usize,
+    score_delta: i64,
+}
+
+struct GameScript {
+    name: String,
+    ast: AST,
+}
+
+impl GameScript {
+    fn has_hook(&self, hook: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == hook && function.params.len() == 1)
+    }
+}
+
+#[derive(Resource)]
+struct GameScripts {
+    engine: Engine,
+    scripts: Vec<GameScript>,
+    requests: Arc<Mutex<ScriptRequests>>,
+}
+
+impl GameScripts {
+    fn load() -> Self {
+        let requests = Arc::new(Mutex::new(ScriptRequests::default()));
+        let engine = script_engine(&requests);
+
+        let scripts = storage::data_files(SCRIPTS_FOLDER, "rhai")
+            .into_iter()
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().into_owned();
+                match engine.compile_file(path) {
+                    Ok(ast) => {
+                        info!("loaded script {name}");
+                        Some(GameScript { name, ast })
+                    }
+                    Err(err) => {
+                        warn!("script {name} failed to compile: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        GameScripts {
+            engine,
+            scripts,
+            requests,
+        }
+    }
+
+    /// Calls `hook` in every script defining it; a script whose hook fails is
+    /// dropped for the rest of the session.
+    fn call(&mut self, hook: &str, args: impl FuncArgs + Clone) {
+        let engine = &self.engine;
+        self.scripts.retain(|script| {
+            if !script.has_hook(hook) {
+                return true;
+            }
+            // only the hook runs, not the script's top-level statements
+            let options = CallFnOptions::new().eval_ast(false);
+            let called = engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                hook,
+                args.clone(),
+            );
+            match called {
+                Ok(_) => true,
+                Err(err) => {
+                    warn!(
+                        "script {} switched off after {hook} failed: {err}",
+                        script.name
+                    );
+                    false
+                }
+            }
+        });
+    }
+
+    fn take_requests(&self) -> ScriptRequests {
+        self.requests
+            .lock()
+            .map(|mut requests| std::mem::take(&mut *requests))
+            .unwrap_or_default()
+    }
+}
+
+fn script_engine(requests: &Arc<Mutex<ScriptRequests>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| info!("script: {text}"));
+
+    let queue = Arc::clone(requests);
+    engine.register_fn("spawn_apple", move || {
+        if let Ok(mut requests) = queue.lock() {
+            requests.apples += 1;
+        }
+    });
+    let queue = Arc::clone(requests);
+    engine.register_fn("spawn_obstacle", move || {
+        if let Ok(mut requests) = queue.lock() {
+            requests.obstacles += 1;
+        }
+    });
+    let queue = Arc::clone(requests);
+    engine.register_fn("add_score", move |points: i64| {
+        if let Ok(mut requests) = queue.lock() {
+            requests.score_delta = requests.score_delta.saturating_add(points);
+        }
+    });
+    engine
+}
+
+fn run_gameplay_hooks(
+    mut scripts: ResMut<GameScripts>,
+    mut collisions: EventReader<CollisionEvent>,
+    time: Res<Time>,
+    scoreboard: Res<Scoreboard>,
+) {
+    if scripts.scripts.is_empty() {
+        return;
+    }
+    let apples = collisions
+        .read()
+        .filter(|collision| collision.kind == ColliderKind::Apple)
+        .count();
+    for _ in 0..apples {
+        scripts.call("on_apple_eaten", (scoreboard.score as i64,));
+    }
+    scripts.call("on_tick", (time.delta_seconds() as f64,));
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn apply_script_requests(
+    mut commands: Commands,
+    scripts: Res<GameScripts>,
+    game_assets: Res<GameAssets>,
+    settings: Res<GameSettings>,
+    mut rng: ResMut<GameRng>,
+    mut scoreboard: ResMut<Scoreboard>,
+    snake_parts: Query<&GridPosition, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    colliders: Query<(&Transform, &ColliderKind)>,
+) {
+    let requests = scripts.take_requests();
+
+    let mut occupied_cells = occupied_boxes(&snake_parts, &colliders, &settings.hitboxes);
+    for _ in 0..requests.apples {
+        let Some(cell) = apple_spawn_free(
+            &mut commands,
+            &game_assets,
+            &mut rng,
+            WALL_THICKNESS,
+            &occupied_cells,
+        ) else {
+            break;
+        };
+        occupied_cells.push(Aabb2d::new(cell, SEGMENT_SIZE / 2.0));
+    }
+
+    // obstacles stay clear of the snake so they can't end the run on the spot
+    let occupied: Vec<Vec2> = snake_parts.iter().map(|cell| cell.translation()).collect();
+    for _ in 0..requests.obstacles {
+        let Some(position) = sim::free_apple_position(
+            &mut rng.0,
+            &occupied,
+            SEGMENT_SIZE * 1.5,
+            WALL_THICKNESS,
+            OBSTACLE_ATTEMPTS,
+        ) else {
+            continue;
+        };
+        obstacle_spawn_at(&mut commands, position);
+    }
+
+    if requests.score_delta != 0 {
+        let delta = requests.score_delta.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        scoreboard.score = scoreboard.score.saturating_add_signed(delta);
+    }
+}
+
+fn run_death_hook(
+    mut scripts: ResMut<GameScripts>,
+    run_end: Res<RunEnd>,
+    scoreboard: Res<Scoreboard>,
+) {
+    // the credits and help screens come back through game over, and wins aren't deaths
+    if run_end.unrecorded().and_then(Ending::death_cause).is_none() {
+        return;
+    }
+    scripts.call("on_death", (scoreboard.score as i64,));
+    // the run is over, so whatever the hooks asked for is dropped
+    scripts.take_requests();
+}
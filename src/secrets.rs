@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, toast::Toast, SnakeHead};
+
+const SECRETS_KEY: &str = "secrets";
+const GIANT_HEAD_SCALE: f32 = 2.0;
+
+const KONAMI_CODE: &[KeyCode] = &[
+    KeyCode::ArrowUp,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::KeyB,
+    KeyCode::KeyA,
+];
+const HONK_CODE: &[KeyCode] = &[KeyCode::KeyH, KeyCode::KeyO, KeyCode::KeyN, KeyCode::KeyK];
+
+/// Cheat-code easter eggs. Typing a registered sequence unlocks its secret
+/// for good and toggles it on or off.
+pub struct SecretsPlugin;
+
+impl Plugin for SecretsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SecretProgress::load())
+            .register_secret("giant_head", "Giant head", KONAMI_CODE)
+            // swaps the apple crunch for a honk
+            .register_secret("comic_sounds", "Comic sound pack", HONK_CODE)
+            .add_systems(Update, (detect_secret_codes, apply_giant_head).chain());
+    }
+}
+
+struct Secret {
+    id: &'static str,
+    name: &'static str,
+    code: &'static [KeyCode],
+}
+
+#[derive(Resource, Default)]
+pub struct Secrets(Vec<Secret>);
+
+pub trait RegisterSecret {
+    fn register_secret(
+        &mut self,
+        id: &'static str,
+        name: &'static str,
+        code: &'static [KeyCode],
+    ) -> &mut Self;
+}
+
+impl RegisterSecret for App {
+    fn register_secret(
+        &mut self,
+        id: &'static str,
+        name: &'static str,
+        code: &'static [KeyCode],
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(Secrets::default)
+            .0
+            .push(Secret { id, name, code });
+        self
+    }
+}
+
+/// Secrets found and switched on by the local profile.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct SecretProgress {
+    unlocked: Vec<String>,
+    enabled: Vec<String>,
+}
+
+impl SecretProgress {
+    fn load() -> Self {
+        storage::load_ron(SECRETS_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(SECRETS_KEY, self);
+    }
+
+    pub fn enabled(&self, id: &str) -> bool {
+        self.enabled.iter().any(|enabled| enabled == id)
+    }
+
+    /// Returns whether this was the first time the secret was found.
+    fn toggle(&mut self, id: &str) -> bool {
+        let first_time = !self.unlocked.iter().any(|unlocked| unlocked == id);
+        if first_time {
+            self.unlocked.push(id.to_string());
+        }
+        if self.enabled(id) {
+            self.enabled.retain(|enabled| enabled != id);
+        } else {
+            self.enabled.push(id.to_string());
+        }
+        first_time
+    }
+}
+
+fn detect_secret_codes(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    secrets: Res<Secrets>,
+    mut progress: ResMut<SecretProgress>,
+    mut toasts: EventWriter<Toast>,
+    mut recent: Local<Vec<KeyCode>>,
+) {
+    let longest = secrets
+        .0
+        .iter()
+        .map(|secret| secret.code.len())
+        .max()
+        .unwrap_or_default();
+
+    for &key in keyboard_input.get_just_pressed() {
+        recent.push(key);
+        let excess = recent.len().saturating_sub(longest);
+        recent.drain(..excess);
+
+        let Some(secret) = secrets
+            .0
+            .iter()
+            .find(|secret| recent.ends_with(secret.code))
+        else {
+            continue;
+        };
+        recent.clear();
+
+        let message = if progress.toggle(secret.id) {
+            format!("Secret unlocked: {}!", secret.name)
+        } else if progress.enabled(secret.id) {
+            format!("{} on", secret.name)
+        } else {
+            format!("{} off", secret.name)
+        };
+        progress.save();
+        toasts.send(Toast(message));
+    }
+}
+
+fn apply_giant_head(
+    progress: Res<SecretProgress>,
+    mut snake_heads: Query<&mut Transform, With<SnakeHead>>,
+) {
+    // only the sprite grows; the hitbox comes from the settings
+    let scale = if progress.enabled("giant_head") {
+        Vec3::new(GIANT_HEAD_SCALE, GIANT_HEAD_SCALE, 1.0)
+    } else {
+        Vec3::ONE
+    };
+    for mut transform in &mut snake_heads {
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
@@ -0,0 +1,549 @@
+use std::path::PathBuf;
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    render::renderer::RenderAdapterInfo,
+    window::{MonitorSelection, PrimaryWindow, WindowPosition, WindowResolution},
+    winit::WinitWindows,
+};
+use serde::{Deserialize, Serialize};
+use wgpu::DeviceType;
+
+use crate::{
+    arena::WallBehavior,
+    capture::{capture_path, write_capture},
+    input::{ControlPreset, GameAction},
+    storage,
+    world_events::WorldEventWeights,
+    WALL_THICKNESS,
+};
+
+pub const SETTINGS_KEY: &str = "settings";
+// exports land here, and imports are picked up from here
+const PORTABLE_FOLDER: &str = "settings";
+// dragging a window edge changes the size every frame, so writes are batched
+const SAVE_DELAY_SECONDS: f32 = 0.5;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // `run` loads the settings early to configure the primary window
+        if !app.world.contains_resource::<GameSettings>() {
+            app.insert_resource(GameSettings::load());
+        }
+
+        app.add_systems(Startup, detect_quality_preset)
+            .add_systems(
+                Update,
+                (cycle_quality_preset, apply_graphics_settings).chain(),
+            )
+            .add_systems(Update, toggle_forgiving_hitboxes)
+            .add_systems(Last, save_settings);
+
+        if cfg!(not(any(
+            target_arch = "wasm32",
+            target_os = "android",
+            target_os = "ios"
+        ))) {
+            app.add_systems(Update, track_window_geometry);
+        }
+    }
+}
+
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Debug, Default)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct GameSettings {
+    pub graphics: GraphicsSettings,
+    pub window: WindowSettings,
+    pub hitboxes: HitboxSettings,
+    pub modifiers: ModifierSettings,
+    pub world_events: WorldEventSettings,
+    pub telemetry: TelemetrySettings,
+    pub cloud_sync: CloudSyncSettings,
+    pub speedrun: SpeedrunSettings,
+    pub accessibility: AccessibilitySettings,
+    pub controls: ControlSettings,
+    pub audio: AudioSettings,
+    pub speed: SpeedSettings,
+}
+
+/// Chosen on the controls screen; the `KeyBindings` are rebuilt from it.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub preset: ControlPreset,
+    // moves the confirm and help keys to Enter and Slash
+    pub left_handed: bool,
+    // keys picked on the rebind screen, over the preset's, e.g.
+    // `[(TurnUp, "KeyW"), (Pause, "Backspace")]`
+    pub custom_keys: Vec<(GameAction, String)>,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // scales the snake's tick rate, stepped with F9
+    pub game_speed: f32,
+    // one button turns the snake clockwise, toggled with F2
+    pub single_switch: bool,
+    // forgives a lethal hit if the snake turns away in time, toggled with F11
+    pub assist: bool,
+    // slow runs pause after this long without input; 0 turns it off
+    pub idle_pause_seconds: f32,
+    // pauses every run when idle, not only the slow ones
+    pub idle_pause_always: bool,
+    // flashes, pulses and captions for the moments that would come with a sound
+    pub visual_cues: bool,
+    // keeps the camera still when a run ends
+    pub reduced_motion: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        AccessibilitySettings {
+            game_speed: 1.0,
+            single_switch: false,
+            assist: false,
+            idle_pause_seconds: 20.0,
+            idle_pause_always: false,
+            visual_cues: false,
+            reduced_motion: false,
+        }
+    }
+}
+
+/// How the snake speeds up as it eats, for each difficulty. Modes that
+/// bring their own speed-up keep it.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SpeedSettings {
+    pub easy: SpeedRamp,
+    pub normal: SpeedRamp,
+    pub hard: SpeedRamp,
+}
+
+impl Default for SpeedSettings {
+    fn default() -> Self {
+        SpeedSettings {
+            easy: SpeedRamp {
+                speedup_per_apple: 0.01,
+                max_speedup: 1.5,
+            },
+            normal: SpeedRamp {
+                speedup_per_apple: 0.02,
+                max_speedup: 2.0,
+            },
+            hard: SpeedRamp {
+                speedup_per_apple: 0.03,
+                max_speedup: 2.5,
+            },
+        }
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SpeedRamp {
+    // fraction of the time between moves each apple shaves off
+    pub speedup_per_apple: f32,
+    // the floor, as how many times faster than at the start of the run
+    pub max_speedup: f32,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    // scales every sound and the music; 0 mutes the game
+    pub volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings { volume: 1.0 }
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SpeedrunSettings {
+    // the split timer HUD, toggled with F5
+    pub timer: bool,
+}
+
+/// Where the `cloud-sync` feature keeps a copy of the saves; off while `endpoint` is empty.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct CloudSyncSettings {
+    // a WebDAV folder or S3-compatible bucket URL; each save is a `<key>.ron` under it
+    pub endpoint: String,
+    // sent as the Authorization header when set, e.g. "Basic ..."
+    pub authorization: String,
+}
+
+/// Anonymous gameplay reporting, off until the player opts in.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    pub enabled: bool,
+    // reports stay queued locally while this is empty
+    pub endpoint: String,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WorldEventSettings {
+    pub enabled: bool,
+    pub interval_seconds: f32,
+    pub weights: WorldEventWeights,
+}
+
+impl Default for WorldEventSettings {
+    fn default() -> Self {
+        WorldEventSettings {
+            enabled: false,
+            interval_seconds: 30.0,
+            weights: WorldEventWeights::default(),
+        }
+    }
+}
+
+/// Optional rule changes layered on top of a normal run.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ModifierSettings {
+    pub poison_trail: bool,
+    pub poison_drop_seconds: f32,
+    pub poison_lifetime_seconds: f32,
+    pub food_chain: bool,
+    pub king_of_the_hill: bool,
+    pub fleeing_apples: bool,
+    pub apple_chains: bool,
+    // swaps left and right, and up and down too with `mirror_vertical`
+    pub mirrored: bool,
+    pub mirror_vertical: bool,
+    // only now and then, in announced bursts, instead of the whole run
+    pub mirror_bursts: bool,
+    pub darkness: bool,
+    pub danger_bonus: bool,
+    pub bounty_apples: bool,
+    // golden apples, speed boosts, slow-downs and shrinks turn up now and then
+    pub power_ups: bool,
+    // interior obstacle layouts that change every 10 apples
+    pub levels: bool,
+    // picked on the main menu
+    pub walls: WallBehavior,
+    pub two_player: bool,
+    // picks a new start with room ahead every run instead of the usual one
+    pub random_start: bool,
+    // the kids rule and theme presets, toggled with G on the game-over screen
+    pub kids_mode: bool,
+    // the snake falls and apples drop onto ledges, toggled with N on the game-over screen
+    pub gravity: bool,
+    // rounds of apple quotas against the clock, toggled with Q on the game-over screen
+    pub quota_timer: bool,
+}
+
+impl Default for ModifierSettings {
+    fn default() -> Self {
+        ModifierSettings {
+            poison_trail: false,
+            poison_drop_seconds: 5.0,
+            poison_lifetime_seconds: 15.0,
+            food_chain: false,
+            king_of_the_hill: false,
+            fleeing_apples: false,
+            apple_chains: false,
+            mirrored: false,
+            mirror_vertical: false,
+            mirror_bursts: false,
+            darkness: false,
+            danger_bonus: false,
+            bounty_apples: false,
+            power_ups: false,
+            levels: false,
+            walls: WallBehavior::Solid,
+            two_player: false,
+            random_start: false,
+            kids_mode: false,
+            gravity: false,
+            quota_timer: false,
+        }
+    }
+}
+
+/// Collision box sizes in world units.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct HitboxSettings {
+    pub head: (f32, f32),
+    pub apple_radius: f32,
+    pub wall_thickness: f32,
+    // shrinks the boxes that end a run by 20%
+    pub forgiving: bool,
+}
+
+impl Default for HitboxSettings {
+    fn default() -> Self {
+        HitboxSettings {
+            head: (20.0, 20.0),
+            apple_radius: 10.0,
+            wall_thickness: WALL_THICKNESS,
+            forgiving: false,
+        }
+    }
+}
+
+impl HitboxSettings {
+    pub fn head(&self) -> Vec2 {
+        Vec2::from(self.head)
+    }
+
+    /// Head box tested against walls and anything else that ends the run.
+    pub fn lethal_head(&self) -> Vec2 {
+        if self.forgiving {
+            self.head() * 0.8
+        } else {
+            self.head()
+        }
+    }
+
+    pub fn apple_half_size(&self) -> Vec2 {
+        Vec2::splat(self.apple_radius)
+    }
+}
+
+/// Primary window geometry from the previous session.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub size: Option<(f32, f32)>,
+    pub position: Option<(i32, i32)>,
+    pub monitor: Option<usize>,
+}
+
+impl WindowSettings {
+    pub fn apply(&self, window: &mut Window) {
+        if let Some((width, height)) = self.size {
+            window.resolution = WindowResolution::new(width, height);
+        }
+        window.position = match (self.position, self.monitor) {
+            (Some((x, y)), _) => WindowPosition::At(IVec2::new(x, y)),
+            (None, Some(index)) => WindowPosition::Centered(MonitorSelection::Index(index)),
+            (None, None) => window.position,
+        };
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    // `None` until the first launch picks a preset for the detected GPU
+    pub quality: Option<QualityPreset>,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl GraphicsSettings {
+    pub fn quality(&self) -> QualityPreset {
+        self.quality.unwrap_or_default()
+    }
+}
+
+impl QualityPreset {
+    pub fn msaa(self) -> Msaa {
+        match self {
+            QualityPreset::Low => Msaa::Off,
+            // WebGL2 only supports 1 or 4 samples
+            QualityPreset::Medium if cfg!(target_arch = "wasm32") => Msaa::Sample4,
+            QualityPreset::Medium => Msaa::Sample2,
+            QualityPreset::High => Msaa::Sample4,
+        }
+    }
+
+    /// Fraction of the full particle and trail count that effects should spawn.
+    pub fn effect_density(self) -> f32 {
+        match self {
+            QualityPreset::Low => 0.25,
+            QualityPreset::Medium => 0.5,
+            QualityPreset::High => 1.0,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            QualityPreset::Low => QualityPreset::Medium,
+            QualityPreset::Medium => QualityPreset::High,
+            QualityPreset::High => QualityPreset::Low,
+        }
+    }
+}
+
+impl GameSettings {
+    pub fn load() -> Self {
+        storage::load_ron(SETTINGS_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(SETTINGS_KEY, self);
+    }
+}
+
+/// The settings worth carrying to another machine, as a shareable JSON file.
+/// Window geometry and service endpoints stay behind.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct PortableSettings {
+    pub controls: ControlSettings,
+    pub accessibility: AccessibilitySettings,
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+}
+
+impl PortableSettings {
+    fn from_settings(settings: &GameSettings) -> Self {
+        PortableSettings {
+            controls: settings.controls.clone(),
+            accessibility: settings.accessibility.clone(),
+            graphics: settings.graphics.clone(),
+            audio: settings.audio.clone(),
+        }
+    }
+
+    /// Writes the bundle next to the captures and returns where it went.
+    pub fn export(settings: &GameSettings) -> Result<PathBuf, String> {
+        let json = serde_json::to_string_pretty(&PortableSettings::from_settings(settings))
+            .map_err(|err| err.to_string())?;
+        let path = capture_path(PORTABLE_FOLDER, "settings", "json");
+        write_capture(&path, json)?;
+        Ok(path)
+    }
+
+    /// Applies the newest JSON bundle in the settings folder, whether exported
+    /// here or copied in from elsewhere, and returns which file it was.
+    pub fn import(settings: &mut GameSettings) -> Result<PathBuf, String> {
+        let dir = storage::data_dir()
+            .ok_or("importing isn't available in the browser")?
+            .join(PORTABLE_FOLDER);
+        let newest = std::fs::read_dir(&dir)
+            .map_err(|err| format!("{}: {err}", dir.display()))?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .max_by_key(|path| path.metadata().and_then(|meta| meta.modified()).ok())
+            .ok_or_else(|| format!("no .json file in {}", dir.display()))?;
+
+        let json = std::fs::read_to_string(&newest).map_err(|err| err.to_string())?;
+        let portable: PortableSettings =
+            serde_json::from_str(&json).map_err(|err| format!("{}: {err}", newest.display()))?;
+        settings.controls = portable.controls;
+        settings.accessibility = portable.accessibility;
+        settings.graphics = portable.graphics;
+        settings.audio = portable.audio;
+        Ok(newest)
+    }
+}
+
+fn detect_quality_preset(
+    mut settings: ResMut<GameSettings>,
+    adapter_info: Option<Res<RenderAdapterInfo>>,
+) {
+    if settings.graphics.quality.is_some() {
+        return;
+    }
+
+    let integrated = adapter_info.is_some_and(|info| info.device_type == DeviceType::IntegratedGpu);
+    settings.graphics.quality = Some(if integrated {
+        QualityPreset::Low
+    } else {
+        QualityPreset::default()
+    });
+}
+
+fn cycle_quality_preset(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F8) {
+        let quality = settings.graphics.quality().next();
+        settings.graphics.quality = Some(quality);
+        info!("render quality set to {quality:?}");
+    }
+}
+
+fn toggle_forgiving_hitboxes(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F7) {
+        settings.hitboxes.forgiving = !settings.hitboxes.forgiving;
+        info!("forgiving hitboxes: {}", settings.hitboxes.forgiving);
+    }
+}
+
+fn apply_graphics_settings(settings: Res<GameSettings>, mut msaa: ResMut<Msaa>) {
+    if settings.is_changed() {
+        *msaa = settings.graphics.quality().msaa();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn track_window_geometry(
+    windows: Query<(Entity, &Window), (With<PrimaryWindow>, Changed<Window>)>,
+    winit_windows: NonSend<WinitWindows>,
+    mut settings: ResMut<GameSettings>,
+) {
+    let Ok((entity, window)) = windows.get_single() else {
+        return;
+    };
+
+    let monitor = winit_windows.get_window(entity).and_then(|winit_window| {
+        let current = winit_window.current_monitor()?;
+        winit_window
+            .available_monitors()
+            .position(|monitor| monitor == current)
+    });
+    let position = match window.position {
+        WindowPosition::At(position) => Some((position.x, position.y)),
+        _ => settings.window.position,
+    };
+    let geometry = WindowSettings {
+        size: Some((window.width(), window.height())),
+        position,
+        monitor: monitor.or(settings.window.monitor),
+    };
+
+    if settings.window != geometry {
+        settings.window = geometry;
+    }
+}
+
+pub fn save_settings(
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+    mut exit_events: EventReader<AppExit>,
+    mut pending_save: Local<Option<Timer>>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *pending_save = Some(Timer::from_seconds(SAVE_DELAY_SECONDS, TimerMode::Once));
+    }
+
+    let exiting = exit_events.read().next().is_some();
+    let Some(timer) = pending_save.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() || exiting {
+        settings.save();
+        *pending_save = None;
+    }
+}
@@ -0,0 +1,294 @@
+//! Gameplay rules that don't touch the ECS, shared by the systems and the benchmarks.
+
+use bevy::math::{
+    bounding::{Aabb2d, BoundingVolume, IntersectsVolume},
+    vec2, IVec2, Vec2,
+};
+use rand::Rng;
+
+use crate::{BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, SEGMENT_SIZE, TOP_WALL};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Collision {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+pub fn collided_with_wall_apple(snake_segment: Aabb2d, wall_or_apple: Aabb2d) -> Option<Collision> {
+    if !snake_segment.intersects(&wall_or_apple) {
+        return None;
+    }
+
+    let closest = wall_or_apple.closest_point(snake_segment.center());
+
+    let offset = snake_segment.center() - closest;
+
+    let side = if offset.x.abs() > offset.y.abs() {
+        if offset.x < 0.0 {
+            Collision::Left
+        } else {
+            Collision::Right
+        }
+    } else if offset.y > 0.0 {
+        Collision::Top
+    } else {
+        Collision::Bottom
+    };
+    Some(side)
+}
+
+/// Index of the first collider the head overlaps, and from which side.
+pub fn first_collision(head: Aabb2d, colliders: &[Aabb2d]) -> Option<(usize, Collision)> {
+    colliders.iter().enumerate().find_map(|(index, collider)| {
+        collided_with_wall_apple(head, *collider).map(|collision| (index, collision))
+    })
+}
+
+/// Moves every segment into the place of the one in front of it; the first
+/// segment takes the head's previous position, or cell.
+pub fn follow_head<T>(body: &mut [T], previous_head: T) {
+    let mut vacated = previous_head;
+    for segment in body {
+        vacated = std::mem::replace(segment, vacated);
+    }
+}
+
+/// The playing field, from wall center to wall center.
+pub fn arena() -> Aabb2d {
+    Aabb2d {
+        min: vec2(LEFT_WALL, BOTTOM_WALL),
+        max: vec2(RIGHT_WALL, TOP_WALL),
+    }
+}
+
+/// Index of the body segment whose cell the head moved into, if any. The
+/// segment right behind the head never counts, and neither do segments
+/// gathered under the head, like after a reset, until they trail out.
+pub fn hits_own_body(head: IVec2, body: &[IVec2]) -> Option<usize> {
+    let neck = body.iter().take_while(|&&cell| cell == head).count().max(1);
+    body.iter()
+        .skip(neck)
+        .position(|&cell| cell == head)
+        .map(|index| neck + index)
+}
+
+/// Boxes of the four walls, laid out like the game's wall sprites.
+pub fn walls(wall_thickness: f32) -> [Aabb2d; 4] {
+    let width = RIGHT_WALL - LEFT_WALL + wall_thickness;
+    let height = TOP_WALL - BOTTOM_WALL + wall_thickness;
+    let half_thickness = wall_thickness / 2.0;
+    [
+        Aabb2d::new(vec2(LEFT_WALL, 0.0), vec2(half_thickness, height / 2.0)),
+        Aabb2d::new(vec2(RIGHT_WALL, 0.0), vec2(half_thickness, height / 2.0)),
+        Aabb2d::new(vec2(0.0, BOTTOM_WALL), vec2(width / 2.0, half_thickness)),
+        Aabb2d::new(vec2(0.0, TOP_WALL), vec2(width / 2.0, half_thickness)),
+    ]
+}
+
+pub fn random_apple_position(rng: &mut impl Rng, wall_thickness: f32) -> Vec2 {
+    vec2(
+        rng.gen_range((LEFT_WALL + wall_thickness)..(RIGHT_WALL - wall_thickness)),
+        rng.gen_range((BOTTOM_WALL + wall_thickness)..(TOP_WALL - wall_thickness)),
+    )
+}
+
+/// Rejection-samples an apple position whose box doesn't overlap any of the
+/// `occupied` boxes, giving up after `attempts` rolls.
+pub fn free_apple_position(
+    rng: &mut impl Rng,
+    occupied: &[Vec2],
+    half_size: Vec2,
+    wall_thickness: f32,
+    attempts: usize,
+) -> Option<Vec2> {
+    (0..attempts).find_map(|_| {
+        let candidate = random_apple_position(rng, wall_thickness);
+        let apple = Aabb2d::new(candidate, half_size);
+        let blocked = occupied
+            .iter()
+            .any(|&position| apple.intersects(&Aabb2d::new(position, half_size)));
+        (!blocked).then_some(candidate)
+    })
+}
+
+/// Where a fleeing apple goes next: whichever neighbouring cell, or its own,
+/// is farthest from `head`. Cells outside the walls or overlapping any of the
+/// `blocked` boxes are never picked, so cornered apples slide along the walls.
+pub fn flee_step(
+    apple: Vec2,
+    head: Vec2,
+    half_size: Vec2,
+    blocked: &[Aabb2d],
+    wall_thickness: f32,
+) -> Vec2 {
+    let min = vec2(LEFT_WALL + wall_thickness, BOTTOM_WALL + wall_thickness);
+    let max = vec2(RIGHT_WALL - wall_thickness, TOP_WALL - wall_thickness);
+    [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y]
+        .into_iter()
+        .map(|step| apple + step * SEGMENT_SIZE)
+        .filter(|cell| cell.cmpge(min).all() && cell.cmple(max).all())
+        .filter(|cell| {
+            let moved = Aabb2d::new(*cell, half_size);
+            !blocked.iter().any(|other| moved.intersects(other))
+        })
+        // staying put comes last, so it wins ties
+        .chain(std::iter::once(apple))
+        .max_by(|a, b| {
+            a.distance_squared(head)
+                .total_cmp(&b.distance_squared(head))
+        })
+        .unwrap_or(apple)
+}
+
+/// Where something at `position` lands when dropped straight down onto the
+/// highest of the `supports` below it; it stays put when there are none.
+pub fn resting_position(position: Vec2, half_size: Vec2, supports: &[Aabb2d]) -> Vec2 {
+    let bottom = position.y - half_size.y;
+    supports
+        .iter()
+        .filter(|support| {
+            support.min.x < position.x + half_size.x
+                && support.max.x > position.x - half_size.x
+                && support.max.y <= bottom
+        })
+        .map(|support| support.max.y)
+        .reduce(f32::max)
+        .map_or(position, |floor| vec2(position.x, floor + half_size.y))
+}
+
+/// The first and last cells, inclusive, of the grid the snake moves on that
+/// fit inside the walls. Cells count from the arena center in segment-sized
+/// steps.
+pub fn grid_bounds(wall_thickness: f32) -> (IVec2, IVec2) {
+    let half_size = SEGMENT_SIZE / 2.0;
+    let min = (vec2(LEFT_WALL, BOTTOM_WALL) + wall_thickness + half_size) / SEGMENT_SIZE;
+    let max = (vec2(RIGHT_WALL, TOP_WALL) - wall_thickness - half_size) / SEGMENT_SIZE;
+    (min.ceil().as_ivec2(), max.floor().as_ivec2())
+}
+
+/// Cells of the grid the snake moves on that none of the `occupied` boxes
+/// overlap, where an apple can go without landing on anything.
+pub fn free_cells(occupied: &[Aabb2d], wall_thickness: f32) -> Vec<Vec2> {
+    let (min, max) = grid_bounds(wall_thickness);
+    (min.x..=max.x)
+        .flat_map(|x| (min.y..=max.y).map(move |y| vec2(x as f32, y as f32) * SEGMENT_SIZE))
+        .filter(|&cell| {
+            // shrunk so neighbours that merely touch don't block the cell
+            let tile = Aabb2d::new(cell, SEGMENT_SIZE / 2.0 * 0.9);
+            !occupied.iter().any(|blocker| tile.intersects(blocker))
+        })
+        .collect()
+}
+
+/// Free cells of the grid the snake moves on that sit right next to one of
+/// the `walls` or a `body` segment, the spots where an apple is a risk to go
+/// for. Cells overlapping any of them are left out.
+pub fn risky_cells(walls: &[Aabb2d], body: &[Vec2], wall_thickness: f32) -> Vec<Vec2> {
+    let half_size = SEGMENT_SIZE / 2.0;
+    let (min, max) = grid_bounds(wall_thickness);
+    let segments: Vec<Aabb2d> = body
+        .iter()
+        .map(|&segment| Aabb2d::new(segment, half_size))
+        .collect();
+
+    let mut cells = Vec::new();
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            let cell = vec2(x as f32, y as f32) * SEGMENT_SIZE;
+            // shrunk so neighbours that merely touch don't block the cell
+            let tile = Aabb2d::new(cell, half_size * 0.9);
+            // the grid doesn't line up with the walls, so the reach goes past the neighbouring cell
+            let reach = Aabb2d::new(cell, half_size + SEGMENT_SIZE * 0.75);
+            let mut obstacles = walls.iter().chain(&segments);
+            if obstacles.clone().any(|obstacle| tile.intersects(obstacle)) {
+                continue;
+            }
+            if obstacles.any(|obstacle| reach.intersects(obstacle)) {
+                cells.push(cell);
+            }
+        }
+    }
+    cells
+}
+
+/// A turn-based puzzle on the cell grid: the snake moves one cell per move
+/// and has to eat every apple, and that's all the state there is, so a run
+/// can be replayed, undone or checked move by move.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PuzzleBoard {
+    /// Head first.
+    pub snake: Vec<IVec2>,
+    pub apples: Vec<IVec2>,
+    pub walls: Vec<IVec2>,
+    pub moves: u32,
+    /// The grid the board is played on, as from `grid_bounds`.
+    pub bounds: (IVec2, IVec2),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleStep {
+    Moved,
+    Ate,
+    /// The last apple was eaten.
+    Solved,
+    /// The move would run into a wall or the body; nothing changed.
+    Blocked,
+}
+
+impl PuzzleBoard {
+    /// Moves the head a cell in `direction`, one of the four unit steps. The
+    /// tail moves on unless an apple was eaten, so the head may take its cell.
+    pub fn step(&mut self, direction: IVec2) -> PuzzleStep {
+        let head = self.snake[0] + direction;
+        let (min, max) = self.bounds;
+        let apple = self.apples.iter().position(|&apple| apple == head);
+        let body = match apple {
+            Some(_) => &self.snake[..],
+            None => &self.snake[..self.snake.len() - 1],
+        };
+        if head.cmplt(min).any()
+            || head.cmpgt(max).any()
+            || self.walls.contains(&head)
+            || body.contains(&head)
+        {
+            return PuzzleStep::Blocked;
+        }
+
+        self.moves += 1;
+        self.snake.insert(0, head);
+        let Some(apple) = apple else {
+            self.snake.pop();
+            return PuzzleStep::Moved;
+        };
+        self.apples.swap_remove(apple);
+        if self.apples.is_empty() {
+            PuzzleStep::Solved
+        } else {
+            PuzzleStep::Ate
+        }
+    }
+
+    /// Whether playing `moves` from this board eats every apple within
+    /// `max_moves`, without a single blocked move.
+    pub fn solved_by(&self, moves: &[IVec2], max_moves: u32) -> bool {
+        let mut board = self.clone();
+        for &direction in moves {
+            match board.step(direction) {
+                PuzzleStep::Solved => return board.moves <= max_moves,
+                PuzzleStep::Blocked => return false,
+                PuzzleStep::Moved | PuzzleStep::Ate => {}
+            }
+        }
+        false
+    }
+}
+
+/// The snake of a puzzle laid out on whole cells, head first, with `length`
+/// body segments trailing straight back from `head` against `facing`.
+pub fn puzzle_snake(head: IVec2, length: usize, facing: IVec2) -> Vec<IVec2> {
+    (0..=length as i32)
+        .map(|step| head - facing * step)
+        .collect()
+}
@@ -0,0 +1,395 @@
+use std::collections::{LinkedList, VecDeque};
+
+use bevy::{math::vec2, prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    arena::{ArenaGrid, GridPosition},
+    assist::PendingCollision,
+    collision::{ColliderKind, CollisionEvent, DetectCollisions},
+    errors::GameError,
+    input::{KeyBindings, TurnInput},
+    pool::EntityPool,
+    puzzle,
+    rules::{self, GameRules, SnakeStart},
+    scoring::score_update,
+    settings::GameSettings,
+    sim, Collider, Direction, Ending, GameAssets, GameState, OnGameScreen, RunEnd, Snake,
+    SnakeBodySegment, SnakeHead, SnakeSegment, SnakeTurned, LEFT_WALL, MOVE_INTERVAL, RIGHT_WALL,
+    SEGMENT_SIZE,
+};
+
+// presses past this many between two moves are dropped
+const TURN_QUEUE_LEN: usize = 2;
+
+/// The snake: its head stepping a cell on each tick the way it's heading,
+/// turning as queued presses come up, the body following cell by cell, a
+/// segment more for every apple eaten and the end of the run when it hits
+/// something lethal.
+pub struct SnakePlugin;
+
+impl Plugin for SnakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Snake>()
+            .init_resource::<EntityPool<SnakeBodySegment>>()
+            .add_event::<SnakeTurned>()
+            .add_systems(
+                Update,
+                (
+                    // puzzles move the snake a cell per key press instead
+                    (queue_turns, move_snake)
+                        .chain()
+                        .run_if(not(puzzle::solving_puzzle)),
+                    detect_turns,
+                )
+                    .chain()
+                    .before(DetectCollisions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (grow_on_apple_collisions, end_on_lethal_collisions)
+                    .after(DetectCollisions)
+                    .before(score_update)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+impl Snake {
+    /// The snake laid out at `start`, its body trailing behind the head.
+    pub fn at(start: &SnakeStart) -> Self {
+        let mut body = LinkedList::new();
+        let back = start.facing.opposite().vector();
+        let mut position = start.position();
+
+        let head = SnakeSegment {
+            x: position.x,
+            y: position.y,
+            entity: None,
+        };
+
+        for _ in 0..start.length {
+            position += back * SEGMENT_SIZE;
+            body.push_back(SnakeSegment {
+                x: position.x,
+                y: position.y,
+                entity: None,
+            });
+        }
+
+        Snake {
+            head,
+            body,
+            direction: start.facing,
+            turn_queue: VecDeque::new(),
+            move_cooldown: Timer::from_seconds(MOVE_INTERVAL, TimerMode::Once),
+        }
+    }
+
+    /// Queues a turn for the coming moves. Repeats of the heading it would
+    /// already have, and turns straight back onto the neck, are dropped.
+    pub fn queue_turn(&mut self, direction: Direction) {
+        queue_turn(&mut self.turn_queue, self.direction, direction);
+    }
+}
+
+/// Queues `direction` for a snake currently heading `heading`, the way
+/// `Snake::queue_turn` does, for snakes that keep their own queue.
+pub fn queue_turn(queue: &mut VecDeque<Direction>, heading: Direction, direction: Direction) {
+    let heading = queue.back().copied().unwrap_or(heading);
+    if queue.len() < TURN_QUEUE_LEN && direction != heading && direction != heading.opposite() {
+        queue.push_back(direction);
+    }
+}
+
+impl Default for Snake {
+    fn default() -> Self {
+        Snake::at(&SnakeStart::default())
+    }
+}
+
+fn queue_turns(
+    turn_input: TurnInput,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut snake: ResMut<Snake>,
+    mut player_two: Query<&mut Snake>,
+) {
+    for direction in [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ] {
+        if turn_input.just_pressed(direction) {
+            snake.queue_turn(direction);
+        }
+        // only the keyboard steers the second snake
+        if keyboard_input.just_pressed(bindings.player_two_turn(direction)) {
+            for mut snake in &mut player_two {
+                snake.queue_turn(direction);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+pub fn move_snake(
+    mut snake: ResMut<Snake>,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    grid: Res<ArenaGrid>,
+    mut game_errors: EventWriter<GameError>,
+    mut snake_head_query: Query<
+        (&mut GridPosition, &mut Transform),
+        (With<Collider>, With<SnakeHead>),
+    >,
+    mut snake_body_segment_query: Query<
+        (&mut GridPosition, &mut Transform),
+        (With<SnakeBodySegment>, Without<SnakeHead>),
+    >,
+    mut player_two: Query<
+        (&mut Snake, &mut GridPosition, &mut Transform),
+        (Without<SnakeHead>, Without<SnakeBodySegment>),
+    >,
+    mut player_two_segments: Query<
+        (&mut GridPosition, &mut Transform),
+        (
+            Without<Snake>,
+            Without<SnakeHead>,
+            Without<SnakeBodySegment>,
+        ),
+    >,
+) {
+    // the accessibility game speed scales whatever tick rate the run is using
+    let delta = time.delta().mul_f32(settings.accessibility.game_speed);
+    let wraps = rules::wraps_edges(&rules, &settings);
+
+    // the second snake keeps its own pace, and its segments in `body`
+    for (mut snake, mut head_cell, mut head_transform) in &mut player_two {
+        if !snake.move_cooldown.tick(delta).finished() {
+            continue;
+        }
+        snake.move_cooldown.reset();
+        let previous_head = step_head(&mut snake, &rules, &grid, wraps, &mut head_cell);
+        head_transform.translation = head_cell.translation().extend(head_transform.translation.z);
+
+        let segments: Vec<Entity> = snake
+            .body
+            .iter()
+            .filter_map(|segment| segment.entity)
+            .collect();
+        let mut body: Vec<IVec2> = segments
+            .iter()
+            .filter_map(|&segment| player_two_segments.get(segment).ok())
+            .map(|(cell, _)| cell.0)
+            .collect();
+        sim::follow_head(&mut body, previous_head);
+        for (&segment, next) in segments.iter().zip(body) {
+            if let Ok((mut cell, mut transform)) = player_two_segments.get_mut(segment) {
+                *cell = GridPosition(next);
+                transform.translation = cell.translation().extend(transform.translation.z);
+            }
+        }
+    }
+
+    if !snake.move_cooldown.tick(delta).finished() {
+        return;
+    }
+    let Ok((mut head_cell, mut head_transform)) = snake_head_query.get_single_mut() else {
+        game_errors.send(GameError("The snake's head went missing".to_string()));
+        return;
+    };
+    snake.move_cooldown.reset();
+
+    let previous_head = step_head(&mut snake, &rules, &grid, wraps, &mut head_cell);
+    head_transform.translation = head_cell.translation().extend(head_transform.translation.z);
+
+    let _span = info_span!("follow_head").entered();
+    let mut body: Vec<IVec2> = snake_body_segment_query
+        .iter()
+        .map(|(cell, _)| cell.0)
+        .collect();
+    sim::follow_head(&mut body, previous_head);
+    for ((mut cell, mut transform), next) in snake_body_segment_query.iter_mut().zip(body) {
+        if cell.0 != next {
+            *cell = GridPosition(next);
+            transform.translation = cell.translation().extend(transform.translation.z);
+        }
+    }
+}
+
+// one cell per tick, turning first if a turn is queued; returns the cell the
+// head left
+fn step_head(
+    snake: &mut Snake,
+    rules: &GameRules,
+    grid: &ArenaGrid,
+    wraps: bool,
+    head: &mut GridPosition,
+) -> IVec2 {
+    if let Some(turn) = snake.turn_queue.pop_front() {
+        snake.direction = turn;
+    }
+    let mut step = snake.direction.vector().as_ivec2();
+    // gravity drops the head a cell every move it isn't climbing or diving
+    if rules.gravity && step.y == 0 {
+        step.y = -1;
+    }
+
+    let previous = head.0;
+    let mut next = previous + step;
+    if wraps {
+        next = grid.wrap(next);
+    }
+    *head = GridPosition(next);
+    previous
+}
+
+fn detect_turns(
+    snake_heads: Query<&Transform, (With<SnakeHead>, Changed<Transform>)>,
+    mut last: Local<(Option<Vec2>, Option<Direction>)>,
+    mut turns: EventWriter<SnakeTurned>,
+) {
+    let Ok(head) = snake_heads.get_single() else {
+        return;
+    };
+    let position = head.translation.truncate();
+    let (last_position, heading) = &mut *last;
+    let Some(previous) = last_position.replace(position) else {
+        return;
+    };
+
+    let delta = position - previous;
+    // wrapping through an edge jumps across the arena, which isn't a turn
+    if delta == Vec2::ZERO || delta.abs().max_element() > (RIGHT_WALL - LEFT_WALL) / 2.0 {
+        return;
+    }
+    let direction = if delta.x.abs() > delta.y.abs() {
+        if delta.x < 0.0 {
+            Direction::Left
+        } else {
+            Direction::Right
+        }
+    } else if delta.y < 0.0 {
+        Direction::Down
+    } else {
+        Direction::Up
+    };
+    if *heading != Some(direction) {
+        *heading = Some(direction);
+        turns.send(SnakeTurned(direction));
+    }
+}
+
+fn grow_on_apple_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    rules: Res<GameRules>,
+    snake_head_query: Query<&Transform, With<SnakeHead>>,
+) {
+    let Ok(snake_head_transform) = snake_head_query.get_single() else {
+        return;
+    };
+    let apples = collision_events
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .count();
+    for _ in 0..apples * rules.growth_per_apple {
+        snake_segment_spawn(
+            &mut commands,
+            &game_assets,
+            &mut segment_pool,
+            snake_head_transform.translation.x,
+            snake_head_transform.translation.y,
+        );
+    }
+}
+
+fn end_on_lethal_collisions(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut turns: EventReader<SnakeTurned>,
+    mut pending: ResMut<PendingCollision>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // game over if a wall or poison is hit
+    let hit = collision_events
+        .read()
+        .find(|event| event.kind.is_lethal())
+        .copied();
+    let turned = turns.read().count() > 0;
+    let hit = if settings.accessibility.assist {
+        pending.hold(hit, turned, time.delta())
+    } else {
+        hit
+    };
+    if let Some(event) = hit {
+        run_end.end(Ending::Died(event.kind));
+        next_state.set(GameState::GameOver);
+    }
+}
+
+pub fn snake_segment_spawn(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    segment_pool: &mut EntityPool<SnakeBodySegment>,
+    x: f32,
+    y: f32,
+) -> Entity {
+    let _span = info_span!("snake_segment_spawn").entered();
+    let cell = GridPosition::containing(vec2(x, y));
+    segment_pool.acquire(
+        commands,
+        (
+            MaterialMesh2dBundle {
+                mesh: game_assets.quad.clone(),
+                material: game_assets.snake_material.clone(),
+                transform: Transform::from_translation(cell.translation().extend(0.0)),
+                ..default()
+            },
+            cell,
+            SnakeBodySegment,
+            OnGameScreen,
+        ),
+    )
+}
+
+pub fn snake_spawn(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    segment_pool: &mut EntityPool<SnakeBodySegment>,
+    snake: &mut Snake,
+) {
+    let _span = info_span!("snake_spawn").entered();
+
+    let cell = GridPosition::containing(vec2(snake.head.x, snake.head.y));
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: game_assets.quad.clone(),
+            material: game_assets.snake_head_material.clone(),
+            transform: Transform::from_translation(cell.translation().extend(0.0)),
+            ..default()
+        },
+        cell,
+        SnakeHead,
+        Collider,
+        OnGameScreen,
+    ));
+
+    for segment in snake.body.iter_mut() {
+        segment.entity = Some(snake_segment_spawn(
+            commands,
+            game_assets,
+            segment_pool,
+            segment.x,
+            segment.y,
+        ));
+    }
+}
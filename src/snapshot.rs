@@ -0,0 +1,98 @@
+use crate::{
+    pool::Pooled, Apple, GameAssets, OnGameScreen, Scoreboard, Snake, SnakeBodySegment, SnakeHead,
+};
+use bevy::{
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    scene::{DynamicScene, DynamicSceneBuilder, SceneSpawnError},
+    sprite::Mesh2dHandle,
+};
+
+/// The gameplay state of a run: every arena entity with its reflected
+/// components, plus the run's resources. Save/resume, rewind, checkpoints and
+/// replay checks all capture and restore through this.
+///
+/// HUD nodes and render handles are left out; they are rebuilt from
+/// `GameAssets` on restore, which is also what keeps snapshots serializable.
+pub struct WorldSnapshot(DynamicScene);
+
+impl WorldSnapshot {
+    pub fn capture(world: &mut World) -> Self {
+        let entities: Vec<Entity> = world
+            .query_filtered::<Entity, (With<OnGameScreen>, Without<Node>, Without<Pooled>)>()
+            .iter(world)
+            .collect();
+
+        let scene = DynamicSceneBuilder::from_world(world)
+            .deny::<Mesh2dHandle>()
+            .deny::<Handle<ColorMaterial>>()
+            .deny::<Handle<Image>>()
+            .allow_resource::<Snake>()
+            .allow_resource::<Scoreboard>()
+            .extract_entities(entities.into_iter())
+            .extract_resources()
+            .build();
+        WorldSnapshot(scene)
+    }
+
+    /// Puts the run back as it was captured. Entities still alive are updated
+    /// in place, so references to them stay valid; ones spawned since are
+    /// despawned and ones despawned since come back under new ids.
+    pub fn restore(&self, world: &mut World) -> Result<(), SceneSpawnError> {
+        let mut entity_map = EntityHashMap::default();
+        let current: Vec<Entity> = world
+            .query_filtered::<Entity, (With<OnGameScreen>, Without<Node>, Without<Pooled>)>()
+            .iter(world)
+            .collect();
+        for entity in current {
+            if self.0.entities.iter().any(|saved| saved.entity == entity) {
+                entity_map.insert(entity, entity);
+            } else {
+                world.entity_mut(entity).despawn_recursive();
+            }
+        }
+
+        self.0.write_to_world(world, &mut entity_map)?;
+        restore_visuals(world);
+        Ok(())
+    }
+
+    pub fn to_ron(&self, world: &World) -> Result<String, String> {
+        self.0
+            .serialize_ron(world.resource::<AppTypeRegistry>())
+            .map_err(|err| err.to_string())
+    }
+}
+
+fn restore_visuals(world: &mut World) {
+    let game_assets = world.resource::<GameAssets>();
+    let (quad, snake_material, apple_material) = (
+        game_assets.quad.clone(),
+        game_assets.snake_material.clone(),
+        game_assets.apple_material.clone(),
+    );
+
+    let meshless: Vec<(Entity, bool)> = world
+        .query_filtered::<(Entity, Has<Apple>), (
+            Or<(With<Apple>, With<SnakeHead>, With<SnakeBodySegment>)>,
+            Without<Mesh2dHandle>,
+        )>()
+        .iter(world)
+        .collect();
+    for (entity, is_apple) in meshless {
+        let material = if is_apple {
+            apple_material.clone()
+        } else {
+            snake_material.clone()
+        };
+        world.entity_mut(entity).insert((quad.clone(), material));
+    }
+
+    let textureless: Vec<Entity> = world
+        .query_filtered::<Entity, (With<Sprite>, Without<Handle<Image>>)>()
+        .iter(world)
+        .collect();
+    for entity in textureless {
+        world.entity_mut(entity).insert(Handle::<Image>::default());
+    }
+}
@@ -0,0 +1,315 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    capture::{capture_path, write_capture},
+    display_final_score,
+    practice::PracticeMode,
+    settings::GameSettings,
+    storage,
+    toast::Toast,
+    GameState, OnGameOverScreen, OnGameScreen, SafeArea, Scoreboard, SnakeHead,
+};
+
+const PERSONAL_BEST_KEY: &str = "splits";
+const SPLIT_APPLES: u32 = 10;
+const VISIBLE_SPLITS: usize = 5;
+const AHEAD_COLOR: Color = Color::rgb(0.3, 1.0, 0.4);
+const BEHIND_COLOR: Color = Color::rgb(1.0, 0.35, 0.35);
+
+/// Speedrun timer with a split every `SPLIT_APPLES` apples, compared against
+/// the personal best. F5 switches it on or off; E on the game-over screen
+/// exports the run's splits.
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunClock>()
+            .insert_resource(PersonalBest::load())
+            .add_systems(Update, toggle_speedrun_timer)
+            // real time: pauses count too, for as long as the run is on screen
+            .add_systems(
+                Update,
+                tick_run_clock.run_if(any_with_component::<SnakeHead>),
+            )
+            .add_systems(
+                Update,
+                (record_splits, update_speedrun_hud)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    save_personal_best.before(display_final_score),
+                    display_final_splits.after(display_final_score),
+                ),
+            )
+            .add_systems(Update, export_splits.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnExit(GameState::GameOver), reset_run_clock);
+    }
+}
+
+#[derive(Resource, Default)]
+struct RunClock {
+    elapsed: Duration,
+    splits: Vec<Duration>,
+}
+
+/// Split times in seconds of the best run so far: the one reaching the most
+/// splits, and the fastest among those.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct PersonalBest {
+    splits: Vec<f32>,
+}
+
+impl PersonalBest {
+    fn load() -> Self {
+        storage::load_ron(PERSONAL_BEST_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(PERSONAL_BEST_KEY, self);
+    }
+
+    fn beaten_by(&self, splits: &[f32]) -> bool {
+        match (splits.last(), self.splits.last()) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(time), Some(best)) => {
+                splits.len() > self.splits.len()
+                    || (splits.len() == self.splits.len() && time < best)
+            }
+        }
+    }
+
+    /// Seconds ahead (negative) or behind (positive) the best at split `index`.
+    fn delta(&self, index: usize, time: f32) -> Option<f32> {
+        self.splits.get(index).map(|best| time - best)
+    }
+}
+
+#[derive(Component)]
+struct SpeedrunHud;
+
+fn format_time(seconds: f32) -> String {
+    let millis = (seconds * 1000.0).round() as u64;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+fn delta_color(delta: Option<f32>) -> Color {
+    match delta {
+        Some(delta) if delta < 0.0 => AHEAD_COLOR,
+        Some(_) => BEHIND_COLOR,
+        None => Color::WHITE,
+    }
+}
+
+fn split_line(personal_best: &PersonalBest, index: usize, time: f32) -> (String, Color) {
+    let delta = personal_best.delta(index, time);
+    let mut line = format!(
+        "\n{:>3}  {}",
+        (index as u32 + 1) * SPLIT_APPLES,
+        format_time(time)
+    );
+    if let Some(delta) = delta {
+        line.push_str(&format!("  {delta:+.2}"));
+    }
+    (line, delta_color(delta))
+}
+
+fn toggle_speedrun_timer(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        settings.speedrun.timer = !settings.speedrun.timer;
+        info!("speedrun timer: {}", settings.speedrun.timer);
+    }
+}
+
+fn tick_run_clock(time: Res<Time>, mut clock: ResMut<RunClock>) {
+    clock.elapsed += time.delta();
+}
+
+fn record_splits(scoreboard: Res<Scoreboard>, mut clock: ResMut<RunClock>) {
+    // a big bonus can cross more than one split at once
+    while (clock.splits.len() as u32 + 1) * SPLIT_APPLES <= scoreboard.score {
+        let elapsed = clock.elapsed;
+        clock.splits.push(elapsed);
+    }
+}
+
+fn update_speedrun_hud(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    safe_area: Res<SafeArea>,
+    clock: Res<RunClock>,
+    personal_best: Res<PersonalBest>,
+    mut huds: Query<(Entity, &mut Text), With<SpeedrunHud>>,
+) {
+    if !settings.speedrun.timer {
+        for (entity, _) in &huds {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let elapsed = clock.elapsed.as_secs_f32();
+    // the running time is judged against the best time for the next split
+    let next_delta = personal_best.delta(clock.splits.len(), elapsed);
+    let running_color = match next_delta {
+        Some(delta) if delta > 0.0 => BEHIND_COLOR,
+        _ => Color::WHITE,
+    };
+    let style = |font_size: f32, color: Color| TextStyle {
+        font_size,
+        color,
+        ..default()
+    };
+
+    let mut sections = vec![TextSection::new(
+        format_time(elapsed),
+        style(28.0, running_color),
+    )];
+    let skip = clock.splits.len().saturating_sub(VISIBLE_SPLITS);
+    for (index, split) in clock.splits.iter().enumerate().skip(skip) {
+        let (line, color) = split_line(&personal_best, index, split.as_secs_f32());
+        sections.push(TextSection::new(line, style(18.0, color)));
+    }
+
+    if let Ok((_, mut text)) = huds.get_single_mut() {
+        text.sections = sections;
+    } else {
+        commands.spawn((
+            TextBundle::from_sections(sections)
+                .with_text_justify(JustifyText::Right)
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0 + safe_area.top),
+                    right: Val::Px(10.0),
+                    ..default()
+                }),
+            SpeedrunHud,
+            OnGameScreen,
+        ));
+    }
+}
+
+fn run_splits(clock: &RunClock) -> Vec<f32> {
+    clock
+        .splits
+        .iter()
+        .map(|split| split.as_secs_f32())
+        .collect()
+}
+
+fn save_personal_best(
+    settings: Res<GameSettings>,
+    practice: Res<PracticeMode>,
+    clock: Res<RunClock>,
+    personal_best: Res<PersonalBest>,
+) {
+    if practice.is_active() {
+        return;
+    }
+    let splits = run_splits(&clock);
+    // the old best stays loaded until the next run so the game-over screen
+    // can show this run's deltas against it
+    if settings.speedrun.timer && personal_best.beaten_by(&splits) {
+        PersonalBest { splits }.save();
+    }
+}
+
+fn display_final_splits(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    clock: Res<RunClock>,
+    personal_best: Res<PersonalBest>,
+) {
+    if !settings.speedrun.timer || clock.splits.is_empty() {
+        return;
+    }
+
+    let mut sections = vec![TextSection::new(
+        format!("Final time {}", format_time(clock.elapsed.as_secs_f32())),
+        TextStyle {
+            font_size: 22.0,
+            color: Color::WHITE,
+            ..default()
+        },
+    )];
+    for (index, split) in clock.splits.iter().enumerate() {
+        let (line, color) = split_line(&personal_best, index, split.as_secs_f32());
+        sections.push(TextSection::new(
+            line,
+            TextStyle {
+                font_size: 18.0,
+                color,
+                ..default()
+            },
+        ));
+    }
+    sections.push(TextSection::new(
+        "\nPress E to export the splits",
+        TextStyle {
+            font_size: 18.0,
+            color: Color::rgb(0.5, 0.5, 1.0),
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        TextBundle::from_sections(sections)
+            .with_text_justify(JustifyText::Right)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            }),
+        OnGameOverScreen,
+    ));
+}
+
+fn export_splits(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    clock: Res<RunClock>,
+    personal_best: Res<PersonalBest>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyE) || clock.splits.is_empty() {
+        return;
+    }
+
+    let mut csv = String::from("apples,time,personal_best,delta\n");
+    for (index, split) in run_splits(&clock).into_iter().enumerate() {
+        let best = personal_best.splits.get(index);
+        csv.push_str(&format!(
+            "{},{split:.3},{},{}\n",
+            (index as u32 + 1) * SPLIT_APPLES,
+            best.map_or(String::new(), |best| format!("{best:.3}")),
+            best.map_or(String::new(), |best| format!("{:.3}", split - best)),
+        ));
+    }
+
+    let path = capture_path("splits", "splits", "csv");
+    match write_capture(&path, csv) {
+        Ok(()) => toasts.send(Toast(format!("Splits saved to {}", path.display()))),
+        Err(err) => toasts.send(Toast(format!("Splits export failed: {err}"))),
+    };
+}
+
+fn reset_run_clock(mut clock: ResMut<RunClock>, mut personal_best: ResMut<PersonalBest>) {
+    *clock = RunClock::default();
+    // pick up a best saved at the end of the last run
+    *personal_best = PersonalBest::load();
+}
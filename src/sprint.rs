@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+
+use crate::{
+    move_snake, new_run, GameState, OnGameScreen, SafeArea, Snake, SnakeHead, SEGMENT_SIZE,
+};
+
+const SPRINT_KEY: KeyCode = KeyCode::ShiftLeft;
+const PLAYER_TWO_SPRINT_KEY: KeyCode = KeyCode::KeyQ;
+/// Sprinting divides the time between moves by this, on top of the run's speed.
+pub const SPRINT_MULTIPLIER: f32 = 2.0;
+// a full bar lasts two seconds of sprinting and refills in four
+const STAMINA_DRAIN_PER_SECOND: f32 = 0.5;
+const STAMINA_REFILL_PER_SECOND: f32 = 0.25;
+const BAR_WIDTH: f32 = 120.0;
+const BAR_HEIGHT: f32 = 8.0;
+const AURA_SCALE: f32 = 1.8;
+const AURA_COLOR: Color = Color::rgba(0.6, 0.9, 1.0, 0.18);
+
+/// Holding left shift sprints: the snake moves twice as often while a
+/// stamina bar drains, and the bar refills once the key is let go. An empty
+/// bar can't be sprinted on again until the key is released. The bar sits
+/// in the HUD and the head glows faintly while sprinting. A versus round's
+/// second snake sprints on Q, on a bar of its own that isn't shown.
+pub struct SprintPlugin;
+
+impl Plugin for SprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stamina>()
+            .init_resource::<Sprinting>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_stamina, spawn_stamina_bar).run_if(new_run),
+            )
+            .add_systems(OnExit(GameState::Playing), stop_sprinting)
+            .add_systems(
+                Update,
+                (
+                    drain_stamina.before(move_snake),
+                    (update_stamina_bar, show_speed_aura),
+                    add_speed_aura,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// the second player's, like `Sprinting`, ride on its snake's head
+#[derive(Resource, Component, Debug)]
+pub struct Stamina {
+    /// From 0 (spent) to 1 (full).
+    pub current: f32,
+    // emptied while the key was held, so it has to be let go first
+    exhausted: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            current: 1.0,
+            exhausted: false,
+        }
+    }
+}
+
+/// Whether the snake is sprinting right now. Kept apart from `Stamina`,
+/// which changes every frame, so the speed is only recomputed on a switch.
+#[derive(Resource, Component, Default, Debug, PartialEq)]
+pub struct Sprinting(pub bool);
+
+#[derive(Component)]
+struct StaminaBarFill;
+
+#[derive(Component)]
+struct SpeedAura;
+
+fn reset_stamina(mut stamina: ResMut<Stamina>) {
+    *stamina = Stamina::default();
+}
+
+fn stop_sprinting(mut sprinting: ResMut<Sprinting>) {
+    sprinting.set_if_neq(Sprinting(false));
+}
+
+fn drain_stamina(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut stamina: ResMut<Stamina>,
+    mut sprinting: ResMut<Sprinting>,
+    mut player_two: Query<(&mut Stamina, &mut Sprinting), With<Snake>>,
+) {
+    let sprint = stamina.drain(keyboard_input.pressed(SPRINT_KEY), time.delta_seconds());
+    sprinting.set_if_neq(Sprinting(sprint));
+    for (mut stamina, mut sprinting) in &mut player_two {
+        let held = keyboard_input.pressed(PLAYER_TWO_SPRINT_KEY);
+        let sprint = stamina.drain(held, time.delta_seconds());
+        sprinting.set_if_neq(Sprinting(sprint));
+    }
+}
+
+impl Stamina {
+    // whether the snake sprints this frame, with the key `held` or not
+    fn drain(&mut self, held: bool, seconds: f32) -> bool {
+        if !held {
+            self.exhausted = false;
+        }
+        let sprint = held && !self.exhausted && self.current > 0.0;
+        if sprint {
+            self.current = (self.current - STAMINA_DRAIN_PER_SECOND * seconds).max(0.0);
+            self.exhausted = self.current == 0.0;
+        } else if self.current < 1.0 {
+            self.current = (self.current + STAMINA_REFILL_PER_SECOND * seconds).min(1.0);
+        }
+        sprint
+    }
+}
+
+fn spawn_stamina_bar(mut commands: Commands, safe_area: Res<SafeArea>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(10.0 + safe_area.bottom),
+                    right: Val::Px(10.0),
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(BAR_HEIGHT),
+                    ..default()
+                },
+                background_color: Color::rgba(1.0, 1.0, 1.0, 0.15).into(),
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.4, 0.85, 1.0).into(),
+                    ..default()
+                },
+                StaminaBarFill,
+            ));
+        });
+}
+
+fn update_stamina_bar(
+    stamina: Res<Stamina>,
+    mut fills: Query<(&mut Style, &mut BackgroundColor), With<StaminaBarFill>>,
+) {
+    if !stamina.is_changed() {
+        return;
+    }
+    for (mut style, mut background) in &mut fills {
+        style.width = Val::Percent(stamina.current * 100.0);
+        // an exhausted bar is grayed out until the key is let go
+        background.0 = if stamina.exhausted {
+            Color::GRAY
+        } else {
+            Color::rgb(0.4, 0.85, 1.0)
+        };
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn add_speed_aura(
+    mut commands: Commands,
+    heads: Query<Entity, Or<(Added<SnakeHead>, Added<Snake>)>>,
+) {
+    for head in &heads {
+        commands.entity(head).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: AURA_COLOR,
+                        custom_size: Some(SEGMENT_SIZE * AURA_SCALE),
+                        ..default()
+                    },
+                    // just behind the head
+                    transform: Transform::from_xyz(0.0, 0.0, -0.1),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                SpeedAura,
+            ));
+        });
+    }
+}
+
+fn show_speed_aura(
+    sprinting: Res<Sprinting>,
+    player_two: Query<Ref<Sprinting>>,
+    mut auras: Query<(&Parent, &mut Visibility), With<SpeedAura>>,
+) {
+    for (head, mut visibility) in &mut auras {
+        let (changed, on) = player_two
+            .get(head.get())
+            .map_or((sprinting.is_changed(), sprinting.0), |sprinting| {
+                (sprinting.is_changed(), sprinting.0)
+            });
+        if !changed {
+            continue;
+        }
+        *visibility = if on {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,441 @@
+use std::{collections::BTreeMap, f32::consts::TAU};
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::Mutators, despawn_screen, display_final_score, menu::MenuItem, new_run,
+    practice::PracticeMode, sim, storage, Ending, GameState, OnGameOverScreen, RunEnd, Scoreboard,
+};
+
+const HISTORY_KEY: &str = "history";
+// a few months of regular play
+const MAX_RUNS: usize = 1000;
+const PLOTTED_RUNS: usize = 40;
+const PLOTTED_WEEKS: usize = 8;
+const LINE_WIDTH: f32 = 3.0;
+const PIE_STEPS: usize = 64;
+const MARGIN: f32 = 40.0;
+const AXIS_COLOR: Color = Color::rgb(0.5, 0.5, 1.0);
+const SLICE_COLORS: [Color; 6] = [
+    Color::rgb(1.0, 0.5, 0.5),
+    Color::rgb(0.5, 0.9, 0.3),
+    Color::rgb(0.3, 0.7, 1.0),
+    Color::rgb(1.0, 0.8, 0.3),
+    Color::rgb(0.8, 0.5, 1.0),
+    Color::rgb(0.9, 0.9, 0.9),
+];
+
+/// Keeps a history of finished runs and charts it on a screen opened with Z
+/// from the game-over screen: score per run, what ended the runs, and the
+/// average run length per week. Escape or Space goes back.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunHistory::load())
+            .init_resource::<RunClock>()
+            .add_systems(OnEnter(GameState::Playing), reset_run_clock.run_if(new_run))
+            .add_systems(Update, tick_run_clock.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    record_run.before(display_final_score),
+                    display_stats_hint.after(display_final_score),
+                ),
+            )
+            .add_systems(Update, open_stats.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnEnter(GameState::Stats), spawn_charts)
+            .add_systems(OnExit(GameState::Stats), despawn_screen::<StatsScreen>)
+            .add_systems(Update, close_stats.run_if(in_state(GameState::Stats)));
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct RunRecord {
+    // local date, YYYY-MM-DD
+    date: String,
+    score: u32,
+    seconds: f32,
+    cause: String,
+    // names of the mutators the run was played with
+    #[serde(default)]
+    mutators: Vec<String>,
+}
+
+/// Finished runs, oldest first.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct RunHistory {
+    runs: Vec<RunRecord>,
+}
+
+impl RunHistory {
+    fn load() -> Self {
+        storage::load_ron(HISTORY_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(HISTORY_KEY, self);
+    }
+}
+
+/// Seconds spent playing the current run, pauses left out.
+#[derive(Resource, Default)]
+struct RunClock(f32);
+
+#[derive(Component)]
+struct StatsScreen;
+
+fn reset_run_clock(mut clock: ResMut<RunClock>) {
+    clock.0 = 0.0;
+}
+
+fn tick_run_clock(time: Res<Time>, mut clock: ResMut<RunClock>) {
+    clock.0 += time.delta_seconds();
+}
+
+fn record_run(
+    run_end: Res<RunEnd>,
+    practice: Res<PracticeMode>,
+    mutators: Res<Mutators>,
+    scoreboard: Res<Scoreboard>,
+    clock: Res<RunClock>,
+    mut history: ResMut<RunHistory>,
+) {
+    // coming back from the credits or help re-enters game over without a new run
+    let Some(ending) = run_end.unrecorded() else {
+        return;
+    };
+    if practice.is_active() {
+        return;
+    }
+    history.runs.push(RunRecord {
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        score: scoreboard.score,
+        seconds: clock.0,
+        cause: match ending {
+            Ending::Died(kind) => format!("{kind:?}"),
+            ending => format!("{ending:?}"),
+        },
+        mutators: mutators.names().into_iter().map(String::from).collect(),
+    });
+    let overflow = history.runs.len().saturating_sub(MAX_RUNS);
+    history.runs.drain(..overflow);
+    history.save();
+}
+
+fn display_stats_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Press Z for stats",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(195.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        MenuItem(KeyCode::KeyZ),
+        OnGameOverScreen,
+    ));
+}
+
+fn open_stats(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        next_state.set(GameState::Stats);
+    }
+}
+
+fn close_stats(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::Space]) {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn spawn_charts(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    history: Res<RunHistory>,
+) {
+    let arena = sim::arena();
+    let center = (arena.min + arena.max) / 2.0;
+    if history.runs.is_empty() {
+        spawn_label(&mut commands, "No runs recorded yet", center, 24.0);
+        return;
+    }
+
+    // scores top left, causes top right, weeks along the bottom
+    let scores_area = Rect::new(
+        arena.min.x + MARGIN,
+        center.y + MARGIN,
+        center.x - MARGIN,
+        arena.max.y - MARGIN,
+    );
+    let pie_radius = (arena.max - center).min_element() / 2.0 - MARGIN;
+    let pie_center = Vec2::new(
+        (center.x + arena.max.x) / 2.0,
+        (center.y + arena.max.y) / 2.0,
+    );
+    let weeks_area = Rect::new(
+        arena.min.x + MARGIN,
+        arena.min.y + MARGIN,
+        arena.max.x - MARGIN,
+        center.y - MARGIN,
+    );
+
+    spawn_score_chart(&mut commands, &history.runs, scores_area);
+    spawn_cause_chart(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &history.runs,
+        pie_center,
+        pie_radius,
+    );
+    spawn_week_chart(&mut commands, &history.runs, weeks_area);
+}
+
+fn spawn_score_chart(commands: &mut Commands, runs: &[RunRecord], area: Rect) {
+    let runs = &runs[runs.len().saturating_sub(PLOTTED_RUNS)..];
+    let best = runs.iter().map(|run| run.score).max().unwrap_or(0).max(1);
+    let points: Vec<Vec2> = runs
+        .iter()
+        .enumerate()
+        .map(|(index, run)| {
+            let x = if runs.len() > 1 {
+                index as f32 / (runs.len() - 1) as f32
+            } else {
+                0.5
+            };
+            let y = run.score as f32 / best as f32;
+            area.min + Vec2::new(x, y) * area.size()
+        })
+        .collect();
+
+    spawn_axes(commands, area);
+    for pair in points.windows(2) {
+        spawn_line(commands, pair[0], pair[1], SLICE_COLORS[1]);
+    }
+    if let &[point] = points.as_slice() {
+        spawn_line(
+            commands,
+            point,
+            point + Vec2::X * LINE_WIDTH,
+            SLICE_COLORS[1],
+        );
+    }
+    spawn_label(
+        commands,
+        &format!("Score, last {} runs (best {best})", runs.len()),
+        Vec2::new(area.center().x, area.max.y + MARGIN / 2.0),
+        18.0,
+    );
+}
+
+fn spawn_cause_chart(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    runs: &[RunRecord],
+    center: Vec2,
+    radius: f32,
+) {
+    let mut causes: BTreeMap<&str, u32> = BTreeMap::new();
+    for run in runs {
+        *causes.entry(run.cause.as_str()).or_default() += 1;
+    }
+
+    let mut start = 0.0;
+    let mut legend = Vec::new();
+    for (index, (cause, count)) in causes.iter().enumerate() {
+        let color = SLICE_COLORS[index % SLICE_COLORS.len()];
+        let end = start + TAU * *count as f32 / runs.len() as f32;
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(pie_slice(radius, start, end))),
+                material: materials.add(color),
+                transform: Transform::from_translation(center.extend(0.0)),
+                ..default()
+            },
+            StatsScreen,
+        ));
+        legend.push(TextSection::new(
+            format!("{cause} {count}  "),
+            TextStyle {
+                font_size: 16.0,
+                color,
+                ..default()
+            },
+        ));
+        start = end;
+    }
+
+    spawn_label(
+        commands,
+        "Death causes",
+        center + Vec2::Y * (radius + MARGIN / 2.0),
+        18.0,
+    );
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections(legend),
+            transform: Transform::from_translation(
+                (center - Vec2::Y * (radius + 15.0)).extend(1.0),
+            ),
+            ..default()
+        },
+        StatsScreen,
+    ));
+}
+
+fn spawn_week_chart(commands: &mut Commands, runs: &[RunRecord], area: Rect) {
+    // (iso year, iso week) -> (total seconds, runs)
+    let mut weeks: BTreeMap<(i32, u32), (f32, u32)> = BTreeMap::new();
+    for run in runs {
+        let Ok(date) = NaiveDate::parse_from_str(&run.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let week = date.iso_week();
+        let entry = weeks.entry((week.year(), week.week())).or_default();
+        entry.0 += run.seconds;
+        entry.1 += 1;
+    }
+    let averages: Vec<((i32, u32), f32)> = weeks
+        .into_iter()
+        .map(|(week, (total, count))| (week, total / count as f32))
+        .collect();
+    let averages = &averages[averages.len().saturating_sub(PLOTTED_WEEKS)..];
+    let longest = averages
+        .iter()
+        .map(|(_, average)| *average)
+        .fold(1.0, f32::max);
+
+    spawn_axes(commands, area);
+    let slot = area.width() / averages.len().max(1) as f32;
+    for (index, ((_, week), average)) in averages.iter().enumerate() {
+        let height = (average / longest * area.height()).max(1.0);
+        let x = area.min.x + slot * (index as f32 + 0.5);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: SLICE_COLORS[2],
+                    custom_size: Some(Vec2::new(slot * 0.6, height)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, area.min.y + height / 2.0, 0.0),
+                ..default()
+            },
+            StatsScreen,
+        ));
+        spawn_label(
+            commands,
+            &format!("{average:.0}s"),
+            Vec2::new(x, area.min.y + height + 10.0),
+            14.0,
+        );
+        spawn_label(
+            commands,
+            &format!("W{week}"),
+            Vec2::new(x, area.min.y - 12.0),
+            14.0,
+        );
+    }
+    spawn_label(
+        commands,
+        "Average run length per week",
+        Vec2::new(area.center().x, area.max.y + MARGIN / 2.0),
+        18.0,
+    );
+}
+
+/// A filled circle sector between two angles, in radians counterclockwise from +x.
+fn pie_slice(radius: f32, start: f32, end: f32) -> Mesh {
+    let steps = ((end - start) / TAU * PIE_STEPS as f32).ceil().max(1.0) as u32;
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    positions.extend((0..=steps).map(|step| {
+        let angle = start + (end - start) * step as f32 / steps as f32;
+        [radius * angle.cos(), radius * angle.sin(), 0.0]
+    }));
+    let indices = (1..=steps).flat_map(|step| [0, step, step + 1]).collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn spawn_axes(commands: &mut Commands, area: Rect) {
+    spawn_line(
+        commands,
+        area.min,
+        Vec2::new(area.min.x, area.max.y),
+        AXIS_COLOR,
+    );
+    spawn_line(
+        commands,
+        area.min,
+        Vec2::new(area.max.x, area.min.y),
+        AXIS_COLOR,
+    );
+}
+
+fn spawn_line(commands: &mut Commands, from: Vec2, to: Vec2, color: Color) {
+    let delta = to - from;
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(delta.length(), LINE_WIDTH)),
+                ..default()
+            },
+            transform: Transform {
+                translation: ((from + to) / 2.0).extend(0.5),
+                rotation: Quat::from_rotation_z(delta.y.atan2(delta.x)),
+                ..default()
+            },
+            ..default()
+        },
+        StatsScreen,
+    ));
+}
+
+fn spawn_label(commands: &mut Commands, text: &str, position: Vec2, font_size: f32) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font_size,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..default()
+        },
+        StatsScreen,
+    ));
+}
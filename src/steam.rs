@@ -0,0 +1,392 @@
+use std::{
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+use steamworks::{
+    Client, LeaderboardDisplayType, LeaderboardSortMethod, SingleClient, UploadScoreMethod,
+};
+
+use crate::{
+    accessibility::RunSpeed,
+    challenge::{merge_weekly_best, ActiveChallenge, WEEKLY_BEST_KEY},
+    display_final_score,
+    hardcore::HardcoreMode,
+    practice::PracticeMode,
+    progression::{Progression, PROGRESSION_KEY},
+    settings::{save_settings, SETTINGS_KEY},
+    storage,
+    toast::Toast,
+    GameState, OnGameOverScreen, RunEnd, Scoreboard,
+};
+
+// Valve's public test app until the game has its own id; a steam_appid.txt next
+// to the executable takes precedence
+const STEAM_APP_ID: u32 = 480;
+const SYNCED_KEYS: [&str; 3] = [PROGRESSION_KEY, SETTINGS_KEY, WEEKLY_BEST_KEY];
+const BEST_RUN_LEADERBOARD: &str = "best_run";
+const HARDCORE_LEADERBOARD: &str = "hardcore";
+const SUBMISSIONS_KEY: &str = "leaderboard_queue";
+// retries wait 1, 2, 4... minutes, and a score is given up on after the last
+const RETRY_BASE_SECONDS: i64 = 60;
+const MAX_ATTEMPTS: u32 = 6;
+const MAX_FAILED_SHOWN: usize = 20;
+
+/// Connects to a running Steam client and pulls newer saves from Steam Cloud.
+/// Returns why not when Steam isn't available, in which case the game runs
+/// without it. The `SingleClient` has to stay on the main thread, so it goes
+/// into the app as a non-send resource rather than through the plugin.
+pub fn init() -> Result<(SteamPlugin, SingleClient), String> {
+    let (client, single) = Client::init_app(STEAM_APP_ID).map_err(|err| err.to_string())?;
+    pull_saves(&client);
+    Ok((SteamPlugin { client }, single))
+}
+
+/// Steam achievements and leaderboards fed from progression and run scores,
+/// plus Steam Cloud copies of the saves pushed on exit. Scores go through a
+/// queue on disk and are retried with backoff until Steam takes them.
+pub struct SteamPlugin {
+    client: Client,
+}
+
+impl Plugin for SteamPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Steam(self.client.clone()))
+            .insert_resource(SubmissionQueue::load())
+            .init_resource::<SubmissionOutcomes>()
+            .add_systems(First, run_steam_callbacks)
+            .add_systems(Startup, submit_queued_scores)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    (report_run, queue_run_scores).before(display_final_score),
+                    submit_queued_scores.after(queue_run_scores),
+                    display_submission_status.after(display_final_score),
+                ),
+            )
+            .add_systems(Update, collect_submission_outcomes)
+            .add_systems(Last, push_saves_on_exit.after(save_settings));
+    }
+}
+
+/// Stands in for `SteamPlugin` when Steam isn't running: run scores are
+/// queued on disk and submitted on a later launch with Steam. `init` fails
+/// before logging is up, so why it did is passed on to be reported here.
+pub struct OfflineScoresPlugin {
+    pub reason: String,
+}
+
+impl Plugin for OfflineScoresPlugin {
+    fn build(&self, app: &mut App) {
+        warn!("steam unavailable: {}", self.reason);
+        app.insert_resource(SubmissionQueue::load())
+            .add_systems(Startup, announce_offline)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    queue_run_scores.before(display_final_score),
+                    display_submission_status.after(display_final_score),
+                ),
+            );
+    }
+}
+
+fn announce_offline(mut toasts: EventWriter<Toast>) {
+    toasts.send(Toast(
+        "Steam isn't running, scores are kept for the next launch with it".to_string(),
+    ));
+}
+
+#[derive(Resource)]
+struct Steam(Client);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Submission {
+    id: u64,
+    leaderboard: String,
+    score: i32,
+    details: Vec<i32>,
+    attempts: u32,
+    // unix seconds
+    retry_at: i64,
+    #[serde(skip)]
+    in_flight: bool,
+}
+
+/// Leaderboard scores waiting for Steam, and the ones it gave up on.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct SubmissionQueue {
+    pending: Vec<Submission>,
+    failed: Vec<Submission>,
+    next_id: u64,
+}
+
+impl SubmissionQueue {
+    fn load() -> Self {
+        storage::load_ron(SUBMISSIONS_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(SUBMISSIONS_KEY, self);
+    }
+
+    fn push(&mut self, leaderboard: String, score: i32, details: &[i32]) {
+        self.pending.push(Submission {
+            id: self.next_id,
+            leaderboard,
+            score,
+            details: details.to_vec(),
+            attempts: 0,
+            retry_at: 0,
+            in_flight: false,
+        });
+        self.next_id += 1;
+    }
+}
+
+/// Upload results by submission id, filled in from Steam's callbacks.
+#[derive(Resource, Default)]
+struct SubmissionOutcomes(Arc<Mutex<Vec<(u64, bool)>>>);
+
+fn run_steam_callbacks(single: NonSend<SingleClient>) {
+    single.run_callbacks();
+}
+
+// achievement ids as set up on the Steamworks partner site
+fn earned_achievements(progression: &Progression, score: u32) -> Vec<&'static str> {
+    [
+        ("SCORE_50", score >= 50),
+        ("MISSIONS_10", progression.missions_completed >= 10),
+        ("PRESTIGE_1", progression.prestige >= 1),
+    ]
+    .into_iter()
+    .filter_map(|(id, earned)| earned.then_some(id))
+    .collect()
+}
+
+fn report_run(
+    steam: Res<Steam>,
+    scoreboard: Res<Scoreboard>,
+    progression: Res<Progression>,
+    practice: Res<PracticeMode>,
+) {
+    if practice.is_active() {
+        return;
+    }
+    let stats = steam.0.user_stats();
+    for id in earned_achievements(&progression, scoreboard.score) {
+        let _ = stats.achievement(id).set();
+    }
+    let _ = stats.store_stats();
+}
+
+fn queue_run_scores(
+    scoreboard: Res<Scoreboard>,
+    challenge: Res<ActiveChallenge>,
+    practice: Res<PracticeMode>,
+    hardcore: Res<HardcoreMode>,
+    speed: Res<RunSpeed>,
+    run_end: Res<RunEnd>,
+    mut queue: ResMut<SubmissionQueue>,
+) {
+    // coming back from the credits or help re-enters game over without a new run
+    if practice.is_active() || run_end.unrecorded().is_none() {
+        return;
+    }
+    let score = scoreboard.score as i32;
+    // other players see these details, so runs at a non-default game speed are marked
+    let details = if speed.adjusted() {
+        let (slowest, fastest) = speed.range();
+        vec![(slowest * 100.0) as i32, (fastest * 100.0) as i32]
+    } else {
+        Vec::new()
+    };
+    queue.push(BEST_RUN_LEADERBOARD.to_string(), score, &details);
+    if let Some(week) = challenge.week_label() {
+        queue.push(format!("weekly_{week}"), score, &details);
+    }
+    if hardcore.counts() {
+        queue.push(HARDCORE_LEADERBOARD.to_string(), score, &details);
+    }
+    queue.save();
+}
+
+// runs on launch and after every run, sending whatever is due for a retry
+fn submit_queued_scores(
+    steam: Res<Steam>,
+    outcomes: Res<SubmissionOutcomes>,
+    mut queue: ResMut<SubmissionQueue>,
+) {
+    let now = chrono::Utc::now().timestamp();
+    for submission in &mut queue.pending {
+        if submission.in_flight || submission.retry_at > now {
+            continue;
+        }
+        submission.in_flight = true;
+        let (id, outcomes) = (submission.id, outcomes.0.clone());
+        upload_score(
+            &steam.0,
+            submission.leaderboard.clone(),
+            submission.score,
+            &submission.details,
+            move |uploaded| outcomes.lock().unwrap().push((id, uploaded)),
+        );
+    }
+}
+
+fn collect_submission_outcomes(
+    outcomes: Res<SubmissionOutcomes>,
+    mut queue: ResMut<SubmissionQueue>,
+) {
+    let finished: Vec<(u64, bool)> = outcomes.0.lock().unwrap().drain(..).collect();
+    if finished.is_empty() {
+        return;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for (id, uploaded) in finished {
+        let Some(index) = queue.pending.iter().position(|pending| pending.id == id) else {
+            continue;
+        };
+        if uploaded {
+            queue.pending.remove(index);
+            continue;
+        }
+        let submission = &mut queue.pending[index];
+        submission.in_flight = false;
+        submission.attempts += 1;
+        submission.retry_at = now + RETRY_BASE_SECONDS * (1 << (submission.attempts - 1));
+        if submission.attempts >= MAX_ATTEMPTS {
+            let submission = queue.pending.remove(index);
+            warn!(
+                "gave up submitting {} to steam leaderboard {}",
+                submission.score, submission.leaderboard
+            );
+            queue.failed.push(submission);
+            let overflow = queue.failed.len().saturating_sub(MAX_FAILED_SHOWN);
+            queue.failed.drain(..overflow);
+        }
+    }
+    queue.save();
+}
+
+fn display_submission_status(mut commands: Commands, queue: Res<SubmissionQueue>) {
+    let mut lines = Vec::new();
+    if !queue.pending.is_empty() {
+        lines.push(format!(
+            "{} leaderboard score(s) waiting for Steam",
+            queue.pending.len()
+        ));
+    }
+    if !queue.failed.is_empty() {
+        lines.push(format!(
+            "{} leaderboard score(s) couldn't be submitted",
+            queue.failed.len()
+        ));
+    }
+    if lines.is_empty() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            lines.join("\n"),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.5, 0.5, 1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(145.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        OnGameOverScreen,
+    ));
+}
+
+fn upload_score(
+    client: &Client,
+    leaderboard: String,
+    score: i32,
+    details: &[i32],
+    on_done: impl FnOnce(bool) + Send + 'static,
+) {
+    let uploader = client.clone();
+    let details = details.to_vec();
+    client.user_stats().find_or_create_leaderboard(
+        &leaderboard,
+        LeaderboardSortMethod::Descending,
+        LeaderboardDisplayType::Numeric,
+        move |found| match found {
+            Ok(Some(board)) => uploader.user_stats().upload_leaderboard_score(
+                &board,
+                UploadScoreMethod::KeepBest,
+                score,
+                &details,
+                move |uploaded| on_done(uploaded.is_ok()),
+            ),
+            Ok(None) => {
+                warn!("steam leaderboard {leaderboard} not found");
+                on_done(false);
+            }
+            Err(err) => {
+                warn!("steam leaderboard {leaderboard} unavailable: {err}");
+                on_done(false);
+            }
+        },
+    );
+}
+
+fn cloud_file_name(key: &str) -> String {
+    format!("{key}.ron")
+}
+
+// the newer copy wins, except weekly bests, which are merged
+fn pull_saves(client: &Client) {
+    let remote_storage = client.remote_storage();
+    for key in SYNCED_KEYS {
+        let file = remote_storage.file(&cloud_file_name(key));
+        if !file.exists() {
+            continue;
+        }
+        let mut contents = String::new();
+        if file.read().read_to_string(&mut contents).is_err() {
+            continue;
+        }
+
+        let local = storage::load_raw(key);
+        let local_modified = storage::modified(key)
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs() as i64);
+        let merged = match &local {
+            Some(local) if key == WEEKLY_BEST_KEY => merge_weekly_best(local, &contents),
+            _ => None,
+        };
+        if let Some(merged) = merged {
+            storage::save_raw(key, &merged);
+        } else if local.is_none() || file.timestamp() > local_modified {
+            storage::save_raw(key, &contents);
+        }
+    }
+}
+
+fn push_saves_on_exit(mut exit_events: EventReader<AppExit>, steam: Res<Steam>) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+    let remote_storage = steam.0.remote_storage();
+    for key in SYNCED_KEYS {
+        let Some(contents) = storage::load_raw(key) else {
+            continue;
+        };
+        let mut writer = remote_storage.file(&cloud_file_name(key)).write();
+        if let Err(err) = writer.write_all(contents.as_bytes()) {
+            warn!("steam cloud write of {key} failed: {err}");
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use bevy::log::warn;
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(all(
+    any(feature = "cloud-sync", feature = "steam"),
+    not(target_arch = "wasm32")
+))]
+pub use backend::modified;
+
+// loads happen before the app exists, so failures wait here for the error screen
+static UNREADABLE: Mutex<Vec<UnreadableSave>> = Mutex::new(Vec::new());
+// headless runs play by the defaults and leave nothing behind
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// From here on, every load finds nothing and every save is dropped, so the
+/// process plays the same on any machine and keeps none of its runs.
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+fn enabled() -> bool {
+    !DISABLED.load(Ordering::Relaxed)
+}
+
+/// Platform data directory, or `None` on targets without a filesystem.
+pub fn data_dir() -> Option<PathBuf> {
+    enabled().then(backend::data_dir).flatten()
+}
+
+pub fn load_raw(key: &str) -> Option<String> {
+    enabled().then(|| backend::load(key)).flatten()
+}
+
+#[cfg(all(
+    any(feature = "cloud-sync", feature = "steam"),
+    not(target_arch = "wasm32")
+))]
+pub fn save_raw(key: &str, contents: &str) {
+    if enabled() {
+        backend::save(key, contents);
+    }
+}
+
+/// Saved data that failed to parse and was replaced by defaults.
+#[derive(Debug, Clone)]
+pub struct UnreadableSave {
+    pub key: String,
+    pub error: String,
+}
+
+impl UnreadableSave {
+    /// Moves the data aside to `<key>.bak` so it can be recovered by hand.
+    pub fn back_up(&self) {
+        if let Some(contents) = backend::load(&self.key) {
+            backend::save(&format!("{}.bak", self.key), &contents);
+        }
+        backend::remove(&self.key);
+    }
+
+    pub fn discard(&self) {
+        backend::remove(&self.key);
+    }
+}
+
+pub fn load_ron<T: DeserializeOwned>(key: &str) -> Option<T> {
+    parse_ron(key, &load_raw(key)?)
+}
+
+/// Parses `key`'s data, noting it for the error screen if it can't be read.
+pub fn parse_ron<T: DeserializeOwned>(key: &str, contents: &str) -> Option<T> {
+    match ron::from_str(contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("ignoring unreadable {key} data: {err}");
+            if let Ok(mut unreadable) = UNREADABLE.lock() {
+                if !unreadable.iter().any(|save| save.key == key) {
+                    unreadable.push(UnreadableSave {
+                        key: key.to_string(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+            None
+        }
+    }
+}
+
+/// Unreadable saves found since the last call, none once storage is
+/// disabled and no saves are read.
+pub fn take_unreadable() -> Vec<UnreadableSave> {
+    if !enabled() {
+        return Vec::new();
+    }
+    UNREADABLE
+        .lock()
+        .map(|mut unreadable| std::mem::take(&mut *unreadable))
+        .unwrap_or_default()
+}
+
+pub fn save_ron<T: Serialize>(key: &str, value: &T) {
+    if !enabled() {
+        return;
+    }
+    match ron::ser::to_string_pretty(value, PrettyConfig::default()) {
+        Ok(contents) => backend::save(key, &contents),
+        Err(err) => warn!("failed to serialize {key} data: {err}"),
+    }
+}
+
+pub fn remove(key: &str) {
+    if enabled() {
+        backend::remove(key);
+    }
+}
+
+/// Files ending in `extension` in a folder under the data directory, sorted by
+/// name so anything loaded from them always loads in the same order.
+#[cfg(all(
+    any(feature = "mods", feature = "scripting"),
+    not(target_arch = "wasm32")
+))]
+pub fn data_files(folder: &str, extension: &str) -> Vec<std::path::PathBuf> {
+    let Some(entries) = data_dir().and_then(|dir| std::fs::read_dir(dir.join(folder)).ok()) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|found| found == extension))
+        .collect();
+    paths.sort();
+    paths
+}
+
+// native builds keep one RON file per key in the platform data directory
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::{fs, path::PathBuf};
+
+    use bevy::log::warn;
+
+    pub fn data_dir() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "snake-bevy")
+            .map(|dirs| dirs.data_dir().to_path_buf())
+    }
+
+    fn path(key: &str) -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(format!("{key}.ron")))
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        fs::read_to_string(path(key)?).ok()
+    }
+
+    pub fn save(key: &str, contents: &str) {
+        let Some(path) = path(key) else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(err) = fs::write(&path, contents) {
+            warn!("failed to write {}: {err}", path.display());
+        }
+    }
+
+    pub fn remove(key: &str) {
+        if let Some(path) = path(key) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[cfg(any(feature = "cloud-sync", feature = "steam"))]
+    pub fn modified(key: &str) -> Option<std::time::SystemTime> {
+        fs::metadata(path(key)?).ok()?.modified().ok()
+    }
+}
+
+// the browser has no filesystem, so web builds use the page's local storage
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    use std::path::PathBuf;
+
+    use bevy::log::warn;
+    use web_sys::Storage;
+
+    pub fn data_dir() -> Option<PathBuf> {
+        None
+    }
+
+    fn local_storage() -> Option<Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    fn storage_key(key: &str) -> String {
+        format!("snake-bevy/{key}")
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        local_storage()?.get_item(&storage_key(key)).ok()?
+    }
+
+    pub fn save(key: &str, contents: &str) {
+        let Some(storage) = local_storage() else {
+            return;
+        };
+        if storage.set_item(&storage_key(key), contents).is_err() {
+            warn!("failed to write {key} to local storage");
+        }
+    }
+
+    pub fn remove(key: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(&storage_key(key));
+        }
+    }
+}
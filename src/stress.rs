@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use bevy::{math::bounding::Aabb2d, prelude::*};
+
+use crate::{
+    apple_spawn_free, arena::WallThickness, console::RegisterConsoleCommand, obstacle_spawn_at,
+    pool::EntityPool, sim, snake_segment_spawn, toast::Toast, GameAssets, GameRng, GameState,
+    SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+const CLI_FLAG: &str = "--stress";
+const DEFAULT_SEGMENTS: usize = 10_000;
+const DEFAULT_APPLES: usize = 300;
+const DEFAULT_OBSTACLES: usize = 200;
+const MEASURE_SECONDS: f32 = 10.0;
+// obstacles stay off the head so the run survives the first move
+const HEAD_CLEARANCE: f32 = 100.0;
+const PERCENTILES: [f32; 4] = [50.0, 90.0, 95.0, 99.0];
+
+/// Developer load test, started with `stress` in the console or by passing
+/// `--stress` on the command line: once a run is going it gets a snake of
+/// ten thousand segments, hundreds of apples and obstacles, then frame
+/// times are recorded for a few seconds and their percentiles logged. Only
+/// built into debug profiles, like the console.
+pub struct StressTestPlugin;
+
+impl Plugin for StressTestPlugin {
+    fn build(&self, app: &mut App) {
+        if std::env::args().any(|arg| arg == CLI_FLAG) {
+            app.insert_resource(StressLoad::default());
+        }
+        app.register_console_command(
+            "stress",
+            "stress [segments] [apples] [obstacles]",
+            stress_command,
+        )
+        .add_systems(
+            Update,
+            (
+                spawn_stress_load.run_if(resource_exists::<StressLoad>),
+                record_frame_times.run_if(resource_exists::<FrameTimes>),
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// A load waiting for a run to be spawned into.
+#[derive(Resource, Debug, Clone, Copy)]
+struct StressLoad {
+    segments: usize,
+    apples: usize,
+    obstacles: usize,
+}
+
+impl Default for StressLoad {
+    fn default() -> Self {
+        StressLoad {
+            segments: DEFAULT_SEGMENTS,
+            apples: DEFAULT_APPLES,
+            obstacles: DEFAULT_OBSTACLES,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct FrameTimes {
+    load: StressLoad,
+    samples: Vec<Duration>,
+    timer: Timer,
+}
+
+fn stress_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let defaults = StressLoad::default();
+    let count = |index: usize, default: usize| match args.get(index) {
+        Some(count) => count
+            .parse()
+            .map_err(|_| format!("'{count}' is not a count")),
+        None => Ok(default),
+    };
+    let load = StressLoad {
+        segments: count(0, defaults.segments)?,
+        apples: count(1, defaults.apples)?,
+        obstacles: count(2, defaults.obstacles)?,
+    };
+    world.insert_resource(load);
+    Ok(format!(
+        "stress test: {} segments, {} apples, {} obstacles once a run is going",
+        load.segments, load.apples, load.obstacles
+    ))
+}
+
+fn spawn_stress_load(
+    mut commands: Commands,
+    load: Res<StressLoad>,
+    game_assets: Res<GameAssets>,
+    wall_thickness: Res<WallThickness>,
+    mut rng: ResMut<GameRng>,
+    mut segment_pool: ResMut<EntityPool<SnakeBodySegment>>,
+    heads: Query<&Transform, With<SnakeHead>>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let head = head.translation;
+
+    // new segments gather under the head and trail out as it moves
+    for _ in 0..load.segments {
+        snake_segment_spawn(
+            &mut commands,
+            &game_assets,
+            &mut segment_pool,
+            head.x,
+            head.y,
+        );
+    }
+    // off the snake and out of each other's way, like any other apple
+    let mut occupied = vec![Aabb2d::new(head.truncate(), SEGMENT_SIZE / 2.0)];
+    for _ in 0..load.apples {
+        let Some(cell) = apple_spawn_free(
+            &mut commands,
+            &game_assets,
+            &mut rng,
+            wall_thickness.0,
+            &occupied,
+        ) else {
+            break;
+        };
+        occupied.push(Aabb2d::new(cell, SEGMENT_SIZE / 2.0));
+    }
+    let mut placed = 0;
+    while placed < load.obstacles {
+        let position = sim::random_apple_position(&mut rng.0, wall_thickness.0);
+        let cell = (position / SEGMENT_SIZE).round() * SEGMENT_SIZE;
+        if cell.distance(head.truncate()) > HEAD_CLEARANCE {
+            obstacle_spawn_at(&mut commands, cell);
+            placed += 1;
+        }
+    }
+
+    info!(
+        "stress test: spawned {} segments, {} apples, {} obstacles",
+        load.segments, load.apples, load.obstacles
+    );
+    commands.insert_resource(FrameTimes {
+        load: *load,
+        samples: Vec::new(),
+        timer: Timer::from_seconds(MEASURE_SECONDS, TimerMode::Once),
+    });
+    commands.remove_resource::<StressLoad>();
+}
+
+/// The sample at or just above the `percentile` of `sorted`.
+fn percentile(sorted: &[Duration], percentile: f32) -> Duration {
+    let index = ((sorted.len() - 1) as f32 * percentile / 100.0).ceil() as usize;
+    sorted[index]
+}
+
+fn record_frame_times(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut frame_times: ResMut<FrameTimes>,
+    mut toasts: EventWriter<Toast>,
+) {
+    frame_times.samples.push(time.delta());
+    if !frame_times.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    commands.remove_resource::<FrameTimes>();
+
+    let mut samples = std::mem::take(&mut frame_times.samples);
+    samples.sort();
+    let mut report: Vec<String> = PERCENTILES
+        .iter()
+        .map(|&p| {
+            format!(
+                "p{p:.0} {:.2}ms",
+                percentile(&samples, p).as_secs_f64() * 1000.0
+            )
+        })
+        .collect();
+    if let Some(max) = samples.last() {
+        report.push(format!("max {:.2}ms", max.as_secs_f64() * 1000.0));
+    }
+    let load = frame_times.load;
+    info!(
+        "stress test with {} segments, {} apples, {} obstacles over {} frames: {}",
+        load.segments,
+        load.apples,
+        load.obstacles,
+        samples.len(),
+        report.join(", ")
+    );
+    toasts.send(Toast(format!("Stress test: {}", report.join(", "))));
+}
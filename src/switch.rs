@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::KeyBindings, settings::GameSettings, toast::Toast, Direction, GameState, OnGameScreen,
+    SnakeHead,
+};
+
+const PREVIEW_DISTANCE: f32 = 30.0;
+
+/// One-switch play for assistive hardware, toggled with F2. The snake keeps
+/// moving on its own, and the confirm key or a click turns it clockwise; an
+/// arrow next to the head shows where the next press will send it.
+pub struct SingleSwitchPlugin;
+
+impl Plugin for SingleSwitchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SwitchHeading>()
+            .add_systems(Update, toggle_single_switch)
+            .add_systems(
+                Update,
+                (cycle_heading, update_preview_arrow)
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and_then(single_switch_enabled)),
+            )
+            .add_systems(OnExit(GameState::GameOver), reset_heading);
+    }
+}
+
+/// Heading the snake follows while single-switch input is on.
+#[derive(Resource)]
+pub struct SwitchHeading(pub Direction);
+
+impl Default for SwitchHeading {
+    fn default() -> Self {
+        // the snake starts out with its body trailing below the head
+        SwitchHeading(Direction::Up)
+    }
+}
+
+#[derive(Component)]
+struct PreviewArrow;
+
+fn single_switch_enabled(settings: Res<GameSettings>) -> bool {
+    settings.accessibility.single_switch
+}
+
+fn toggle_single_switch(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        settings.accessibility.single_switch = !settings.accessibility.single_switch;
+        toasts.send(Toast(if settings.accessibility.single_switch {
+            "Single-switch input on".to_string()
+        } else {
+            "Single-switch input off".to_string()
+        }));
+    }
+}
+
+fn cycle_heading(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    bindings: Res<KeyBindings>,
+    state: Res<State<GameState>>,
+    mut heading: ResMut<SwitchHeading>,
+) {
+    // the press that restarted or resumed the run isn't a turn
+    if state.is_changed() {
+        return;
+    }
+    if keyboard_input.just_pressed(bindings.confirm) || mouse_input.just_pressed(MouseButton::Left)
+    {
+        heading.0 = heading.0.clockwise();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_preview_arrow(
+    mut commands: Commands,
+    heading: Res<SwitchHeading>,
+    snake_heads: Query<&Transform, With<SnakeHead>>,
+    mut arrows: Query<(&mut Transform, &mut Text), (With<PreviewArrow>, Without<SnakeHead>)>,
+) {
+    let Ok(head) = snake_heads.get_single() else {
+        return;
+    };
+    let next = heading.0.clockwise();
+    let (offset, glyph) = match next {
+        Direction::Up => (Vec2::Y, "^"),
+        Direction::Right => (Vec2::X, ">"),
+        Direction::Down => (Vec2::NEG_Y, "v"),
+        Direction::Left => (Vec2::NEG_X, "<"),
+    };
+    let translation = (head.translation.truncate() + offset * PREVIEW_DISTANCE).extend(1.0);
+
+    if let Ok((mut transform, mut text)) = arrows.get_single_mut() {
+        transform.translation = translation;
+        if text.sections[0].value != glyph {
+            text.sections[0].value = glyph.to_string();
+        }
+        return;
+    }
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                glyph,
+                TextStyle {
+                    font_size: 28.0,
+                    color: Color::rgb(1.0, 0.8, 0.3),
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        PreviewArrow,
+        OnGameScreen,
+    ));
+}
+
+fn reset_heading(mut heading: ResMut<SwitchHeading>) {
+    *heading = SwitchHeading::default();
+}
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::ActiveChallenge, display_final_score, hardcore::HardcoreMode,
+    practice::PracticeMode, settings::GameSettings, storage, toast::Toast, GameState, RunEnd,
+    Scoreboard,
+};
+
+const QUEUE_KEY: &str = "telemetry";
+const SCORE_BUCKET_SIZE: u32 = 5;
+const RETRY_SECONDS: f32 = 60.0;
+
+/// Opt-in anonymous gameplay aggregates. Each app session adds one report to
+/// a local queue, which is posted to the configured endpoint whenever it can
+/// be reached. F6 switches it on or off.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionReport>()
+            .insert_resource(TelemetryQueue::load())
+            .init_resource::<TelemetryUpload>()
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                record_run.before(display_final_score),
+            )
+            .add_systems(Update, (toggle_telemetry, upload_reports).chain())
+            .add_systems(Last, queue_session_report);
+    }
+}
+
+/// How a run's scores spread out, in buckets of `SCORE_BUCKET_SIZE` apples.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+struct ScoreDistribution {
+    runs: u32,
+    buckets: BTreeMap<u32, u32>,
+}
+
+/// Everything reported about one app session; nothing in it identifies the player.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone)]
+struct SessionReport {
+    session_seconds: f32,
+    modes: BTreeMap<String, ScoreDistribution>,
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct TelemetryQueue {
+    reports: Vec<SessionReport>,
+}
+
+impl TelemetryQueue {
+    fn load() -> Self {
+        storage::load_ron(QUEUE_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save_ron(QUEUE_KEY, self);
+    }
+}
+
+/// The post in flight, with how many queued reports it carries.
+#[derive(Resource)]
+struct TelemetryUpload {
+    task: Option<(Task<bool>, usize)>,
+    retry: Timer,
+}
+
+impl Default for TelemetryUpload {
+    fn default() -> Self {
+        let mut retry = Timer::from_seconds(RETRY_SECONDS, TimerMode::Repeating);
+        // the first attempt happens right away
+        retry.set_elapsed(retry.duration());
+        TelemetryUpload { task: None, retry }
+    }
+}
+
+fn record_run(
+    settings: Res<GameSettings>,
+    challenge: Res<ActiveChallenge>,
+    practice: Res<PracticeMode>,
+    hardcore: Res<HardcoreMode>,
+    run_end: Res<RunEnd>,
+    scoreboard: Res<Scoreboard>,
+    mut report: ResMut<SessionReport>,
+) {
+    // coming back from the credits or help re-enters game over without a new run
+    if !settings.telemetry.enabled || run_end.unrecorded().is_none() {
+        return;
+    }
+
+    let mode = if practice.is_active() {
+        "practice"
+    } else if hardcore.is_active() {
+        "hardcore"
+    } else if challenge.is_active() {
+        "weekly"
+    } else {
+        "classic"
+    };
+    let distribution = report.modes.entry(mode.to_string()).or_default();
+    distribution.runs += 1;
+    let bucket = scoreboard.score / SCORE_BUCKET_SIZE * SCORE_BUCKET_SIZE;
+    *distribution.buckets.entry(bucket).or_default() += 1;
+}
+
+fn toggle_telemetry(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        settings.telemetry.enabled = !settings.telemetry.enabled;
+        toasts.send(Toast(if settings.telemetry.enabled {
+            "Anonymous telemetry on, thanks!".to_string()
+        } else {
+            "Anonymous telemetry off".to_string()
+        }));
+    }
+}
+
+fn upload_reports(
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+    mut queue: ResMut<TelemetryQueue>,
+    mut upload: ResMut<TelemetryUpload>,
+) {
+    if let Some((task, count)) = upload.task.as_mut() {
+        let Some(sent) = block_on(future::poll_once(task)) else {
+            return;
+        };
+        if sent {
+            let count = (*count).min(queue.reports.len());
+            queue.reports.drain(..count);
+            queue.save();
+        }
+        upload.task = None;
+    }
+
+    let telemetry = &settings.telemetry;
+    if !upload.retry.tick(time.delta()).finished()
+        || !telemetry.enabled
+        || telemetry.endpoint.is_empty()
+        || queue.reports.is_empty()
+    {
+        return;
+    }
+
+    let endpoint = telemetry.endpoint.clone();
+    let reports = queue.reports.clone();
+    let count = reports.len();
+    let task = IoTaskPool::get().spawn(async move { post_reports(&endpoint, &reports) });
+    upload.task = Some((task, count));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn post_reports(endpoint: &str, reports: &[SessionReport]) -> bool {
+    match ureq::post(endpoint).send_json(reports) {
+        Ok(_) => true,
+        Err(err) => {
+            // offline or rejected; the reports stay queued for the next attempt
+            debug!("telemetry upload failed: {err}");
+            false
+        }
+    }
+}
+
+// web builds keep their queue in local storage but don't post it yet
+#[cfg(target_arch = "wasm32")]
+fn post_reports(_endpoint: &str, _reports: &[SessionReport]) -> bool {
+    false
+}
+
+fn queue_session_report(
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+    mut exit_events: EventReader<AppExit>,
+    mut report: ResMut<SessionReport>,
+    mut queue: ResMut<TelemetryQueue>,
+) {
+    if exit_events.read().next().is_none() || !settings.telemetry.enabled {
+        return;
+    }
+
+    let mut report = std::mem::take(&mut *report);
+    report.session_seconds = time.elapsed_seconds();
+    queue.reports.push(report);
+    queue.save();
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    settings::GameSettings, toast::Toast, Apple, GameAssets, GameState, OnGameScreen, Scoreboard,
+    SnakeHead,
+};
+
+const CONFETTI_PIECES: usize = 40;
+const CONFETTI_SIZE: f32 = 6.0;
+const CONFETTI_SPEED: f32 = 260.0;
+const CONFETTI_GRAVITY: f32 = -420.0;
+const CONFETTI_SECONDS: f32 = 1.2;
+
+/// How a run looks: fruit size and colors, and the confetti thrown at
+/// milestones. Like `GameRules`, presets are swapped in whole.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Theme>()
+            .register_type::<Theme>()
+            .add_systems(Update, style_new_fruit)
+            .add_systems(
+                Update,
+                (
+                    celebrate_milestones.run_if(resource_changed::<Scoreboard>),
+                    fly_confetti,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct Theme {
+    /// Fruits are drawn, and hit, this many times their normal size.
+    pub fruit_scale: f32,
+    /// Fruits take these colors in turn; apples stay red while it's empty.
+    pub fruit_colors: Vec<Color>,
+    /// Throws confetti every this many apples.
+    pub celebrate_every: Option<u32>,
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Theme {
+            fruit_scale: 1.0,
+            fruit_colors: Vec::new(),
+            celebrate_every: None,
+        }
+    }
+
+    pub fn kids() -> Self {
+        Theme {
+            fruit_scale: 1.6,
+            fruit_colors: vec![
+                Color::rgb(1.0, 0.25, 0.3),
+                Color::rgb(1.0, 0.6, 0.1),
+                Color::rgb(1.0, 0.9, 0.2),
+                Color::rgb(0.6, 0.3, 1.0),
+                Color::rgb(1.0, 0.45, 0.8),
+                Color::rgb(0.2, 0.7, 1.0),
+            ],
+            celebrate_every: Some(5),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+#[derive(Component)]
+struct Confetti {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+fn style_new_fruit(
+    theme: Res<Theme>,
+    game_assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut palette: Local<Vec<Handle<ColorMaterial>>>,
+    // cycled rather than rolled, so the run's seeded rng stays untouched
+    mut next_color: Local<usize>,
+    mut fruits: Query<(&mut Transform, &mut Handle<ColorMaterial>), Added<Apple>>,
+) {
+    if theme.is_changed() {
+        *palette = theme
+            .fruit_colors
+            .iter()
+            .map(|color| {
+                materials.add(ColorMaterial {
+                    color: *color,
+                    texture: Some(game_assets.apple_texture.clone()),
+                })
+            })
+            .collect();
+    }
+    for (mut transform, mut material) in &mut fruits {
+        transform.scale = Vec3::new(theme.fruit_scale, theme.fruit_scale, 1.0);
+        if !palette.is_empty() {
+            *material = palette[*next_color % palette.len()].clone();
+            *next_color += 1;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn celebrate_milestones(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    scoreboard: Res<Scoreboard>,
+    settings: Res<GameSettings>,
+    mut toasts: EventWriter<Toast>,
+    // the last milestone reached, which drops back when a new run starts
+    mut celebrated: Local<u32>,
+    heads: Query<&Transform, With<SnakeHead>>,
+) {
+    let Some(every) = theme.celebrate_every.filter(|every| *every > 0) else {
+        return;
+    };
+    let milestone = scoreboard.score / every;
+    let reached = milestone > *celebrated;
+    *celebrated = milestone;
+    if !reached {
+        return;
+    }
+
+    toasts.send(Toast(format!("{} apples, hooray!", scoreboard.score)));
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let mut rng = thread_rng();
+    let pieces = (CONFETTI_PIECES as f32 * settings.graphics.quality().effect_density()) as usize;
+    for _ in 0..pieces {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let color = if theme.fruit_colors.is_empty() {
+            Color::YELLOW
+        } else {
+            theme.fruit_colors[rng.gen_range(0..theme.fruit_colors.len())]
+        };
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: head.translation.truncate().extend(1.0),
+                    scale: Vec3::new(CONFETTI_SIZE, CONFETTI_SIZE, 1.0),
+                    ..default()
+                },
+                sprite: Sprite { color, ..default() },
+                ..default()
+            },
+            Confetti {
+                velocity: Vec2::from_angle(angle) * CONFETTI_SPEED * rng.gen_range(0.5..1.0),
+                lifetime: Timer::from_seconds(CONFETTI_SECONDS, TimerMode::Once),
+            },
+            OnGameScreen,
+        ));
+    }
+}
+
+fn fly_confetti(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut confetti: Query<(Entity, &mut Transform, &mut Sprite, &mut Confetti)>,
+) {
+    for (entity, mut transform, mut sprite, mut piece) in &mut confetti {
+        if piece.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        piece.velocity.y += CONFETTI_GRAVITY * time.delta_seconds();
+        transform.translation += (piece.velocity * time.delta_seconds()).extend(0.0);
+        sprite.color.set_a(piece.lifetime.fraction_remaining());
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::*};
+
+use crate::{collision::ColliderKind, display_final_score, GameState, OnGameOverScreen, RunEnd};
+
+// how much likelier a tip about what just ended the run is than any other tip
+const MATCHING_WEIGHT: u32 = 6;
+const GENERAL_WEIGHT: u32 = 2;
+const OTHER_WEIGHT: u32 = 1;
+
+// `None` tips apply to any run
+const TIPS: &[(Option<ColliderKind>, &str)] = &[
+    (
+        Some(ColliderKind::Wall),
+        "Press W on the main menu to make the walls wrap around",
+    ),
+    (
+        Some(ColliderKind::Wall),
+        "F7 switches on forgiving hitboxes for walls and poison",
+    ),
+    (
+        Some(ColliderKind::Poison),
+        "Your tail drops poison; avoid doubling back over your own path",
+    ),
+    (
+        Some(ColliderKind::Poison),
+        "Poison fades after a while, so circle around and wait it out",
+    ),
+    (
+        Some(ColliderKind::Rival),
+        "You can only eat rivals shorter than you",
+    ),
+    (
+        Some(ColliderKind::Rival),
+        "Rivals wander at random; give the longer ones room",
+    ),
+    (
+        Some(ColliderKind::Body),
+        "Your own body is solid in this run; leave yourself room to turn",
+    ),
+    (None, "Missions pay coins; press Tab to show them"),
+    (None, "The weekly challenge uses the same seed for everyone"),
+    (None, "Standing in the glowing zone earns bonus points"),
+    (None, "Press H to check the controls and the active rules"),
+];
+
+/// Shows a tip on the game-over screen, favouring ones about what ended the run.
+pub struct TipsPlugin;
+
+impl Plugin for TipsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            display_tip.after(display_final_score),
+        );
+    }
+}
+
+fn tip_weight(tip_cause: Option<ColliderKind>, death_cause: Option<ColliderKind>) -> u32 {
+    match tip_cause {
+        None => GENERAL_WEIGHT,
+        Some(kind) if Some(kind) == death_cause => MATCHING_WEIGHT,
+        Some(_) => OTHER_WEIGHT,
+    }
+}
+
+fn display_tip(mut commands: Commands, run_end: Res<RunEnd>) {
+    let weights = TIPS
+        .iter()
+        .map(|(cause, _)| tip_weight(*cause, run_end.death_cause()));
+    let Ok(index) = WeightedIndex::new(weights) else {
+        return;
+    };
+    // not the game rng, so a seeded run replays the same however the tips fall
+    let tip = TIPS[index.sample(&mut thread_rng())].1;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Tip: {tip}"),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::rgb(1.0, 0.8, 0.3),
+                    ..default()
+                },
+            ));
+        });
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+const TOAST_SECONDS: f32 = 2.5;
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_systems(Update, (show_toasts, expire_toasts));
+    }
+}
+
+/// Short confirmation message shown at the bottom of the screen.
+#[derive(Event)]
+pub struct Toast(pub String);
+
+#[derive(Component)]
+struct ToastMessage(Timer);
+
+fn show_toasts(
+    mut commands: Commands,
+    mut toasts: EventReader<Toast>,
+    visible: Query<Entity, With<ToastMessage>>,
+) {
+    let Some(toast) = toasts.read().last() else {
+        return;
+    };
+
+    for entity in &visible {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(40.0),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ToastMessage(Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                })
+                .with_children(|background| {
+                    background.spawn(TextBundle::from_section(
+                        toast.0.clone(),
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut ToastMessage)>,
+) {
+    for (entity, mut toast) in &mut toasts {
+        if toast.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{Direction, GameState, SafeArea};
+
+// logical pixels a finger has to travel before a drag counts as a swipe
+const SWIPE_THRESHOLD: f32 = 30.0;
+const DPAD_BUTTON_SIZE: f32 = 72.0;
+const DPAD_BUTTON_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+
+/// Touch controls for phones and browsers: a swipe turns the snake the way
+/// it went, and a finger kept down can swipe again without lifting, each
+/// swipe measured from where the last one ended. Mobile builds also get an
+/// on-screen d-pad.
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchDirection>().add_systems(
+            Update,
+            update_touch_direction.run_if(in_state(GameState::Playing)),
+        );
+
+        if cfg!(any(target_os = "android", target_os = "ios")) {
+            app.add_systems(
+                OnEnter(GameState::Playing),
+                spawn_dpad.run_if(not(any_with_component::<DpadButton>)),
+            );
+        }
+    }
+}
+
+/// Direction currently held through a swipe or the on-screen d-pad.
+#[derive(Resource, Default, PartialEq)]
+pub struct TouchDirection(pub Option<Direction>);
+
+#[derive(Component)]
+struct DpadButton(Direction);
+
+/// One finger's swipes: where the next one is measured from and the way the
+/// last one went.
+struct Swipe {
+    anchor: Vec2,
+    direction: Option<Direction>,
+}
+
+fn swipe_direction(drag: Vec2) -> Direction {
+    // screen space grows downwards
+    if drag.x.abs() > drag.y.abs() {
+        if drag.x > 0.0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        }
+    } else if drag.y > 0.0 {
+        Direction::Down
+    } else {
+        Direction::Up
+    }
+}
+
+fn update_touch_direction(
+    touches: Res<Touches>,
+    dpad_buttons: Query<(&Interaction, &DpadButton)>,
+    mut swipes: Local<HashMap<u64, Swipe>>,
+    mut touch_direction: ResMut<TouchDirection>,
+) {
+    let pressed_button = dpad_buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button.0);
+
+    swipes.retain(|&id, _| touches.get_pressed(id).is_some());
+    let mut swiped = None;
+    for touch in touches.iter() {
+        let swipe = swipes.entry(touch.id()).or_insert(Swipe {
+            anchor: touch.start_position(),
+            direction: None,
+        });
+        let drag = touch.position() - swipe.anchor;
+        if drag.length() >= SWIPE_THRESHOLD {
+            swipe.anchor = touch.position();
+            swipe.direction = Some(swipe_direction(drag));
+        }
+        swiped = swiped.or(swipe.direction);
+    }
+
+    touch_direction.set_if_neq(TouchDirection(pressed_button.or(swiped)));
+}
+
+fn spawn_dpad(mut commands: Commands, safe_area: Res<SafeArea>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(20.0 + safe_area.bottom),
+                width: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            dpad_button(parent, Direction::Up, "^");
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(DPAD_BUTTON_SIZE),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    dpad_button(row, Direction::Left, "<");
+                    dpad_button(row, Direction::Right, ">");
+                });
+            dpad_button(parent, Direction::Down, "v");
+        });
+}
+
+fn dpad_button(parent: &mut ChildBuilder, direction: Direction, label: &str) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(DPAD_BUTTON_SIZE),
+                    height: Val::Px(DPAD_BUTTON_SIZE),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: DPAD_BUTTON_COLOR.into(),
+                ..default()
+            },
+            DpadButton(direction),
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
@@ -0,0 +1,323 @@
+use bevy::{prelude::*, window::ApplicationLifetime};
+
+use crate::{
+    despawn_screen,
+    input::{key_name, ActionInput, GameAction, KeyBindings},
+    menu::MenuItem,
+    persistence::SAVE_AND_QUIT_KEY,
+    practice::practicing,
+    puzzle::solving_puzzle,
+    sandbox::sandboxing,
+    teardown_game_screen, Ending, GameState, OnGameOverScreen, PauseOverlay, RunEnd, SafeArea,
+    Scoreboard,
+};
+
+// like the other screens, Escape backs out, here to the title screen
+const MAIN_MENU_KEY: KeyCode = KeyCode::Escape;
+const COUNTDOWN_FROM: u32 = 3;
+const COUNTDOWN_STEP_SECONDS: f32 = 0.6;
+
+/// The screens around a run: the pause overlay, brought up with the pause
+/// key or a gamepad's Start, or when the app is suspended, the 3-2-1
+/// countdown that holds the run still after resuming and the game-over
+/// screen with the final score, a win when the snake filled the board, plus
+/// the keys that leave them, to a new run or back to the main menu.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SafeArea>()
+            .add_systems(OnEnter(GameState::Paused), display_pause_overlay)
+            .add_systems(OnExit(GameState::Paused), despawn_screen::<PauseOverlay>)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                display_final_score.after(teardown_game_screen),
+            )
+            .add_systems(
+                OnExit(GameState::GameOver),
+                despawn_screen::<OnGameOverScreen>,
+            )
+            .add_systems(
+                OnTransition {
+                    from: GameState::Paused,
+                    to: GameState::Playing,
+                },
+                start_resume_countdown,
+            )
+            // a bought-back death picks up heading the same way it died
+            .add_systems(
+                OnTransition {
+                    from: GameState::ContinuePrompt,
+                    to: GameState::Playing,
+                },
+                start_resume_countdown,
+            )
+            .add_systems(OnExit(GameState::Playing), end_resume_countdown)
+            .add_systems(Update, pause_on_suspend)
+            .add_systems(
+                Update,
+                (
+                    // those modes already give Escape or P a job of their own
+                    pause_input
+                        .run_if(not(practicing))
+                        .run_if(not(solving_puzzle))
+                        .run_if(not(sandboxing)),
+                    tick_resume_countdown,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, resume_input.run_if(in_state(GameState::Paused)))
+            .add_systems(
+                Update,
+                gameover_keyboard_input.run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+/// Holds the run still after a resume, counting down until it moves again.
+#[derive(Component)]
+struct ResumeCountdown(Timer);
+
+pub fn display_final_score(
+    mut commands: Commands,
+    mut scoreboard: ResMut<Scoreboard>,
+    bindings: Res<KeyBindings>,
+    run_end: Res<RunEnd>,
+) {
+    let (title, title_color) = if run_end.ending == Some(Ending::Won) {
+        ("You Win!", Color::rgb(1.0, 0.84, 0.0))
+    } else {
+        ("Game Over", Color::RED)
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceEvenly,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: 80.0,
+                    color: title_color,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!("Total apples eaten: {}", scoreboard.score),
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("Press {} to restart", key_name(bindings.confirm)),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::rgb(0.5, 0.5, 1.0),
+                        ..default()
+                    },
+                ),
+                MenuItem(bindings.confirm),
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("Press {} for the main menu", key_name(MAIN_MENU_KEY)),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::rgb(0.5, 0.5, 1.0),
+                        ..default()
+                    },
+                ),
+                MenuItem(MAIN_MENU_KEY),
+            ));
+        });
+    scoreboard.score = 0;
+}
+
+fn display_pause_overlay(mut commands: Commands, bindings: Res<KeyBindings>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                ..default()
+            },
+            PauseOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused".to_string(),
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            for (label, key) in [
+                (
+                    format!("Resume ({} or tap)", key_name(bindings.confirm)),
+                    bindings.confirm,
+                ),
+                ("Photo mode (C)".to_string(), KeyCode::KeyC),
+                (format!("Help ({})", key_name(bindings.help)), bindings.help),
+                (
+                    format!("Save and quit ({})", key_name(SAVE_AND_QUIT_KEY)),
+                    SAVE_AND_QUIT_KEY,
+                ),
+            ] {
+                parent.spawn((
+                    TextBundle::from_section(
+                        label,
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::rgb(0.5, 0.5, 1.0),
+                            ..default()
+                        },
+                    ),
+                    MenuItem(key),
+                ));
+            }
+        });
+}
+
+fn pause_on_suspend(
+    mut lifetime_events: EventReader<ApplicationLifetime>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in lifetime_events.read() {
+        if matches!(event, ApplicationLifetime::Suspended) && *state.get() == GameState::Playing {
+            next_state.set(GameState::Paused);
+        }
+    }
+}
+
+fn pause_input(actions: ActionInput, mut next_state: ResMut<NextState<GameState>>) {
+    if actions.just_pressed(GameAction::Pause) {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn resume_input(
+    actions: ActionInput,
+    touches: Res<Touches>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(GameAction::Pause)
+        || actions.just_pressed(GameAction::Restart)
+        || touches.any_just_pressed()
+    {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn gameover_keyboard_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: ActionInput,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(GameAction::Restart) {
+        next_state.set(GameState::Playing);
+    } else if keyboard_input.just_pressed(MAIN_MENU_KEY) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+// gameplay runs on virtual time, so stopping its clock holds the snake, the
+// hazards and every other timer of the run where they are
+fn start_resume_countdown(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    countdowns: Query<Entity, With<ResumeCountdown>>,
+) {
+    for countdown in &countdowns {
+        commands.entity(countdown).despawn_recursive();
+    }
+    time.pause();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ResumeCountdown(Timer::from_seconds(
+                COUNTDOWN_FROM as f32 * COUNTDOWN_STEP_SECONDS,
+                TimerMode::Once,
+            )),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                COUNTDOWN_FROM.to_string(),
+                TextStyle {
+                    font_size: 120.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn tick_resume_countdown(
+    mut commands: Commands,
+    real_time: Res<Time<Real>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut countdowns: Query<(Entity, &mut ResumeCountdown, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, mut countdown, children) in &mut countdowns {
+        if countdown.0.tick(real_time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            time.unpause();
+            continue;
+        }
+        let step = (countdown.0.remaining_secs() / COUNTDOWN_STEP_SECONDS).ceil() as u32;
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                let digit = step.to_string();
+                if text.sections[0].value != digit {
+                    text.sections[0].value = digit;
+                }
+            }
+        }
+    }
+}
+
+// pausing again, or the run ending, mid-countdown
+fn end_resume_countdown(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    countdowns: Query<Entity, With<ResumeCountdown>>,
+) {
+    for countdown in &countdowns {
+        commands.entity(countdown).despawn_recursive();
+        time.unpause();
+    }
+}
@@ -0,0 +1,551 @@
+use std::collections::HashSet;
+
+use bevy::{math::bounding::Aabb2d, prelude::*};
+use rand::seq::SliceRandom;
+
+use crate::{
+    apple_spawn_at,
+    arena::{
+        apple_spawn_free, occupied_boxes, spawn_on_apple_collisions, GridPosition, WallThickness,
+    },
+    collision::{
+        sync_collision_grid, ColliderKind, CollisionEvent, CollisionGrid, DetectCollisions,
+    },
+    display_final_score, move_snake, new_run,
+    powerups::{ActiveEffect, EffectSpeed},
+    rules::{GameRules, SnakeStart},
+    settings::GameSettings,
+    setup, sim,
+    sprint::{Sprinting, Stamina},
+    teardown_game_screen, Apple, Collider, Ending, GameAssets, GameRng, GameState,
+    OnGameOverScreen, OnGameScreen, RunEnd, SafeArea, Scoreboard, Snake, SnakeBodySegment,
+    SnakeHead, SnakeSegment, SEGMENT_SIZE,
+};
+
+const PLAYER_TWO_HEAD_COLOR: Color = Color::rgb(0.3, 0.6, 1.0);
+const PLAYER_TWO_BODY_COLOR: Color = Color::rgb(0.2, 0.4, 0.8);
+const RESULT_COLOR: Color = Color::rgb(1.0, 0.84, 0.0);
+
+/// Local two-player mode, picked on the main menu: a second, blue snake
+/// shares the arena with the first, steered on its own keys from the
+/// `KeyBindings`, WASD unless the first player has them. It starts mirrored
+/// across the center and carries its own `Snake`, score, stamina and
+/// power-up effect, so the same systems move, speed up and feed both, and
+/// its score shows under the first's. Apples come in pairs mirrored across
+/// the center, so neither side is closer to food. A head running into the other snake
+/// loses the round, both when they meet head on; the second snake also loses
+/// to walls, poison and, when the rules say so, its own body. The game-over
+/// screen names the winner.
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Versus>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_versus.after(setup).run_if(new_run),
+            )
+            .add_systems(
+                Update,
+                (
+                    detect_versus_collisions
+                        .after(move_snake)
+                        .after(sync_collision_grid)
+                        .before(DetectCollisions),
+                    eat_player_two_apples.after(spawn_on_apple_collisions),
+                    mirror_apples.after(eat_player_two_apples),
+                    update_player_two_score,
+                )
+                    .run_if(in_state(GameState::Playing).and_then(versus_active)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    decide_winner
+                        .before(teardown_game_screen)
+                        .before(display_final_score),
+                    display_versus_result.after(display_final_score),
+                )
+                    .run_if(versus_active),
+            );
+    }
+}
+
+/// Which player a snake's head belongs to, 1 or 2.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerId(pub u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Winner(PlayerId),
+    Draw,
+}
+
+/// The two-player round being played, if the run is one.
+#[derive(Resource, Debug, Default)]
+pub struct Versus {
+    active: bool,
+    // both kept from before the game-over screen clears the board
+    player_one_score: u32,
+    player_two_score: u32,
+    outcome: Option<Outcome>,
+}
+
+impl Versus {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+#[derive(Component)]
+struct PlayerTwoSegment;
+
+// the apple across the center from this one; eating either takes both
+#[derive(Component)]
+struct MirroredApple(Entity);
+
+#[derive(Component)]
+struct PlayerTwoScoreText;
+
+fn versus_active(versus: Res<Versus>) -> bool {
+    versus.active
+}
+
+fn player_two_sprite(cell: GridPosition, color: Color) -> SpriteBundle {
+    SpriteBundle {
+        transform: Transform::from_translation(cell.translation().extend(0.0)),
+        sprite: Sprite {
+            color,
+            custom_size: Some(SEGMENT_SIZE),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+// colliders like the rest of the board, so apples and items keep off it
+fn spawn_player_two_segment(commands: &mut Commands, cell: GridPosition) -> SnakeSegment {
+    let entity = commands
+        .spawn((
+            player_two_sprite(cell, PLAYER_TWO_BODY_COLOR),
+            cell,
+            PlayerTwoSegment,
+            Collider,
+            ColliderKind::Rival,
+            OnGameScreen,
+        ))
+        .id();
+    let position = cell.translation();
+    SnakeSegment {
+        x: position.x,
+        y: position.y,
+        entity: Some(entity),
+    }
+}
+
+fn start_versus(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    safe_area: Res<SafeArea>,
+    mut versus: ResMut<Versus>,
+    player_one: Query<Entity, With<SnakeHead>>,
+) {
+    *versus = Versus {
+        active: settings.modifiers.two_player,
+        ..default()
+    };
+    if !versus.active {
+        return;
+    }
+    if let Ok(head) = player_one.get_single() {
+        commands.entity(head).insert(PlayerId(1));
+    }
+
+    // mirrored across the arena's center, so neither starts with an edge
+    let start = SnakeStart {
+        cell: (-rules.start.cell.0, -rules.start.cell.1),
+        facing: rules.start.facing.opposite(),
+        ..rules.start
+    };
+    let mut snake = Snake::at(&start);
+    snake.body = snake
+        .body
+        .iter()
+        .map(|segment| {
+            spawn_player_two_segment(
+                &mut commands,
+                GridPosition::containing(Vec2::new(segment.x, segment.y)),
+            )
+        })
+        .collect();
+    let head = GridPosition::containing(start.position());
+    commands.spawn((
+        player_two_sprite(head, PLAYER_TWO_HEAD_COLOR),
+        head,
+        PlayerId(2),
+        snake,
+        Scoreboard::default(),
+        Stamina::default(),
+        Sprinting::default(),
+        ActiveEffect::default(),
+        EffectSpeed::default(),
+        Collider,
+        ColliderKind::Rival,
+        OnGameScreen,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "Player 2: 0",
+            TextStyle {
+                font_size: 30.0,
+                color: PLAYER_TWO_HEAD_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            // under the first player's score
+            top: Val::Px(44.0 + safe_area.top),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        PlayerTwoScoreText,
+        OnGameScreen,
+    ));
+}
+
+// once the first player's apples are replaced, so an apple both heads reach
+// on the same tick is only eaten, and replaced, once, by the first player
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn eat_player_two_apples(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    wall_thickness: Res<WallThickness>,
+    game_assets: Res<GameAssets>,
+    mut rng: ResMut<GameRng>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut player_two: Query<(&mut Snake, &mut Scoreboard, &GridPosition)>,
+    apples: Query<(Entity, &Transform), With<Apple>>,
+    cells: Query<&GridPosition>,
+    colliders: Query<&Transform, (With<Collider>, Without<Apple>)>,
+) {
+    let taken: Vec<Entity> = collision_events
+        .read()
+        .filter(|event| event.kind == ColliderKind::Apple)
+        .map(|event| event.collider)
+        .collect();
+    let Ok((mut snake, mut scoreboard, head)) = player_two.get_single_mut() else {
+        return;
+    };
+    let Some((apple, _)) = apples.iter().find(|(entity, transform)| {
+        !taken.contains(entity)
+            && GridPosition::containing(transform.translation.truncate()) == *head
+    }) else {
+        return;
+    };
+    commands.entity(apple).despawn();
+    scoreboard.score += 1;
+
+    // new segments wait on the tail until the body trails away from them
+    let tail = snake
+        .body
+        .back()
+        .and_then(|segment| segment.entity)
+        .and_then(|segment| cells.get(segment).ok())
+        .copied()
+        .unwrap_or(*head);
+    for _ in 0..rules.growth_per_apple {
+        let segment = spawn_player_two_segment(&mut commands, tail);
+        snake.body.push_back(segment);
+    }
+
+    let occupied: Vec<Aabb2d> = cells
+        .iter()
+        .map(|cell| Aabb2d::new(cell.translation(), SEGMENT_SIZE / 2.0))
+        .chain(colliders.iter().map(|transform| {
+            Aabb2d::new(
+                transform.translation.truncate(),
+                transform.scale.truncate() / 2.0,
+            )
+        }))
+        .chain(
+            apples
+                .iter()
+                .filter(|(entity, _)| *entity != apple)
+                .map(|(_, transform)| {
+                    Aabb2d::new(
+                        transform.translation.truncate(),
+                        settings.hitboxes.apple_half_size(),
+                    )
+                }),
+        )
+        .collect();
+    apple_spawn_free(
+        &mut commands,
+        &game_assets,
+        &mut rng,
+        wall_thickness.0,
+        &occupied,
+    );
+}
+
+/// The `free` positions, as cells, whose mirror across the arena's center is
+/// a different free cell, in the order they're given.
+pub fn mirrored_pairs(free: &[Vec2]) -> Vec<IVec2> {
+    let cells: Vec<IVec2> = free
+        .iter()
+        .map(|&position| GridPosition::containing(position).0)
+        .collect();
+    let open: HashSet<IVec2> = cells.iter().copied().collect();
+    cells
+        .into_iter()
+        .filter(|&cell| cell != -cell && open.contains(&-cell))
+        .collect()
+}
+
+// however an apple turned up, one rolled cell decides both sides
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn mirror_apples(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    game_assets: Res<GameAssets>,
+    mut rng: ResMut<GameRng>,
+    mut apples: Query<(Entity, &mut Transform, Option<&MirroredApple>), With<Apple>>,
+    snake: Query<&GridPosition, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    colliders: Query<(&Transform, &ColliderKind), Without<Apple>>,
+) {
+    // the other half of an eaten pair goes with it
+    let mut removed = Vec::new();
+    for (entity, _, twin) in &apples {
+        if twin.is_some_and(|twin| !apples.contains(twin.0)) {
+            commands.entity(entity).despawn();
+            removed.push(entity);
+        }
+    }
+    let unpaired: Vec<Entity> = apples
+        .iter()
+        .filter(|(_, _, twin)| twin.is_none())
+        .map(|(entity, _, _)| entity)
+        .collect();
+    if unpaired.is_empty() {
+        return;
+    }
+
+    let mut occupied = occupied_boxes(&snake, &colliders, &settings.hitboxes);
+    occupied.extend(
+        apples
+            .iter()
+            .filter(|(entity, _, twin)| twin.is_some() && !removed.contains(entity))
+            .map(|(_, transform, _)| {
+                Aabb2d::new(
+                    transform.translation.truncate(),
+                    settings.hitboxes.apple_half_size(),
+                )
+            }),
+    );
+    for apple in unpaired {
+        let free = sim::free_cells(&occupied, wall_thickness.0);
+        let pairs = mirrored_pairs(&free);
+        let Ok((_, mut transform, _)) = apples.get_mut(apple) else {
+            continue;
+        };
+        let mut cell = GridPosition::containing(transform.translation.truncate()).0;
+        if !pairs.contains(&cell) {
+            // moved to a cell whose mirror is open too
+            let Some(&open) = pairs.choose(&mut rng.0) else {
+                continue;
+            };
+            cell = open;
+            let position = GridPosition(cell).translation();
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+
+        let position = GridPosition(-cell).translation();
+        let twin = apple_spawn_at(&mut commands, &game_assets, position.extend(-2.0));
+        commands.entity(apple).insert(MirroredApple(twin));
+        commands.entity(twin).insert(MirroredApple(apple));
+        for cell in [cell, -cell] {
+            occupied.push(Aabb2d::new(
+                GridPosition(cell).translation(),
+                SEGMENT_SIZE / 2.0,
+            ));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn detect_versus_collisions(
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    collision_grid: Res<CollisionGrid>,
+    mut versus: ResMut<Versus>,
+    mut run_end: ResMut<RunEnd>,
+    mut next_state: ResMut<NextState<GameState>>,
+    player_one_head: Query<&GridPosition, With<SnakeHead>>,
+    player_one_body: Query<&GridPosition, With<SnakeBodySegment>>,
+    player_two_head: Query<&GridPosition, With<Snake>>,
+    player_two_body: Query<&GridPosition, With<PlayerTwoSegment>>,
+) {
+    let (Ok(&one), Ok(&two)) = (player_one_head.get_single(), player_two_head.get_single()) else {
+        return;
+    };
+    let one_body: Vec<IVec2> = player_one_body.iter().map(|cell| cell.0).collect();
+    let two_body: Vec<IVec2> = player_two_body.iter().map(|cell| cell.0).collect();
+
+    let head_on = one == two;
+    let one_lost = head_on || two_body.contains(&one.0);
+    let hitbox = settings.hitboxes.lethal_head() * rules.head_hitbox_scale;
+    let two_crashed = [ColliderKind::Wall, ColliderKind::Poison]
+        .into_iter()
+        .any(|kind| {
+            collision_grid
+                .first_hit(Aabb2d::new(two.translation(), hitbox / 2.0), kind)
+                .is_some()
+        });
+    let two_lost = head_on
+        || one_body.contains(&two.0)
+        || two_crashed
+        || (rules.self_collision_lethal && sim::hits_own_body(two.0, &two_body).is_some());
+
+    versus.outcome = match (one_lost, two_lost) {
+        (true, true) => Some(Outcome::Draw),
+        (true, false) => Some(Outcome::Winner(PlayerId(2))),
+        (false, true) => Some(Outcome::Winner(PlayerId(1))),
+        (false, false) => return,
+    };
+    run_end.end(Ending::Died(ColliderKind::Rival));
+    next_state.set(GameState::GameOver);
+}
+
+fn update_player_two_score(
+    player_two: Query<&Scoreboard, (With<Snake>, Changed<Scoreboard>)>,
+    mut texts: Query<&mut Text, With<PlayerTwoScoreText>>,
+) {
+    let Ok(scoreboard) = player_two.get_single() else {
+        return;
+    };
+    for mut text in &mut texts {
+        text.sections[0].value = format!("Player 2: {}", scoreboard.score);
+    }
+}
+
+fn decide_winner(
+    run_end: Res<RunEnd>,
+    scoreboard: Res<Scoreboard>,
+    player_two: Query<&Scoreboard, With<Snake>>,
+    mut versus: ResMut<Versus>,
+) {
+    // coming back from the credits or help re-enters game over without a new run
+    if run_end.unrecorded().is_none() {
+        return;
+    }
+    versus.player_one_score = scoreboard.score;
+    versus.player_two_score = player_two
+        .get_single()
+        .map_or(0, |scoreboard| scoreboard.score);
+    // anything else that ended the round got the first player
+    versus.outcome.get_or_insert(Outcome::Winner(PlayerId(2)));
+}
+
+fn display_versus_result(mut commands: Commands, versus: Res<Versus>) {
+    let Some(outcome) = versus.outcome else {
+        return;
+    };
+    let headline = match outcome {
+        Outcome::Winner(PlayerId(player)) => format!("Player {player} wins!"),
+        Outcome::Draw => "Draw!".to_string(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(18.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                headline,
+                TextStyle {
+                    font_size: 48.0,
+                    color: RESULT_COLOR,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Player 1: {}   Player 2: {}",
+                    versus.player_one_score, versus.player_two_score
+                ),
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        headless::{HeadlessPlugin, SnakeCommand},
+        Direction,
+    };
+
+    #[test]
+    fn snakes_meeting_head_on_draw() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HeadlessPlugin { seed: 7 }));
+        app.world
+            .resource_mut::<GameSettings>()
+            .modifiers
+            .two_player = true;
+        app.update();
+
+        let mut player_two = app.world.query_filtered::<&mut Snake, With<PlayerId>>();
+        assert_eq!(player_two.iter(&app.world).count(), 1);
+        let mut player_one = app.world.query_filtered::<&PlayerId, With<SnakeHead>>();
+        assert_eq!(player_one.single(&app.world), &PlayerId(1));
+        let mut apples = app.world.query_filtered::<&Transform, With<Apple>>();
+        let cells: Vec<IVec2> = apples
+            .iter(&app.world)
+            .map(|transform| GridPosition::containing(transform.translation.truncate()).0)
+            .collect();
+        assert!(!cells.is_empty());
+        assert!(cells.iter().all(|cell| cells.contains(&-*cell)));
+
+        // the starts mirror each other, so both step in toward the center
+        app.world.send_event(SnakeCommand::Left);
+        app.world.send_event(SnakeCommand::Down);
+        let mut snake = player_two.single_mut(&mut app.world);
+        snake.queue_turn(Direction::Right);
+        snake.queue_turn(Direction::Up);
+        while *app.world.resource::<State<GameState>>() == GameState::Playing {
+            app.update();
+        }
+
+        let versus = app.world.resource::<Versus>();
+        assert_eq!(versus.outcome, Some(Outcome::Draw));
+        assert_eq!(
+            app.world.resource::<RunEnd>().ending,
+            Some(Ending::Died(ColliderKind::Rival))
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::{settings::GameSettings, GameState, OnGameScreen};
+
+const FLASH_SECONDS: f32 = 0.6;
+const FLASH_WIDTH: f32 = 14.0;
+const FLASH_COLOR: Color = Color::rgb(1.0, 0.25, 0.2);
+const PULSE_SECONDS: f32 = 1.5;
+// the marker beats this many times while it grows and fades
+const PULSE_BEATS: f32 = 3.0;
+const PULSE_SIZE: f32 = 24.0;
+const PULSE_GROWTH: f32 = 1.5;
+const PULSE_COLOR: Color = Color::rgb(1.0, 0.84, 0.0);
+const CAPTION_SECONDS: f32 = 3.0;
+
+/// Visual cues, toggled from the settings file: the moments a player would
+/// otherwise have to hear are shown as well. A hazard warning flashes the
+/// screen edges, a bonus fruit pulses where it landed and announcer lines
+/// run in a caption strip. Gameplay systems send a `Cue` at each of those
+/// moments, whether or not the cues are shown.
+pub struct VisualCuesPlugin;
+
+impl Plugin for VisualCuesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Cue>()
+            .add_systems(
+                Update,
+                show_cues
+                    .run_if(|settings: Res<GameSettings>| settings.accessibility.visual_cues)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, (fade_edge_flash, pulse_bonus, expire_caption));
+    }
+}
+
+/// A moment in play that would come with a sound.
+#[derive(Event, Debug, Clone)]
+pub enum Cue {
+    /// Something dangerous is about to happen.
+    HazardWarning,
+    /// A bonus fruit just appeared at this spot in the arena.
+    BonusSpawned(Vec2),
+    /// An announcer line.
+    Callout(String),
+}
+
+#[derive(Component)]
+struct EdgeFlash(Timer);
+
+#[derive(Component)]
+struct BonusPulse(Timer);
+
+#[derive(Component)]
+struct Caption(Timer);
+
+fn show_cues(
+    mut commands: Commands,
+    mut cues: EventReader<Cue>,
+    flashes: Query<Entity, With<EdgeFlash>>,
+    captions: Query<Entity, With<Caption>>,
+) {
+    for cue in cues.read() {
+        match cue {
+            Cue::HazardWarning => {
+                for flash in &flashes {
+                    commands.entity(flash).despawn_recursive();
+                }
+                commands.spawn(edge_flash());
+            }
+            Cue::BonusSpawned(position) => {
+                commands.spawn(bonus_pulse(*position));
+            }
+            Cue::Callout(line) => {
+                for caption in &captions {
+                    commands.entity(caption).despawn_recursive();
+                }
+                spawn_caption(&mut commands, line);
+            }
+        }
+    }
+}
+
+fn edge_flash() -> impl Bundle {
+    (
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                border: UiRect::all(Val::Px(FLASH_WIDTH)),
+                ..default()
+            },
+            border_color: FLASH_COLOR.into(),
+            // above a blackout, which is one of the hazards it warns about
+            z_index: ZIndex::Global(6),
+            ..default()
+        },
+        EdgeFlash(Timer::from_seconds(FLASH_SECONDS, TimerMode::Once)),
+        OnGameScreen,
+    )
+}
+
+fn bonus_pulse(position: Vec2) -> impl Bundle {
+    (
+        SpriteBundle {
+            // under the bonus itself
+            transform: Transform::from_translation(position.extend(-2.5)),
+            sprite: Sprite {
+                color: PULSE_COLOR,
+                custom_size: Some(Vec2::splat(PULSE_SIZE)),
+                ..default()
+            },
+            ..default()
+        },
+        BonusPulse(Timer::from_seconds(PULSE_SECONDS, TimerMode::Once)),
+        OnGameScreen,
+    )
+}
+
+fn spawn_caption(commands: &mut Commands, line: &str) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(76.0),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    padding: UiRect::vertical(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(6),
+                ..default()
+            },
+            Caption(Timer::from_seconds(CAPTION_SECONDS, TimerMode::Once)),
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                line,
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn fade_edge_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut EdgeFlash, &mut BorderColor)>,
+) {
+    for (entity, mut flash, mut border) in &mut flashes {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        border.0 = FLASH_COLOR.with_a(1.0 - flash.0.fraction());
+    }
+}
+
+fn pulse_bonus(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pulses: Query<(Entity, &mut BonusPulse, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut pulse, mut transform, mut sprite) in &mut pulses {
+        if pulse.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = pulse.0.fraction();
+        let beat = (progress * PULSE_BEATS).fract();
+        transform.scale = Vec3::splat(1.0 + beat * PULSE_GROWTH);
+        sprite.color = PULSE_COLOR.with_a((1.0 - beat) * (1.0 - progress));
+    }
+}
+
+fn expire_caption(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut captions: Query<(Entity, &mut Caption)>,
+) {
+    for (entity, mut caption) in &mut captions {
+        if caption.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
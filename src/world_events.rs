@@ -0,0 +1,237 @@
+use bevy::{math::bounding::Aabb2d, prelude::*};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    seq::SliceRandom,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    apple_spawn_free,
+    arena::{occupied_boxes, GridPosition, WallThickness},
+    collision::ColliderKind,
+    rules::GameRules,
+    settings::GameSettings,
+    sim,
+    toast::Toast,
+    visual_cues::Cue,
+    GameAssets, GameRng, GameState, OnGameScreen, SnakeBodySegment, SnakeHead, SEGMENT_SIZE,
+};
+
+const WARNING_SECONDS: f32 = 2.0;
+const APPLE_RAIN_APPLES: usize = 8;
+const BLACKOUT_SECONDS: f32 = 5.0;
+
+/// Director that now and then warns about, then triggers, an arena-wide event.
+pub struct WorldEventsPlugin;
+
+impl Plugin for WorldEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventDirector>()
+            .add_event::<WorldEventStarted>()
+            .add_systems(OnExit(GameState::GameOver), reset_director)
+            .add_systems(
+                Update,
+                (
+                    direct_world_events
+                        .run_if(|settings: Res<GameSettings>| settings.world_events.enabled),
+                    start_world_events,
+                    lift_blackout,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Relative odds of each event; zero disables it.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WorldEventWeights {
+    pub apple_rain: u32,
+    pub earthquake: u32,
+    pub blackout: u32,
+}
+
+impl Default for WorldEventWeights {
+    fn default() -> Self {
+        WorldEventWeights {
+            apple_rain: 3,
+            earthquake: 2,
+            blackout: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum WorldEvent {
+    AppleRain,
+    // apples and poison shuffle to new spots
+    Earthquake,
+    Blackout,
+}
+
+impl WorldEvent {
+    fn warning(self) -> &'static str {
+        match self {
+            WorldEvent::AppleRain => "Apple rain incoming!",
+            WorldEvent::Earthquake => "Earthquake incoming!",
+            WorldEvent::Blackout => "The lights are about to go out!",
+        }
+    }
+
+    fn callout(self) -> &'static str {
+        match self {
+            WorldEvent::AppleRain => "Apple rain!",
+            WorldEvent::Earthquake => "Earthquake!",
+            WorldEvent::Blackout => "Blackout!",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct EventDirector {
+    // `None` until the first frame reads the interval from the settings
+    next: Option<Timer>,
+    pending: Option<(WorldEvent, Timer)>,
+}
+
+/// Starts an event right away, skipping the warning.
+#[derive(Event)]
+pub struct WorldEventStarted(pub WorldEvent);
+
+#[derive(Component)]
+struct Blackout(Timer);
+
+fn reset_director(mut director: ResMut<EventDirector>) {
+    *director = EventDirector::default();
+}
+
+fn pick_event(weights: &WorldEventWeights, rng: &mut GameRng) -> Option<WorldEvent> {
+    let events = [
+        (WorldEvent::AppleRain, weights.apple_rain),
+        (WorldEvent::Earthquake, weights.earthquake),
+        (WorldEvent::Blackout, weights.blackout),
+    ];
+    let index = WeightedIndex::new(events.iter().map(|(_, weight)| *weight)).ok()?;
+    Some(events[index.sample(&mut rng.0)].0)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn direct_world_events(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    rules: Res<GameRules>,
+    mut rng: ResMut<GameRng>,
+    mut director: ResMut<EventDirector>,
+    mut toasts: EventWriter<Toast>,
+    mut started: EventWriter<WorldEventStarted>,
+    mut cues: EventWriter<Cue>,
+) {
+    if let Some((event, warning)) = director.pending.as_mut() {
+        if warning.tick(time.delta()).just_finished() {
+            started.send(WorldEventStarted(*event));
+            director.pending = None;
+        }
+        return;
+    }
+
+    let interval = settings.world_events.interval_seconds;
+    let next = director
+        .next
+        .get_or_insert_with(|| Timer::from_seconds(interval, TimerMode::Repeating));
+    if !next.tick(time.delta()).just_finished() {
+        return;
+    }
+    let mut weights = settings.world_events.weights.clone();
+    // apple rain is the only free handout the director has
+    if !rules.power_ups {
+        weights.apple_rain = 0;
+    }
+    if let Some(event) = pick_event(&weights, &mut rng) {
+        toasts.send(Toast(event.warning().to_string()));
+        cues.send(Cue::HazardWarning);
+        director.pending = Some((event, Timer::from_seconds(WARNING_SECONDS, TimerMode::Once)));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn start_world_events(
+    mut started: EventReader<WorldEventStarted>,
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    wall_thickness: Res<WallThickness>,
+    game_assets: Res<GameAssets>,
+    mut rng: ResMut<GameRng>,
+    snake: Query<&GridPosition, Or<(With<SnakeHead>, With<SnakeBodySegment>)>>,
+    mut colliders: Query<(&mut Transform, &ColliderKind)>,
+    mut cues: EventWriter<Cue>,
+) {
+    let shaken = |kind: &ColliderKind| matches!(kind, ColliderKind::Apple | ColliderKind::Poison);
+    for &WorldEventStarted(event) in started.read() {
+        info!("world event: {event:?}");
+        cues.send(Cue::Callout(event.callout().to_string()));
+        match event {
+            WorldEvent::AppleRain => {
+                let mut occupied = occupied_boxes(&snake, &colliders, &settings.hitboxes);
+                for _ in 0..APPLE_RAIN_APPLES {
+                    let Some(cell) = apple_spawn_free(
+                        &mut commands,
+                        &game_assets,
+                        &mut rng,
+                        wall_thickness.0,
+                        &occupied,
+                    ) else {
+                        break;
+                    };
+                    occupied.push(Aabb2d::new(cell, SEGMENT_SIZE / 2.0));
+                }
+            }
+            WorldEvent::Earthquake => {
+                // onto free cells of their own, around everything that stays put
+                let occupied = occupied_boxes(
+                    &snake,
+                    colliders.iter().filter(|(_, kind)| !shaken(kind)),
+                    &settings.hitboxes,
+                );
+                let mut cells = sim::free_cells(&occupied, wall_thickness.0);
+                cells.shuffle(&mut rng.0);
+                let moved = colliders.iter_mut().filter(|(_, kind)| shaken(kind));
+                for ((mut transform, _), cell) in moved.zip(cells) {
+                    transform.translation.x = cell.x;
+                    transform.translation.y = cell.y;
+                }
+            }
+            WorldEvent::Blackout => {
+                commands.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Percent(100.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        background_color: Color::rgba(0.0, 0.0, 0.0, 0.92).into(),
+                        // above the arena and HUD, below the console
+                        z_index: ZIndex::Global(5),
+                        ..default()
+                    },
+                    Blackout(Timer::from_seconds(BLACKOUT_SECONDS, TimerMode::Once)),
+                    OnGameScreen,
+                ));
+            }
+        }
+    }
+}
+
+fn lift_blackout(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut blackouts: Query<(Entity, &mut Blackout)>,
+) {
+    for (entity, mut blackout) in &mut blackouts {
+        if blackout.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
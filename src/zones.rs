@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+
+use crate::{
+    arena::WallThickness, settings::GameSettings, sim, GameRng, GameState, OnGameScreen, SafeArea,
+    Scoreboard, SnakeHead, SEGMENT_SIZE,
+};
+
+const ZONE_MOVE_SECONDS: f32 = 15.0;
+const ZONE_TICK_SECONDS: f32 = 0.5;
+const ZONE_BONUS_PER_TICK: u32 = 1;
+// zone side length in cells; odd so it has a center cell
+const ZONE_CELLS: i32 = 5;
+const ZONE_COLOR: Color = Color::rgba(1.0, 0.9, 0.2, 0.25);
+
+/// King-of-the-hill modifier: a zone that relocates every few seconds and
+/// pays bonus points for every tick the head spends inside it.
+pub struct KingOfTheHillPlugin;
+
+impl Plugin for KingOfTheHillPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HillZone>().add_systems(
+            Update,
+            (
+                spawn_zone
+                    .run_if(|settings: Res<GameSettings>| settings.modifiers.king_of_the_hill),
+                (move_zone, score_zone, glow_zone, update_zone_timer),
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HillZone {
+    center: IVec2,
+    relocate: Timer,
+    bonus_tick: Timer,
+}
+
+#[derive(Component)]
+struct ZoneTimerText;
+
+fn cell_of(position: Vec2) -> IVec2 {
+    (position / SEGMENT_SIZE).round().as_ivec2()
+}
+
+fn random_zone_center(rng: &mut GameRng, wall_thickness: f32) -> IVec2 {
+    // keep the whole zone off the walls
+    let inset = wall_thickness + SEGMENT_SIZE.x * (ZONE_CELLS / 2) as f32;
+    cell_of(sim::random_apple_position(&mut rng.0, inset))
+}
+
+fn zone_translation(center: IVec2) -> Vec3 {
+    (center.as_vec2() * SEGMENT_SIZE).extend(-3.0)
+}
+
+fn spawn_zone(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    wall_thickness: Res<WallThickness>,
+    safe_area: Res<SafeArea>,
+    zones: Query<(), With<HillZone>>,
+) {
+    if !zones.is_empty() {
+        return;
+    }
+
+    let center = random_zone_center(&mut rng, wall_thickness.0);
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform {
+                translation: zone_translation(center),
+                scale: (SEGMENT_SIZE * ZONE_CELLS as f32).extend(1.0),
+                ..default()
+            },
+            sprite: Sprite {
+                color: ZONE_COLOR,
+                ..default()
+            },
+            ..default()
+        },
+        HillZone {
+            center,
+            relocate: Timer::from_seconds(ZONE_MOVE_SECONDS, TimerMode::Repeating),
+            bonus_tick: Timer::from_seconds(ZONE_TICK_SECONDS, TimerMode::Repeating),
+        },
+        OnGameScreen,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: ZONE_COLOR.with_a(1.0),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(50.0 + safe_area.top),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ZoneTimerText,
+        OnGameScreen,
+    ));
+}
+
+fn move_zone(
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+    wall_thickness: Res<WallThickness>,
+    mut zones: Query<(&mut HillZone, &mut Transform)>,
+) {
+    for (mut zone, mut transform) in &mut zones {
+        if zone.relocate.tick(time.delta()).just_finished() {
+            zone.center = random_zone_center(&mut rng, wall_thickness.0);
+            transform.translation = zone_translation(zone.center);
+        }
+    }
+}
+
+fn score_zone(
+    time: Res<Time>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut zones: Query<&mut HillZone>,
+    snake_heads: Query<&Transform, With<SnakeHead>>,
+) {
+    let Ok(head) = snake_heads.get_single() else {
+        return;
+    };
+    let head_cell = cell_of(head.translation.truncate());
+
+    for mut zone in &mut zones {
+        let ticks = zone
+            .bonus_tick
+            .tick(time.delta())
+            .times_finished_this_tick();
+        let offset = (head_cell - zone.center).abs();
+        if offset.max_element() <= ZONE_CELLS / 2 {
+            scoreboard.score += ticks * ZONE_BONUS_PER_TICK;
+        }
+    }
+}
+
+fn glow_zone(time: Res<Time>, mut zones: Query<&mut Sprite, With<HillZone>>) {
+    let pulse = 0.5 + 0.5 * (time.elapsed_seconds() * 3.0).sin();
+    for mut sprite in &mut zones {
+        sprite.color.set_a(ZONE_COLOR.a() * (0.6 + 0.8 * pulse));
+    }
+}
+
+fn update_zone_timer(zones: Query<&HillZone>, mut texts: Query<&mut Text, With<ZoneTimerText>>) {
+    let Ok(zone) = zones.get_single() else {
+        return;
+    };
+    let remaining = zone.relocate.remaining_secs().ceil();
+    for mut text in &mut texts {
+        text.sections[0].value = format!("Zone moves in {remaining:.0}s");
+    }
+}